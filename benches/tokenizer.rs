@@ -0,0 +1,66 @@
+//! Benchmarks of the lazy tokenizer iterators against the `Vec`-building passes
+//!
+//! $ cargo bench --bench tokenizer
+//!
+//! To compare with an earlier llamapun, save its run with `-- --save-baseline <name>`, then run
+//! this benchmark with `-- --baseline <name>`
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use libxml::parser::Parser;
+use llamapun::dnm::{DNMParameters, DNM};
+use llamapun::tokenizer::Tokenizer;
+
+fn tokenizer_benchmark(c: &mut Criterion) {
+  let parser = Parser::default_html();
+  let doc = parser
+    .parse_file("tests/resources/0903.1000.html")
+    .expect("benchmark resource should parse.");
+  let dnm = DNM::new(
+    doc.get_root_readonly().unwrap(),
+    DNMParameters::llamapun_normalization(),
+  );
+  let tokenizer = Tokenizer::default();
+
+  c.bench_function("sentences (Vec)", |b| {
+    b.iter(|| tokenizer.sentences(black_box(&dnm)).len())
+  });
+  c.bench_function("sentence_iter (lazy)", |b| {
+    b.iter(|| tokenizer.sentence_iter(black_box(&dnm)).count())
+  });
+
+  let sentences = tokenizer.sentences(&dnm);
+  c.bench_function("words (Vec)", |b| {
+    b.iter(|| {
+      sentences
+        .iter()
+        .map(|sentence| tokenizer.words(black_box(sentence)).len())
+        .sum::<usize>()
+    })
+  });
+  c.bench_function("word_iter (lazy)", |b| {
+    b.iter(|| {
+      sentences
+        .iter()
+        .map(|sentence| tokenizer.word_iter(black_box(sentence)).count())
+        .sum::<usize>()
+    })
+  });
+  c.bench_function("words_and_punct (Vec)", |b| {
+    b.iter(|| {
+      sentences
+        .iter()
+        .map(|sentence| tokenizer.words_and_punct(black_box(sentence)).len())
+        .sum::<usize>()
+    })
+  });
+  c.bench_function("word_and_punct_iter (lazy)", |b| {
+    b.iter(|| {
+      sentences
+        .iter()
+        .map(|sentence| tokenizer.word_and_punct_iter(black_box(sentence)).count())
+        .sum::<usize>()
+    })
+  });
+}
+
+criterion_group!(benches, tokenizer_benchmark);
+criterion_main!(benches);
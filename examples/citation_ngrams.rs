@@ -9,7 +9,6 @@ extern crate llamapun;
 
 use llamapun::ngrams::Ngrams;
 use serde::Serialize;
-use std::collections::HashMap;
 use std::env;
 use std::error::Error;
 use std::fs::File;
@@ -29,7 +28,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     n: 4,
     window_size: 15,
     anchor: Some("citationelement".to_string()),
-    counts: HashMap::new(),
+    ..Ngrams::default()
   };
 
   let mut input_args = env::args();
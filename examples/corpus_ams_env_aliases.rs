@@ -0,0 +1,71 @@
+// Copyright 2015-2019 KWARC research group. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+
+//! Given a `CorTeX` corpus of HTML5 documents, report the `ltx_theorem_<env>` environment suffixes
+//! left unmapped by the AMS environment aliases, with their frequencies, as candidates for new
+//! alias rules. The built-in aliases can be extended with a rule file (see `ams::env_mapping`):
+//! ```text
+//! cargo run --release --example corpus_ams_env_aliases /data/arxmliv/ unmapped_envs.csv [rules.tsv]
+//! ```
+use llamapun::ams::env_mapping::EnvMapping;
+use llamapun::ngrams::counting::Counter;
+use llamapun::parallel_data::Corpus;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::time::Instant;
+
+static BUFFER_CAPACITY: usize = 10_485_760;
+
+#[derive(Debug, Serialize)]
+struct EnvRecord<'a> {
+  env: &'a str,
+  frequency: usize,
+}
+
+pub fn main() -> Result<(), Box<dyn Error>> {
+  let start = Instant::now();
+  let mut input_args = env::args();
+  let _ = input_args.next(); // skip process name
+  let corpus_path = match input_args.next() {
+    Some(path) => path,
+    None => "tests/resources/".to_string(),
+  };
+  let report_filename = match input_args.next() {
+    Some(path) => path,
+    None => "unmapped_ams_envs.csv".to_string(),
+  };
+  let mapping = match input_args.next() {
+    Some(rules_path) => EnvMapping::with_rules_file(&rules_path)?,
+    None => EnvMapping::default(),
+  };
+
+  let corpus = Corpus::new(corpus_path);
+  let unmapped = corpus.fold_with_parallel_walk(
+    HashMap::<String, usize>::new,
+    |document, counts| mapping.count_unmapped(&document.dom, counts),
+    Counter::merge,
+  );
+
+  let report_file = File::create(report_filename)?;
+  let buffered_writer = BufWriter::with_capacity(BUFFER_CAPACITY, report_file);
+  let mut csv_writer = csv::Writer::from_writer(buffered_writer);
+  let ranked = unmapped.top(unmapped.len());
+  for (env, frequency) in ranked.iter() {
+    csv_writer.serialize(EnvRecord {
+      env,
+      frequency: *frequency,
+    })?;
+  }
+  csv_writer.flush()?;
+  println!(
+    "-- found {} unmapped AMS environments in {:?}s",
+    ranked.len(),
+    start.elapsed().as_secs()
+  );
+  Ok(())
+}
@@ -0,0 +1,98 @@
+// Copyright 2015-2019 KWARC research group. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+
+//! Given a `CorTeX` corpus of HTML5 documents, rank the multiword terms and collocations of its
+//! paragraphs by each association measure, writing one CSV report per measure, e.g.
+//! ```text
+//! cargo run --release --example corpus_collocations /data/arxmliv/ collocations
+//! ```
+use llamapun::ngrams::collocation::{Collocations, Measure};
+use llamapun::parallel_data::Corpus;
+use llamapun::stopwords;
+use serde::Serialize;
+use std::env;
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::time::Instant;
+
+static BUFFER_CAPACITY: usize = 10_485_760;
+/// The longest ranked phrase, in words
+static MAX_N: usize = 4;
+/// The number of top ranked phrases written for each measure
+static REPORT_SIZE: usize = 10_000;
+
+#[derive(Debug, Serialize)]
+struct CollocationRecord<'a> {
+  phrase: &'a str,
+  frequency: usize,
+  score: f64,
+}
+
+pub fn main() -> Result<(), Box<dyn Error>> {
+  let start = Instant::now();
+  let mut input_args = env::args();
+  let _ = input_args.next(); // skip process name
+  let corpus_path = match input_args.next() {
+    Some(path) => path,
+    None => "tests/resources/".to_string(),
+  };
+  let report_prefix = match input_args.next() {
+    Some(prefix) => prefix,
+    None => "collocations".to_string(),
+  };
+
+  let corpus = Corpus::new(corpus_path);
+  let mut collocations = corpus.fold_with_parallel_walk(
+    || Collocations::new(2, MAX_N),
+    |mut document, collocations| {
+      for mut sentence in document.sentence_iter() {
+        // formulas, citations and references break the word sequences, rather than forming phrases
+        let mut words = Vec::new();
+        for word in sentence.word_iter() {
+          let word = word.range.get_plaintext();
+          if word.chars().all(char::is_alphabetic) && word.chars().any(char::is_lowercase) {
+            words.push(word.to_lowercase());
+          } else {
+            collocations.add_words(&words.iter().map(String::as_str).collect::<Vec<_>>());
+            words.clear();
+          }
+        }
+        collocations.add_words(&words.iter().map(String::as_str).collect::<Vec<_>>());
+      }
+    },
+    Collocations::merge,
+  );
+  collocations.stopwords = Some(stopwords::english());
+  println!(
+    "-- counted {} words in {:?}s",
+    collocations.word_count(),
+    start.elapsed().as_secs()
+  );
+
+  for (measure, name) in [
+    (Measure::Pmi, "pmi"),
+    (Measure::TScore, "t_score"),
+    (Measure::LogLikelihood, "log_likelihood"),
+    (Measure::CValue, "c_value"),
+    (Measure::NcValue, "nc_value"),
+  ] {
+    let report_file = File::create(format!("{}_{}.csv", report_prefix, name))?;
+    let buffered_writer = BufWriter::with_capacity(BUFFER_CAPACITY, report_file);
+    let mut csv_writer = csv::Writer::from_writer(buffered_writer);
+    for collocation in collocations.rank(measure).iter().take(REPORT_SIZE) {
+      csv_writer.serialize(CollocationRecord {
+        phrase: &collocation.phrase,
+        frequency: collocation.count,
+        score: collocation.score,
+      })?;
+    }
+    csv_writer.flush()?;
+  }
+  println!(
+    "-- collocation ranking finished in {:?}s",
+    start.elapsed().as_secs()
+  );
+  Ok(())
+}
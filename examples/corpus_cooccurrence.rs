@@ -0,0 +1,78 @@
+// Copyright 2015-2019 KWARC research group. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+
+//! Given a `CorTeX` corpus of HTML5 documents, build a GloVe vocabulary and co-occurrence matrix
+//! natively, in two parallel passes, ready for GloVe's `shuffle` and `glove` tools, e.g.
+//! ```text
+//! cargo run --release --example corpus_cooccurrence /data/arxmliv/ vocab.txt cooccurrence.bin
+//! ```
+use llamapun::cooccurrence::{self, Cooccurrences, Scope};
+use llamapun::ngrams::counting::Counter;
+use llamapun::parallel_data::Corpus;
+use llamapun::vocabulary::{Vocabulary, VocabularyFormat};
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Words seen fewer times are left out of the vocabulary, as with GloVe's `vocab_count -min-count`
+static MIN_COUNT: usize = 5;
+
+pub fn main() -> Result<(), Box<dyn Error>> {
+  let start = Instant::now();
+  let mut input_args = env::args();
+  let _ = input_args.next(); // skip process name
+  let corpus_path = match input_args.next() {
+    Some(path) => path,
+    None => "tests/resources/".to_string(),
+  };
+  let vocabulary_path = match input_args.next() {
+    Some(path) => path,
+    None => "vocab.txt".to_string(),
+  };
+  let cooccurrence_path = match input_args.next() {
+    Some(path) => path,
+    None => "cooccurrence.bin".to_string(),
+  };
+  let corpus = Corpus::new(corpus_path);
+
+  let word_counts = corpus.fold_with_parallel_walk(
+    HashMap::<String, usize>::new,
+    |document, counts| {
+      for words in cooccurrence::document_words(&document, Scope::Paragraph, true) {
+        for word in words {
+          counts.add(&word, 1);
+        }
+      }
+    },
+    Counter::merge,
+  );
+  let mut vocabulary = Vocabulary::new();
+  for (word, count) in word_counts {
+    vocabulary.add_count(&word, count);
+  }
+  vocabulary.prune(MIN_COUNT, 1.0);
+  vocabulary.save(&vocabulary_path, VocabularyFormat::Glove)?;
+  println!(
+    "-- vocabulary of {} words finished in {:?}s",
+    vocabulary.len(),
+    start.elapsed().as_secs()
+  );
+
+  let mut template = Cooccurrences::new(Arc::new(vocabulary));
+  template.collapse_math = true;
+  let cooccurrences = corpus.fold_with_parallel_walk(
+    || template.empty_clone(),
+    |document, counts| counts.add_document(&document),
+    Cooccurrences::merge,
+  );
+  cooccurrences.save_glove(&cooccurrence_path)?;
+  println!(
+    "-- {} co-occurring pairs finished in {:?}s",
+    cooccurrences.len(),
+    start.elapsed().as_secs()
+  );
+  Ok(())
+}
@@ -71,11 +71,12 @@ pub fn main() -> Result<(), Error> {
     thread_counts.insert(String::from("total_document_count"), 1);
 
     let mut context = Context::new(&document.dom).unwrap();
+    let language = document.language().unwrap_or_default();
 
     'headings: for mut heading in document.heading_iter() {
-      // Before we go into tokenization, ensure this is an English sentence on the math-normalized
-      // plain text.
-      if data_helpers::invalid_for_english_latin(&heading.dnm) {
+      // Before we go into tokenization, ensure this is a sentence in one of the languages with
+      // heading normalization tables, on the math-normalized plain text.
+      if data_helpers::invalid_for_heading_languages(&heading.dnm) {
         continue 'headings;
       }
       let mut heading_buffer = String::new();
@@ -103,8 +104,8 @@ pub fn main() -> Result<(), Error> {
       }
       // If heading was valid and contains text, record it
       if !invalid_heading {
-        // simplify/normalize to standard names
-        heading_buffer = data_helpers::normalize_heading_title(&heading_buffer);
+        // simplify/normalize to standard names, in the document language
+        heading_buffer = data_helpers::normalize_heading_title_in(&heading_buffer, language);
         if !heading_buffer.is_empty() {
           heading_count += 1;
           let this_heading_counter = thread_counts.entry(heading_buffer).or_insert(0);
@@ -0,0 +1,82 @@
+// Copyright 2015-2019 KWARC research group. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+
+//! Given a `CorTeX` corpus of HTML5 documents, pair the proofs with the statements they prove,
+//! writing a JSON Lines dataset of statement and proof texts, e.g. for premise selection and proof
+//! generation experiments
+//! ```text
+//! cargo run --release --example corpus_proof_pairs /data/arxmliv/ proof_pairs.jsonl [0.8]
+//! ```
+use llamapun::ams::AmsEnv;
+use llamapun::parallel_data::proof_link::{self, ProofPair};
+use llamapun::parallel_data::{Corpus, Statement, StatementOptions};
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+static BUFFER_CAPACITY: usize = 10_485_760;
+
+pub fn main() -> Result<(), Box<dyn Error>> {
+  let start = Instant::now();
+  let mut input_args = env::args();
+  let _ = input_args.next(); // skip process name
+  let corpus_path = match input_args.next() {
+    Some(path) => path,
+    None => "tests/resources/".to_string(),
+  };
+  let dataset_path = match input_args.next() {
+    Some(path) => path,
+    None => "proof_pairs.jsonl".to_string(),
+  };
+  // the pairs by adjacency alone are the noisiest, and left out by default
+  let min_confidence = match input_args.next() {
+    Some(value) => value.parse()?,
+    None => 0.8,
+  };
+
+  let dataset = Arc::new(Mutex::new(BufWriter::with_capacity(
+    BUFFER_CAPACITY,
+    File::create(dataset_path)?,
+  )));
+  let corpus = Corpus::new(corpus_path);
+  let catalog = corpus.catalog_with_parallel_walk(|document| {
+    let statements: Vec<Statement> = document
+      .statement_iter(StatementOptions::default())
+      .collect();
+    let links = proof_link::link_proofs(&statements);
+    let pairs = ProofPair::from_links(&statements, &links, min_confidence);
+
+    let mut counts = HashMap::new();
+    counts.insert(String::from("document_count"), 1);
+    counts.insert(
+      String::from("proof_count"),
+      statements
+        .iter()
+        .filter(|statement| statement.env == AmsEnv::Proof)
+        .count() as u64,
+    );
+    counts.insert(String::from("pair_count"), pairs.len() as u64);
+    let mut dataset_lock = dataset.lock().unwrap();
+    for pair in pairs {
+      pair
+        .write_json_line(&mut *dataset_lock)
+        .expect("writing the dataset should always succeed.");
+    }
+    counts
+  });
+  dataset.lock().unwrap().flush()?;
+
+  println!(
+    "-- paired {:?} of {:?} proofs, from {:?} documents, in {:?}s",
+    catalog.get("pair_count").unwrap_or(&0),
+    catalog.get("proof_count").unwrap_or(&0),
+    catalog.get("document_count").unwrap_or(&0),
+    start.elapsed().as_secs()
+  );
+  Ok(())
+}
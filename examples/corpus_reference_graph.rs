@@ -0,0 +1,72 @@
+// Copyright 2015-2019 KWARC research group. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+
+//! Given a `CorTeX` corpus of HTML5 documents, build the cross-reference graph of every document,
+//! saving it next to the document in the requested format (dot, graphml or json), and report the
+//! statements most relied on by proofs
+//! ```text
+//! cargo run --release --example corpus_reference_graph /data/arxmliv/ json
+//! ```
+use llamapun::ams::AmsEnv;
+use llamapun::parallel_data::reference_graph::{GraphFormat, LabelKind};
+use llamapun::parallel_data::Corpus;
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::time::Instant;
+
+pub fn main() -> Result<(), Box<dyn Error>> {
+  let start = Instant::now();
+  let mut input_args = env::args();
+  let _ = input_args.next(); // skip process name
+  let corpus_path = match input_args.next() {
+    Some(path) => path,
+    None => "tests/resources/".to_string(),
+  };
+  let (format, extension) = match input_args.next().as_deref() {
+    Some("dot") => (GraphFormat::Dot, "dot"),
+    Some("graphml") => (GraphFormat::GraphMl, "graphml"),
+    Some("json") | None => (GraphFormat::Json, "json"),
+    Some(other) => return Err(format!("unknown graph format {other:?}").into()),
+  };
+
+  let corpus = Corpus::new(corpus_path);
+  let catalog = corpus.catalog_with_parallel_walk(|document| {
+    let graph = document.reference_graph();
+    graph
+      .save(&format!("{}.refs.{}", document.path, extension), format)
+      .expect("saving the reference graph should always succeed.");
+
+    let mut counts = HashMap::new();
+    counts.insert(String::from("document_count"), 1);
+    counts.insert(String::from("reference_count"), graph.edges.len() as u64);
+    counts.insert(String::from("unresolved_count"), graph.unresolved as u64);
+    for edge in &graph.edges {
+      let (source, target) = (&graph.nodes[edge.source], &graph.nodes[edge.target]);
+      if let (LabelKind::Statement(AmsEnv::Proof), LabelKind::Statement(env)) =
+        (source.kind, target.kind)
+      {
+        *counts.entry(format!("proof_relies_on_{env}")).or_insert(0) += 1;
+      }
+    }
+    counts
+  });
+
+  let mut reliance: Vec<(&String, &u64)> = catalog
+    .iter()
+    .filter(|(key, _)| key.starts_with("proof_relies_on_"))
+    .collect();
+  reliance.sort_by(|a, b| b.1.cmp(a.1));
+  for (key, count) in reliance {
+    println!("{}: {}", key.trim_start_matches("proof_relies_on_"), count);
+  }
+  println!(
+    "-- resolved {:?} references, with {:?} unresolved, in {:?} documents, in {:?}s",
+    catalog.get("reference_count").unwrap_or(&0),
+    catalog.get("unresolved_count").unwrap_or(&0),
+    catalog.get("document_count").unwrap_or(&0),
+    start.elapsed().as_secs()
+  );
+  Ok(())
+}
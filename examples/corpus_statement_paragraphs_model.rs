@@ -20,7 +20,7 @@ use crypto::digest::Digest;
 use crypto::sha2::Sha256;
 use libxml::xpath::Context;
 use llamapun::ams;
-use llamapun::ams::{AmsEnv, StructuralEnv};
+use llamapun::ams::StructuralEnv;
 use llamapun::dnm::SpecialTagsOption;
 use llamapun::parallel_data::*;
 use llamapun::util::data_helpers;
@@ -93,7 +93,8 @@ pub fn main() -> Result<(), Error> {
     catalog.get("paragraph_count").unwrap_or(&0)
   );
   println!(
-    "{:?} discarded paragraphs (irregular word count or word length)",
+    "{:?} discarded paragraphs (irregular word count or word length, unsupported language or \
+     duplicate statements)",
     catalog.get("overflow_count").unwrap_or(&0)
   );
   let mut builder_lock = tar_builder.lock().unwrap();
@@ -126,15 +127,29 @@ fn extract_document_statements(
   let mut thread_counts = HashMap::new();
   thread_counts.insert(String::from("total_document_count"), 1);
   // Check if document contains AMS markup
-  let has_ams_markup = ams::has_markup_xmldoc(&document.dom);
-  if has_ams_markup {
+  if ams::has_markup_xmldoc(&document.dom) {
     thread_counts.insert(String::from("ams_document_count"), 1);
   }
   let mut context = Context::new(&document.dom).unwrap();
+  let language = document.language().unwrap_or_default();
+
+  // I. The first paragraphs of the AMS statements, already validated for basic data quality
+  let mut statements = document.statement_iter(StatementOptions::paragraph_model(discard_math));
+  for statement in statements.by_ref() {
+    if let Some(words) = statement.words {
+      let mut paragraph_buffer = words.join(" ");
+      paragraph_buffer.push_str(" \n");
+      paragraph_count += 1;
+      // precompute sha inside the thread, to do more in parallel
+      let paragraph_filename = hash_file_path(&statement.env.to_string(), &paragraph_buffer);
+      thread_data.push((paragraph_buffer, paragraph_filename));
+    }
+  }
+  overflow_count += statements.discarded() as u64;
 
   'paragraphs: for mut paragraph in document.extended_paragraph_iter() {
-    // I. Determine the class for this paragraph entry, so that we can iterate over its content
-    // after if no markup at all, ignore the paragraph and skip to next
+    // II. Determine the class for the remaining paragraph entries, so that we can iterate over
+    // their content after. If no markup at all, ignore the paragraph and skip to next
     let para = paragraph.dnm.root_node;
     let para_parent = para.get_parent().unwrap();
     let mut prev_heading_opt = paragraph.dnm.root_node.get_prev_sibling();
@@ -150,7 +165,7 @@ fn extract_document_statements(
       }
     }
     let para_class = para.get_attribute("class").unwrap_or_default();
-    // Check if we are looking at the two current special markup casesthread::spawn(move || {
+    // Check if we are looking at the two current special markup cases
     // div.ltx_acknowledgement
     let special_marker = if para_class.contains("ltx_acknowledgement") {
       Some(StructuralEnv::Acknowledgement)
@@ -161,73 +176,42 @@ fn extract_document_statements(
       if !prev_name.is_empty() && !prev_name.starts_with('h') {
         continue 'paragraphs;
       }
+      // AMS statements were already recorded above
+      let parent_class = para_parent.get_attribute("class").unwrap_or_default();
+      if ams::class_to_env(&parent_class).is_some() {
+        continue 'paragraphs;
+      }
       None
     };
-    // Before we go into tokenization, ensure this is an English paragraph
-    if data_helpers::invalid_for_english_latin(&paragraph.dnm) {
+    // Before we go into tokenization, ensure this paragraph is in one of the languages with heading
+    // normalization tables
+    if data_helpers::invalid_for_heading_languages(&paragraph.dnm) {
       continue 'paragraphs;
     }
     let class_directory = if let Some(env) = special_marker {
       // specific element markup is an override to heading siblings
       env.to_string()
-    } else {
-      // set ams class, if any
-      let ams_class = if has_ams_markup {
-        let parent_class = para_parent.get_attribute("class").unwrap_or_default();
-        ams::class_to_env(&parent_class)
-      } else {
-        None
-      };
-      if let Some(env) = ams_class {
-        match env {
-          // Other and other-like entities that are too noisy to include
-          // New for 2019: ignore the low-volume cases as well
-          AmsEnv::Affirmation
-          | AmsEnv::Algorithm
-          | AmsEnv::Answer
-          | AmsEnv::Bound
-          | AmsEnv::Caption
-          | AmsEnv::Comment
-          | AmsEnv::Constraint
-          | AmsEnv::Convention
-          | AmsEnv::Criterion
-          | AmsEnv::Expansion
-          | AmsEnv::Expectation
-          | AmsEnv::Explanation
-          | AmsEnv::Hint
-          | AmsEnv::Issue
-          | AmsEnv::Keywords
-          | AmsEnv::Note
-          | AmsEnv::Notice
-          | AmsEnv::Paragraph
-          | AmsEnv::Principle
-          | AmsEnv::Rule
-          | AmsEnv::Solution
-          | AmsEnv::Other => continue 'paragraphs,
-          _ => env.to_string(),
-        }
-      } else if let Some(heading_node) = prev_heading_opt {
-        // if no AMS markup found, check for structural markup
-        if let Some(heading_text) = data_helpers::heading_from_node_aux(
-          heading_node,
-          &document.corpus.tokenizer,
-          &mut context,
-        ) {
-          let env: StructuralEnv = heading_text.as_str().into();
-          if env == StructuralEnv::Other {
-            // if Other markup, ignore
-            continue 'paragraphs;
-          }
-          env.to_string()
-        } else {
+    } else if let Some(heading_node) = prev_heading_opt {
+      // if no AMS markup found, check for structural markup
+      if let Some(heading_text) = data_helpers::heading_from_node_aux_in(
+        heading_node,
+        &document.corpus.tokenizer,
+        &mut context,
+        language,
+      ) {
+        let env: StructuralEnv = heading_text.as_str().into();
+        if env == StructuralEnv::Other {
+          // if Other markup, ignore
           continue 'paragraphs;
         }
+        env.to_string()
       } else {
         continue 'paragraphs;
       }
+    } else {
+      continue 'paragraphs;
     };
-    // II. We have a labeled statement. Extract content of current paragraph, validating basic data
-    // quality
+    // III. We have a labeled paragraph. Extract its content, validating basic data quality
     let mut word_count = 0;
     let mut invalid_paragraph = false;
     let mut paragraph_buffer = String::new();
@@ -273,7 +257,7 @@ fn extract_document_statements(
       thread_data.push((paragraph_buffer, paragraph_filename));
     }
   }
-  // III. Record valid entries into archive target, having collected all labeled samples for this
+  // IV. Record valid entries into archive target, having collected all labeled samples for this
   // document
   let mut builder_lock = tar_builder.lock().unwrap();
   for (paragraph_buffer, paragraph_filename) in thread_data.into_iter() {
@@ -281,7 +265,7 @@ fn extract_document_statements(
       .save(&paragraph_buffer, &paragraph_filename)
       .expect("Tar builder should always succeed.")
   }
-  // IV. Bookkeep counts for final report and finish this document
+  // V. Bookkeep counts for final report and finish this document
   thread_counts.insert(String::from("paragraph_count"), paragraph_count);
   thread_counts.insert(String::from("overflow_count"), overflow_count);
   thread_counts
@@ -0,0 +1,63 @@
+// Copyright 2015-2019 KWARC research group. See the LICENSE
+// file at the top-level directory of this distribution.
+//
+
+//! Given a `CorTeX` corpus of HTML5 documents, train a classifier of the structural environments of
+//! sections from their headings and first paragraphs, using the sections with cleanly mapped
+//! headings as labelled examples. The classifier is cross-validated over the folds of the corpus,
+//! evaluating on the content words of the held out sections, then trained on every document and
+//! saved, as the built-in model of llamapun is (see `structural_classifier::DEFAULT_MODEL`)
+//! ```text
+//! cargo run --release --example corpus_structural_classifier /data/arxmliv/ structural_model.tsv
+//! ```
+use llamapun::ams::structural_classifier::{Evaluation, StructuralClassifier, TrainingOptions};
+use llamapun::parallel_data::Corpus;
+use std::env;
+use std::error::Error;
+use std::time::Instant;
+
+pub fn main() -> Result<(), Box<dyn Error>> {
+  let start = Instant::now();
+  let mut input_args = env::args();
+  let _ = input_args.next(); // skip process name
+  let corpus_path = match input_args.next() {
+    Some(path) => path,
+    None => "tests/resources/".to_string(),
+  };
+  let model_path = match input_args.next() {
+    Some(path) => path,
+    None => "structural_model.tsv".to_string(),
+  };
+
+  let corpus = Corpus::new(corpus_path);
+  let options = TrainingOptions::default();
+  let mut evaluation = Evaluation::default();
+  for fold in 0..options.holdout {
+    let fold_options = TrainingOptions { fold, ..options };
+    let classifier = StructuralClassifier::train(&corpus, fold_options);
+    evaluation.merge(classifier.evaluate(&corpus, fold_options));
+  }
+  println!(
+    "-- cross-validated on {:?} sections over {:?} folds: accuracy {:.3}, macro F1 {:.3}",
+    evaluation.total(),
+    options.holdout,
+    evaluation.accuracy(),
+    evaluation.macro_f1()
+  );
+
+  let classifier = StructuralClassifier::train(
+    &corpus,
+    TrainingOptions {
+      holdout: 0,
+      ..options
+    },
+  );
+  classifier.save(&model_path)?;
+  println!(
+    "-- trained on {:?} environments, with {:?} features, in {:?}s",
+    classifier.class_count(),
+    classifier.vocabulary_size(),
+    start.elapsed().as_secs()
+  );
+  Ok(())
+}
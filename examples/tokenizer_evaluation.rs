@@ -0,0 +1,43 @@
+//! Evaluate the default sentence and word tokenization against a gold standard, e.g.
+//! `cargo run --release --example tokenizer_evaluation gold.tsv`
+extern crate llamapun;
+
+use std::env;
+
+use llamapun::dnm::{DNMParameters, DNM};
+use llamapun::tokenizer::evaluation::{evaluate, GoldStandard, Level};
+use llamapun::tokenizer::Tokenizer;
+
+fn main() {
+  let mut input_args = env::args();
+  let _ = input_args.next(); // skip process name
+  let gold_path = input_args
+    .next()
+    .expect("usage: tokenizer_evaluation <gold standard file>");
+  let gold = GoldStandard::load(&gold_path).unwrap();
+  let parameters = DNMParameters::llamapun_normalization();
+  let tokenizer = Tokenizer::default();
+
+  let sentences = evaluate(&gold, Level::Sentence, &parameters, |dnm| {
+    tokenizer.sentences(dnm)
+  })
+  .unwrap();
+  println!("-- sentences:\n{sentences}");
+
+  for (name, quantities) in [("words and punctuation", false), ("with quantities", true)] {
+    let tokens = evaluate(&gold, Level::Token, &parameters, |dnm: &DNM| {
+      tokenizer
+        .sentence_iter(dnm)
+        .flat_map(|sentence| {
+          if quantities {
+            tokenizer.words_punct_and_quantities(&sentence)
+          } else {
+            tokenizer.word_and_punct_iter(&sentence).collect()
+          }
+        })
+        .collect()
+    })
+    .unwrap();
+    println!("-- tokens, {name}:\n{tokens}");
+  }
+}
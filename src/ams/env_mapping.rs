@@ -0,0 +1,191 @@
+//! A data-driven mapping of the `ltx_theorem_<env>` class suffixes of author-defined AMS
+//! environments to environment labels.
+//!
+//! The built-in table (`DEFAULT_ALIASES`) is the one behind `ams::normalize_env`, and can be
+//! extended, or replaced, with rules loaded from a data file, without changing the code. A rule
+//! file has a "<pattern> <label>" rule per line, separated by tabs or spaces, with `#` comments:
+//! - a plain pattern is an exact suffix, e.g. `lemma`
+//! - a pattern ending in `*` is a prefix, e.g. `lem*`
+//! - a pattern between slashes is a regular expression, e.g. `/^lem+a?\d*$/`
+//!
+//! Exact suffixes take precedence over prefixes, the longest of which applies, which in turn take
+//! precedence over regular expressions, in the order they were added. Labels other than the names
+//! of `AmsEnv` variants are kept as custom labels, and normalized to `AmsEnv::Other`.
+use super::AmsEnv;
+use crate::ngrams::counting::Counter;
+use libxml::tree::Document as XmlDoc;
+use libxml::xpath::Context;
+use regex::Regex;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+
+/// The built-in rules, in the rule file format
+pub const DEFAULT_ALIASES: &str = include_str!("env_aliases.tsv");
+
+/// A mapping of AMS environment suffixes to environment labels
+#[derive(Debug, Clone)]
+pub struct EnvMapping {
+  /// the labels of exact suffixes
+  exact: HashMap<String, String>,
+  /// the labels of suffix prefixes, longest first
+  prefixes: Vec<(String, String)>,
+  /// the labels of suffix patterns, in the order they were added
+  patterns: Vec<(Regex, String)>,
+}
+
+impl Default for EnvMapping {
+  fn default() -> EnvMapping {
+    let mut mapping = EnvMapping::new();
+    mapping
+      .add_rules(DEFAULT_ALIASES)
+      .expect("the built-in AMS environment aliases are valid");
+    mapping
+  }
+}
+
+impl EnvMapping {
+  /// an empty mapping, without the built-in rules
+  pub fn new() -> Self {
+    EnvMapping {
+      exact: HashMap::new(),
+      prefixes: Vec::new(),
+      patterns: Vec::new(),
+    }
+  }
+
+  /// the built-in mapping, extended by the rules of a file, which override built-in exact suffixes
+  pub fn with_rules_file(path: &str) -> Result<Self, Box<dyn Error>> {
+    let mut mapping = EnvMapping::default();
+    mapping.load(path)?;
+    Ok(mapping)
+  }
+
+  /// add the rules of a file
+  pub fn load(&mut self, path: &str) -> Result<(), Box<dyn Error>> {
+    let rules = fs::read_to_string(path)?;
+    self.add_rules(&rules)
+  }
+
+  /// Add the rules of a rule file's contents. Fails on the first invalid rule, keeping the rules
+  /// before it
+  pub fn add_rules(&mut self, rules: &str) -> Result<(), Box<dyn Error>> {
+    for (index, line) in rules.lines().enumerate() {
+      let line = line.trim();
+      if line.is_empty() || line.starts_with('#') {
+        continue;
+      }
+      let (pattern, label) = match line.rsplit_once(char::is_whitespace) {
+        Some((pattern, label)) => (pattern.trim_end(), label),
+        None => return Err(format!("line {}: rule without a label: {:?}", index + 1, line).into()),
+      };
+      if pattern.len() > 1 && pattern.starts_with('/') && pattern.ends_with('/') {
+        if let Err(e) = self.add_pattern(&pattern[1..pattern.len() - 1], label) {
+          return Err(format!("line {}: {}", index + 1, e).into());
+        }
+      } else if let Some(prefix) = pattern.strip_suffix('*') {
+        self.add_prefix(prefix, label);
+      } else {
+        self.add_alias(pattern, label);
+      }
+    }
+    Ok(())
+  }
+
+  /// map an exact suffix to a label, replacing its previous label
+  pub fn add_alias(&mut self, suffix: &str, label: &str) {
+    self.exact.insert(suffix.to_string(), label.to_string());
+  }
+
+  /// map the suffixes starting with a prefix to a label, replacing the previous label of the prefix
+  pub fn add_prefix(&mut self, prefix: &str, label: &str) {
+    self.prefixes.retain(|(existing, _)| existing != prefix);
+    let position = self
+      .prefixes
+      .iter()
+      .position(|(existing, _)| existing.len() < prefix.len())
+      .unwrap_or(self.prefixes.len());
+    self
+      .prefixes
+      .insert(position, (prefix.to_string(), label.to_string()));
+  }
+
+  /// map the suffixes matching a regular expression to a label
+  pub fn add_pattern(&mut self, pattern: &str, label: &str) -> Result<(), regex::Error> {
+    self
+      .patterns
+      .push((Regex::new(pattern)?, label.to_string()));
+    Ok(())
+  }
+
+  /// the label of an environment suffix, if mapped
+  pub fn label(&self, env: &str) -> Option<&str> {
+    if let Some(label) = self.exact.get(env) {
+      return Some(label);
+    }
+    if let Some((_, label)) = self
+      .prefixes
+      .iter()
+      .find(|(prefix, _)| env.starts_with(prefix.as_str()))
+    {
+      return Some(label);
+    }
+    self
+      .patterns
+      .iter()
+      .find(|(pattern, _)| pattern.is_match(env))
+      .map(|(_, label)| label.as_str())
+  }
+
+  /// the `AmsEnv` of an environment suffix, which is `AmsEnv::Other` for unmapped suffixes and
+  /// custom labels
+  pub fn normalize(&self, env: &str) -> AmsEnv {
+    self.label(env).map(AmsEnv::from).unwrap_or(AmsEnv::Other)
+  }
+
+  /// As `ams::class_to_env`, the label of a latexml-produced HTML class, such as
+  /// "ltx_theorem ltx_theorem_lemma", or "other" for unmapped suffixes. Returns None if there is
+  /// no AMS markup
+  pub fn class_label(&self, class: &str) -> Option<&str> {
+    if class.is_empty() {
+      None
+    } else if !class.contains("ltx_theorem") {
+      if class == "ltx_proof" {
+        Some("proof")
+      } else {
+        None
+      }
+    } else {
+      match super::class_env_suffix(class) {
+        None => Some("theorem"),
+        Some(env) => Some(self.label(env).unwrap_or("other")),
+      }
+    }
+  }
+
+  /// the number of rules
+  pub fn len(&self) -> usize { self.exact.len() + self.prefixes.len() + self.patterns.len() }
+
+  /// checks if there are no rules
+  pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+  /// Count the unmapped environment suffixes of the AMS markup of a document, e.g. to find the
+  /// aliases worth adding from a corpus run via `Corpus::fold_with_parallel_walk`
+  pub fn count_unmapped<C: Counter>(&self, dom: &XmlDoc, counter: &mut C) {
+    let xpath_context = match Context::new(dom) {
+      Ok(context) => context,
+      Err(_) => return,
+    };
+    let nodes = match xpath_context.evaluate("//*[contains(@class,'ltx_theorem_')]") {
+      Ok(found_payload) => found_payload.get_nodes_as_vec(),
+      _ => return,
+    };
+    for node in nodes {
+      if let Some(env) = super::class_env_suffix(&node.get_property("class").unwrap_or_default()) {
+        if self.label(env).is_none() {
+          counter.add(env, 1);
+        }
+      }
+    }
+  }
+}
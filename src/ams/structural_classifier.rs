@@ -0,0 +1,502 @@
+//! A multinomial naive Bayes classifier of the `StructuralEnv` of document sections, from the words
+//! of their headings and first paragraphs, for the sections whose headings do not map cleanly, e.g.
+//! "Our approach" or "Empirical findings".
+//!
+//! The built-in model (`DEFAULT_MODEL`) is trained with `StructuralClassifier::train`, on the
+//! heading and content words of the sections of the four arXMLiv papers of tests/resources, whose
+//! headings map cleanly. That is only 13 labelled sections: cross-validated over 10 folds of those
+//! papers, on content words, its accuracy is 0.000, and it knows no example of most environments.
+//! It is a starting point until a model is trained over a full corpus, e.g. with the
+//! corpus_structural_classifier example, and saved in the same model file format:
+//! - `smoothing <TAB> <value>`, the additive smoothing of the model, 1 by default
+//! - `examples <TAB> <env> <TAB> <count>`, the number of training sections of an environment
+//! - `feature <TAB> <env> <TAB> <feature> <TAB> <count>`, the count of a feature in its sections
+//!
+//! with `#` comments. Heading words are prefixed with `h:`, to tell them apart from content words.
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::{BufWriter, Write};
+
+use super::StructuralEnv;
+use crate::dnm::{DNMParameters, DNM};
+use crate::parallel_data::{Corpus, Document};
+use crate::stopwords;
+
+/// The built-in model, in the model file format
+pub const DEFAULT_MODEL: &str = include_str!("structural_model.tsv");
+
+/// The prefix of heading word features
+pub const HEADING_PREFIX: &str = "h:";
+
+static MAX_WORD_LENGTH: usize = 25;
+
+/// The words of a text, as features: lowercased alphabetic words of two or more letters, without
+/// English stopwords
+pub fn words(text: &str) -> Vec<String> {
+  let stopwords = stopwords::english();
+  text
+    .split(|c: char| !c.is_alphanumeric())
+    .map(str::to_lowercase)
+    .filter(|word| {
+      (2..=MAX_WORD_LENGTH).contains(&word.len())
+        && word.chars().all(char::is_alphabetic)
+        && !stopwords.contains(word)
+    })
+    .collect()
+}
+
+/// A section of a document, with its heading and content features
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionExample {
+  /// the environment of the heading, `StructuralEnv::Other` if it does not map cleanly
+  pub env: StructuralEnv,
+  /// the title of the heading
+  pub heading: String,
+  /// the heading words, prefixed by `HEADING_PREFIX`, followed by the words of the first paragraphs
+  pub features: Vec<String>,
+}
+
+impl SectionExample {
+  /// Collect the sections of a document, with the words of at most `paragraph_count` of their
+  /// own paragraphs, i.e. outside of their subsections
+  pub fn from_document(document: &Document, paragraph_count: usize) -> Vec<Self> {
+    let outline = document.outline();
+    let mut examples = Vec::new();
+    for (index, section) in outline.sections.iter().enumerate() {
+      let mut features: Vec<String> = words(&section.title)
+        .into_iter()
+        .map(|word| format!("{HEADING_PREFIX}{word}"))
+        .collect();
+      let paragraphs =
+        Document::dfs_filter_nodes(section.node, &|n| n.get_class_names().contains("ltx_para"));
+      for paragraph in paragraphs
+        .into_iter()
+        .filter(|paragraph| outline.section_of(*paragraph) == Some(index))
+        .take(paragraph_count)
+      {
+        let dnm = DNM::new(paragraph, DNMParameters::llamapun_normalization());
+        features.extend(words(&dnm.plaintext));
+      }
+      examples.push(SectionExample {
+        env: section.env,
+        heading: section.title.clone(),
+        features,
+      });
+    }
+    examples
+  }
+
+  /// the content features, without the heading words
+  pub fn content_features(&self) -> Vec<String> {
+    self
+      .features
+      .iter()
+      .filter(|feature| !feature.starts_with(HEADING_PREFIX))
+      .cloned()
+      .collect()
+  }
+}
+
+/// Options for training and evaluating a classifier over a corpus
+#[derive(Debug, Clone, Copy)]
+pub struct TrainingOptions {
+  /// the number of paragraphs of a section to take content words from
+  pub paragraph_count: usize,
+  /// one in every `holdout` documents, by path hash, is held out of training for evaluation
+  pub holdout: u64,
+  /// the held out documents are those of this fold, below `holdout`, e.g. to cross-validate over
+  /// every fold
+  pub fold: u64,
+  /// evaluate on the content words only, as the headings of labelled sections map cleanly
+  pub mask_headings: bool,
+}
+
+impl Default for TrainingOptions {
+  fn default() -> Self {
+    TrainingOptions {
+      paragraph_count: 2,
+      holdout: 10,
+      fold: 0,
+      mask_headings: true,
+    }
+  }
+}
+
+impl TrainingOptions {
+  /// Checks if a document is held out of training, for evaluation
+  pub fn is_held_out(&self, path: &str) -> bool {
+    if self.holdout == 0 {
+      return false;
+    }
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    hasher.finish() % self.holdout == self.fold
+  }
+}
+
+/// A multinomial naive Bayes classifier of structural environments
+#[derive(Debug, Clone)]
+pub struct StructuralClassifier {
+  /// the additive (Laplace) smoothing of feature counts
+  pub smoothing: f64,
+  /// the number of training examples of every environment
+  examples: HashMap<StructuralEnv, usize>,
+  /// the feature counts of every environment
+  features: HashMap<StructuralEnv, HashMap<String, usize>>,
+  /// the total feature count of every environment
+  totals: HashMap<StructuralEnv, usize>,
+  /// every feature seen in training
+  vocabulary: HashSet<String>,
+}
+
+/// a classifier with the built-in model, see `DEFAULT_MODEL`
+impl Default for StructuralClassifier {
+  fn default() -> StructuralClassifier {
+    let mut classifier = StructuralClassifier::new();
+    classifier
+      .add_model(DEFAULT_MODEL)
+      .expect("the built-in structural classifier model is valid");
+    classifier
+  }
+}
+
+impl StructuralClassifier {
+  /// an untrained classifier, without the built-in model
+  pub fn new() -> Self {
+    StructuralClassifier {
+      smoothing: 1.0,
+      examples: HashMap::new(),
+      features: HashMap::new(),
+      totals: HashMap::new(),
+      vocabulary: HashSet::new(),
+    }
+  }
+
+  /// load a classifier from a model file
+  pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+    let mut classifier = StructuralClassifier::new();
+    classifier.add_model(&fs::read_to_string(path)?)?;
+    Ok(classifier)
+  }
+
+  /// Add the counts of a model file's contents. Fails on the first invalid line, keeping the
+  /// counts before it
+  pub fn add_model(&mut self, model: &str) -> Result<(), Box<dyn Error>> {
+    for (index, line) in model.lines().enumerate() {
+      if line.trim().is_empty() || line.starts_with('#') {
+        continue;
+      }
+      let fields: Vec<&str> = line.split('\t').collect();
+      let invalid = || format!("line {}: invalid model line: {:?}", index + 1, line);
+      if fields[0] == "smoothing" && fields.len() == 2 {
+        self.smoothing = fields[1].parse()?;
+        continue;
+      }
+      let env = match fields.get(1).map(|name| StructuralEnv::from(*name)) {
+        Some(env) if env.to_string() == fields[1] => env,
+        _ => return Err(invalid().into()),
+      };
+      match (fields[0], fields.len()) {
+        ("examples", 3) => *self.examples.entry(env).or_insert(0) += fields[2].parse::<usize>()?,
+        ("feature", 4) => self.add_feature_count(env, fields[2], fields[3].parse()?),
+        _ => return Err(invalid().into()),
+      }
+    }
+    Ok(())
+  }
+
+  /// add to the count of a feature in an environment
+  fn add_feature_count(&mut self, env: StructuralEnv, feature: &str, count: usize) {
+    *self
+      .features
+      .entry(env)
+      .or_default()
+      .entry(feature.to_string())
+      .or_insert(0) += count;
+    *self.totals.entry(env).or_insert(0) += count;
+    if !self.vocabulary.contains(feature) {
+      self.vocabulary.insert(feature.to_string());
+    }
+  }
+
+  /// Add a training example of an environment
+  pub fn add_example<S: AsRef<str>>(&mut self, env: StructuralEnv, features: &[S]) {
+    *self.examples.entry(env).or_insert(0) += 1;
+    for feature in features {
+      self.add_feature_count(env, feature.as_ref(), 1);
+    }
+  }
+
+  /// Add the labelled sections of a document as training examples, i.e. the sections with
+  /// headings which map cleanly to a `StructuralEnv`
+  pub fn add_document(&mut self, document: &Document, paragraph_count: usize) {
+    for example in SectionExample::from_document(document, paragraph_count) {
+      if example.env != StructuralEnv::Other {
+        self.add_example(example.env, &example.features);
+      }
+    }
+  }
+
+  /// add the counts of another classifier, e.g. one trained by another thread
+  pub fn merge(&mut self, other: StructuralClassifier) {
+    for (env, count) in other.examples {
+      *self.examples.entry(env).or_insert(0) += count;
+    }
+    for (env, features) in other.features {
+      for (feature, count) in features {
+        self.add_feature_count(env, &feature, count);
+      }
+    }
+  }
+
+  /// the number of environments with training examples
+  pub fn class_count(&self) -> usize { self.examples.len() }
+
+  /// the number of distinct features seen in training
+  pub fn vocabulary_size(&self) -> usize { self.vocabulary.len() }
+
+  /// The posterior probabilities of the environments given features, most probable first.
+  /// Features unseen in training are ignored
+  pub fn probabilities<S: AsRef<str>>(&self, features: &[S]) -> Vec<(StructuralEnv, f64)> {
+    let example_total: usize = self.examples.values().sum();
+    let vocabulary_size = self.vocabulary.len() as f64;
+    let mut scores: Vec<(StructuralEnv, f64)> = self
+      .examples
+      .iter()
+      .map(|(env, count)| {
+        let counts = self.features.get(env);
+        let denominator =
+          self.totals.get(env).copied().unwrap_or(0) as f64 + self.smoothing * vocabulary_size;
+        let mut score = (*count as f64 / example_total as f64).ln();
+        for feature in features {
+          let feature = feature.as_ref();
+          if self.vocabulary.contains(feature) {
+            let count = counts
+              .and_then(|counts| counts.get(feature))
+              .copied()
+              .unwrap_or(0);
+            score += ((count as f64 + self.smoothing) / denominator).ln();
+          }
+        }
+        (*env, score)
+      })
+      .collect();
+    // normalize the log scores into probabilities, stably
+    let max_score = scores
+      .iter()
+      .map(|(_, score)| *score)
+      .fold(f64::NEG_INFINITY, f64::max);
+    let normalizer: f64 = scores
+      .iter()
+      .map(|(_, score)| (score - max_score).exp())
+      .sum();
+    for (_, score) in scores.iter_mut() {
+      *score = (*score - max_score).exp() / normalizer;
+    }
+    scores.sort_by(|a, b| {
+      b.1
+        .partial_cmp(&a.1)
+        .unwrap()
+        .then(a.0.to_string().cmp(&b.0.to_string()))
+    });
+    scores
+  }
+
+  /// the most probable environment given features, with its probability, if trained
+  pub fn predict<S: AsRef<str>>(&self, features: &[S]) -> Option<(StructuralEnv, f64)> {
+    self.probabilities(features).into_iter().next()
+  }
+
+  /// The environment of a section: the one of its heading (`SectionExample::env`, in the document
+  /// language), if it maps cleanly, or else the prediction from its heading and content features,
+  /// if at least `min_probability`
+  pub fn classify(&self, example: &SectionExample, min_probability: f64) -> StructuralEnv {
+    if example.env != StructuralEnv::Other {
+      return example.env;
+    }
+    match self.predict(&example.features) {
+      Some((env, probability)) if probability >= min_probability => env,
+      _ => StructuralEnv::Other,
+    }
+  }
+
+  /// Train a classifier over the documents of a corpus, leaving out the held out ones
+  pub fn train(corpus: &Corpus, options: TrainingOptions) -> Self {
+    corpus.fold_with_parallel_walk(
+      StructuralClassifier::new,
+      |document, classifier| {
+        if !options.is_held_out(&document.path) {
+          classifier.add_document(&document, options.paragraph_count);
+        }
+      },
+      StructuralClassifier::merge,
+    )
+  }
+
+  /// Evaluate the classifier over the labelled sections of the held out documents of a corpus
+  pub fn evaluate(&self, corpus: &Corpus, options: TrainingOptions) -> Evaluation {
+    let catalog = corpus.catalog_with_parallel_walk(|document| {
+      let mut outcomes = HashMap::new();
+      if options.is_held_out(&document.path) {
+        for example in SectionExample::from_document(&document, options.paragraph_count) {
+          if example.env == StructuralEnv::Other {
+            continue;
+          }
+          let features = if options.mask_headings {
+            example.content_features()
+          } else {
+            example.features.clone()
+          };
+          let predicted = self
+            .predict(&features)
+            .map(|(env, _)| env)
+            .unwrap_or(StructuralEnv::Other);
+          *outcomes
+            .entry(format!("{}\t{}", example.env, predicted))
+            .or_insert(0) += 1;
+        }
+      }
+      outcomes
+    });
+    let mut evaluation = Evaluation::default();
+    for (outcome, count) in catalog {
+      if let Some((actual, predicted)) = outcome.split_once('\t') {
+        evaluation.add_count(actual.into(), predicted.into(), count as usize);
+      }
+    }
+    evaluation
+  }
+
+  /// Write the classifier in the model file format, sorted by environment and feature
+  pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+    writeln!(writer, "smoothing\t{}", self.smoothing)?;
+    let mut envs: Vec<&StructuralEnv> = self.examples.keys().collect();
+    envs.sort_by_key(|env| env.to_string());
+    for env in envs {
+      writeln!(writer, "examples\t{}\t{}", env, self.examples[env])?;
+      if let Some(features) = self.features.get(env) {
+        let mut features: Vec<(&String, &usize)> = features.iter().collect();
+        features.sort();
+        for (feature, count) in features {
+          writeln!(writer, "feature\t{env}\t{feature}\t{count}")?;
+        }
+      }
+    }
+    Ok(())
+  }
+
+  /// Save the classifier to a model file
+  pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    self.write(&mut writer)?;
+    writer.flush()?;
+    Ok(())
+  }
+}
+
+/// The confusion counts of an evaluation, by actual and predicted environment
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Evaluation {
+  /// the number of examples of every actual and predicted environment pair
+  pub confusion: HashMap<(StructuralEnv, StructuralEnv), usize>,
+}
+
+impl Evaluation {
+  /// record an example with its actual and predicted environments
+  pub fn add(&mut self, actual: StructuralEnv, predicted: StructuralEnv) {
+    self.add_count(actual, predicted, 1);
+  }
+
+  /// record examples with the same actual and predicted environments
+  pub fn add_count(&mut self, actual: StructuralEnv, predicted: StructuralEnv, count: usize) {
+    *self.confusion.entry((actual, predicted)).or_insert(0) += count;
+  }
+
+  /// add the counts of another evaluation, e.g. of another fold
+  pub fn merge(&mut self, other: Evaluation) {
+    for ((actual, predicted), count) in other.confusion {
+      self.add_count(actual, predicted, count);
+    }
+  }
+
+  /// the number of evaluated examples
+  pub fn total(&self) -> usize { self.confusion.values().sum() }
+
+  /// the ratio of correct predictions
+  pub fn accuracy(&self) -> f64 {
+    let correct: usize = self
+      .confusion
+      .iter()
+      .filter(|((actual, predicted), _)| actual == predicted)
+      .map(|(_, count)| count)
+      .sum();
+    ratio(correct, self.total())
+  }
+
+  /// the ratio of correct predictions of an environment
+  pub fn precision(&self, env: StructuralEnv) -> f64 {
+    ratio(
+      self.count(env, env),
+      self.matching(|_, predicted| predicted == env),
+    )
+  }
+
+  /// the ratio of the examples of an environment which are predicted correctly
+  pub fn recall(&self, env: StructuralEnv) -> f64 {
+    ratio(
+      self.count(env, env),
+      self.matching(|actual, _| actual == env),
+    )
+  }
+
+  /// the harmonic mean of the precision and recall of an environment
+  pub fn f1(&self, env: StructuralEnv) -> f64 {
+    let (precision, recall) = (self.precision(env), self.recall(env));
+    if precision + recall == 0.0 {
+      0.0
+    } else {
+      2.0 * precision * recall / (precision + recall)
+    }
+  }
+
+  /// the mean F1 score of the actual environments
+  pub fn macro_f1(&self) -> f64 {
+    let envs: HashSet<StructuralEnv> = self.confusion.keys().map(|(actual, _)| *actual).collect();
+    if envs.is_empty() {
+      0.0
+    } else {
+      envs.iter().map(|env| self.f1(*env)).sum::<f64>() / envs.len() as f64
+    }
+  }
+
+  /// the count of an actual and predicted environment pair
+  fn count(&self, actual: StructuralEnv, predicted: StructuralEnv) -> usize {
+    self
+      .confusion
+      .get(&(actual, predicted))
+      .copied()
+      .unwrap_or(0)
+  }
+
+  /// the count of the pairs satisfying a condition
+  fn matching(&self, condition: impl Fn(StructuralEnv, StructuralEnv) -> bool) -> usize {
+    self
+      .confusion
+      .iter()
+      .filter(|((actual, predicted), _)| condition(*actual, *predicted))
+      .map(|(_, count)| count)
+      .sum()
+  }
+}
+
+/// a ratio, 1 for an empty denominator, as in `tokenizer::evaluation`
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+  if denominator == 0 {
+    1.0
+  } else {
+    numerator as f64 / denominator as f64
+  }
+}
@@ -0,0 +1,244 @@
+//! Windowed word co-occurrence counts for embedding training, as a native replacement for
+//! writing a `token_model.txt` and running GloVe's `cooccur` tool over it.
+//!
+//! Words are mapped to ids by a `Vocabulary`, skipping the words outside of it, and the weighted
+//! counts are written in GloVe's binary `cooccurrence.bin` format, to be shuffled and trained on
+//! with the vocabulary saved as `VocabularyFormat::Glove`.
+use crate::parallel_data::Document;
+use crate::util::data_helpers::{ams_normalize_word_range, LexicalOptions};
+use crate::vocabulary::{Vocabulary, UNKNOWN};
+use libxml::xpath::Context;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::sync::Arc;
+
+/// The symbol class standing in for a whole formula, when math is collapsed
+pub const MATH_SYMBOL: &str = "mathformula";
+
+/// The text unit which co-occurrence windows may not cross
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Scope {
+  /// windows stay within a sentence
+  Sentence,
+  /// windows stay within a paragraph, as in the token model
+  Paragraph,
+}
+
+/// The weight of a co-occurrence, by the distance between the words
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Weighting {
+  /// every co-occurrence in the window counts as 1
+  Uniform,
+  /// a co-occurrence at distance `d` counts as `1 / d`, as in GloVe
+  Harmonic,
+}
+
+/// Weighted co-occurrence counts of the word pairs of a vocabulary
+#[derive(Debug, Clone)]
+pub struct Cooccurrences {
+  /// the number of preceding words a word co-occurs with
+  pub window_size: usize,
+  /// the weight of a co-occurrence, by distance
+  pub weighting: Weighting,
+  /// if set, every co-occurrence is counted in both directions, otherwise only with the
+  /// preceding word first
+  pub symmetric: bool,
+  /// the text unit which windows may not cross, when adding whole documents
+  pub scope: Scope,
+  /// if set, each formula counts as the single word `MATH_SYMBOL`, rather than as its lexemes
+  pub collapse_math: bool,
+  /// the vocabulary mapping words to ids
+  vocabulary: Arc<Vocabulary>,
+  /// the weighted counts of pairs of vocabulary ids
+  counts: HashMap<(usize, usize), f64>,
+}
+
+impl Cooccurrences {
+  /// empty counts over a vocabulary, with GloVe's default settings: a symmetric window of 15
+  /// words, with harmonic weighting, scoped to paragraphs
+  pub fn new(vocabulary: Arc<Vocabulary>) -> Self {
+    Cooccurrences {
+      window_size: 15,
+      weighting: Weighting::Harmonic,
+      symmetric: true,
+      scope: Scope::Paragraph,
+      collapse_math: false,
+      vocabulary,
+      counts: HashMap::new(),
+    }
+  }
+
+  /// empty counts with the same settings and vocabulary
+  pub fn empty_clone(&self) -> Self {
+    Cooccurrences {
+      vocabulary: Arc::clone(&self.vocabulary),
+      counts: HashMap::new(),
+      ..*self
+    }
+  }
+
+  /// the vocabulary mapping words to ids
+  pub fn vocabulary(&self) -> &Vocabulary { &self.vocabulary }
+
+  /// Count the co-occurrences within a sequence of words, e.g. a line of a token model. With
+  /// `collapse_math`, each run of math lexemes (see `is_math_lexeme`) counts as one `MATH_SYMBOL`
+  pub fn add_words(&mut self, words: &[&str]) {
+    let ids = self.word_ids(words, self.collapse_math);
+    self.add_ids(&ids);
+  }
+
+  /// the vocabulary ids of a sequence of words, skipping the words outside of the vocabulary
+  fn word_ids(&self, words: &[&str], collapse_math: bool) -> Vec<usize> {
+    let mut ids = Vec::with_capacity(words.len());
+    let mut in_formula = false;
+    for word in words {
+      let word = if collapse_math && is_math_lexeme(word) {
+        if in_formula {
+          continue;
+        }
+        in_formula = true;
+        MATH_SYMBOL
+      } else {
+        in_formula = false;
+        word
+      };
+      if let Some(id) = self.vocabulary.id(word) {
+        if word != UNKNOWN {
+          ids.push(id);
+        }
+      }
+    }
+    ids
+  }
+
+  /// count the co-occurrences within a sequence of vocabulary ids
+  fn add_ids(&mut self, ids: &[usize]) {
+    for (position, center) in ids.iter().enumerate() {
+      for distance in 1..=self.window_size.min(position) {
+        let context = ids[position - distance];
+        let weight = match self.weighting {
+          Weighting::Uniform => 1.0,
+          Weighting::Harmonic => 1.0 / distance as f64,
+        };
+        *self.counts.entry((context, *center)).or_insert(0.0) += weight;
+        if self.symmetric {
+          *self.counts.entry((*center, context)).or_insert(0.0) += weight;
+        }
+      }
+    }
+  }
+
+  /// Count the co-occurrences of a document, within each sentence or paragraph, over the words
+  /// normalized as in the token model (see `document_words`)
+  pub fn add_document(&mut self, document: &Document) {
+    for words in document_words(document, self.scope, self.collapse_math) {
+      let words: Vec<&str> = words.iter().map(String::as_str).collect();
+      // formulas are already collapsed, while the lexemes of uncollapsed ones must stay apart
+      let ids = self.word_ids(&words, false);
+      self.add_ids(&ids);
+    }
+  }
+
+  /// add the counts of another instance over the same vocabulary, e.g. one filled by another
+  /// thread
+  pub fn merge(&mut self, other: Cooccurrences) {
+    if self.counts.is_empty() {
+      self.counts = other.counts;
+      return;
+    }
+    for (pair, count) in other.counts {
+      *self.counts.entry(pair).or_insert(0.0) += count;
+    }
+  }
+
+  /// the weighted count of a word co-occurring after a context word
+  pub fn get(&self, context: &str, word: &str) -> f64 {
+    match (self.vocabulary.id(context), self.vocabulary.id(word)) {
+      (Some(context), Some(word)) => self.counts.get(&(context, word)).copied().unwrap_or(0.0),
+      _ => 0.0,
+    }
+  }
+
+  /// the number of word pairs with a co-occurrence
+  pub fn len(&self) -> usize { self.counts.len() }
+
+  /// checks if no co-occurrence was counted
+  pub fn is_empty(&self) -> bool { self.counts.is_empty() }
+
+  /// Write the counts in GloVe's binary `cooccurrence.bin` format: a record per word pair, sorted
+  /// by pair, of two 32-bit 1-based word ids (by line in the GloVe vocabulary file) and a 64-bit
+  /// float count, in little-endian byte order
+  pub fn write_glove<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+    // GloVe reserves the unknown word, so it is left out of the GloVe vocabulary file
+    let offset = if self.vocabulary.word(0) == Some(UNKNOWN) {
+      0
+    } else {
+      1
+    };
+    let mut pairs: Vec<(&(usize, usize), &f64)> = self.counts.iter().collect();
+    pairs.sort_by_key(|(pair, _)| **pair);
+    for ((first, second), count) in pairs {
+      writer.write_all(&((first + offset) as i32).to_le_bytes())?;
+      writer.write_all(&((second + offset) as i32).to_le_bytes())?;
+      writer.write_all(&count.to_le_bytes())?;
+    }
+    Ok(())
+  }
+
+  /// Save the counts to a GloVe `cooccurrence.bin` file
+  pub fn save_glove(&self, path: &str) -> Result<(), Box<dyn Error>> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    self.write_glove(&mut writer)?;
+    writer.flush()?;
+    Ok(())
+  }
+}
+
+/// Checks if a token model word is a math lexeme, i.e. a mixed or uppercase word such as "ID_x"
+/// or "OPERATOR_end" (the other words are lowercased), other than the reserved "NUM"
+pub fn is_math_lexeme(word: &str) -> bool {
+  word != "NUM" && word.chars().any(|c| c.is_ascii_uppercase())
+}
+
+/// The normalized words of a document, as in the token model (see
+/// `util::data_helpers::ams_normalize_word_range`), grouped by sentence or paragraph. Paragraphs
+/// with overly long words, which tend to be conversion errors, are left out
+pub fn document_words(document: &Document, scope: Scope, collapse_math: bool) -> Vec<Vec<String>> {
+  let tokenizer = &document.corpus.tokenizer;
+  let mut context = match Context::new(&document.dom) {
+    Ok(context) => context,
+    Err(_) => return Vec::new(),
+  };
+  let mut units = Vec::new();
+  for paragraph in document.extended_paragraph_iter() {
+    let ranges = match scope {
+      Scope::Paragraph => paragraph.dnm.get_range().into_iter().collect(),
+      Scope::Sentence => tokenizer.sentences(&paragraph.dnm),
+    };
+    let mut paragraph_units = Vec::new();
+    let mut invalid_paragraph = false;
+    'units: for range in ranges {
+      let mut words = Vec::new();
+      for word in tokenizer.words_punct_and_quantities(&range) {
+        if collapse_math && word.get_plaintext().to_lowercase().contains(MATH_SYMBOL) {
+          words.push(MATH_SYMBOL.to_string());
+          continue;
+        }
+        match ams_normalize_word_range(&word, &mut context, LexicalOptions::default()) {
+          Ok(normalized) => words.extend(normalized.split_whitespace().map(str::to_string)),
+          Err(_) => {
+            invalid_paragraph = true;
+            break 'units;
+          },
+        }
+      }
+      paragraph_units.push(words);
+    }
+    if !invalid_paragraph {
+      units.extend(paragraph_units);
+    }
+  }
+  units
+}
@@ -5,6 +5,8 @@ use walkdir::IntoIter as WalkDirIterator;
 use walkdir::WalkDir;
 
 use crate::dnm::{DNMParameters, DNMRange, DNM};
+use crate::parallel_data::source::{self, DocumentSource, Sources};
+use crate::parallel_data::walk::{self, DocumentFailure, FailureKind, WalkOptions, WalkReport};
 use crate::tokenizer::Tokenizer;
 
 use libxml::parser::{Parser, XmlParseError};
@@ -34,16 +36,24 @@ pub struct Corpus {
   /// Default setting for `DNM` generation
   pub dnm_parameters: DNMParameters,
   /// Extension of corpus files (for specially tailored resources such as DLMF's .html5)
-  /// defaults to selecting .html AND .xhtml files
+  /// defaults to selecting .html AND .xhtml files, also within archives and gzipped files
   pub extension: Option<String>,
+  /// Retries and size limits of the walks over the documents (panics and durations only apply to
+  /// the parallel walks, which run the per-document closures)
+  pub walk_options: WalkOptions,
 }
 
-/// File-system iterator yielding individual documents
+/// File-system iterator yielding individual documents, skipping the unreadable and unparseable
+/// ones
 pub struct DocumentIterator<'iter> {
   /// the directory walker
   walker: Box<WalkDirIterator>,
   /// reference to the parent corpus
   pub corpus: &'iter Corpus,
+  /// the documents of the last walked file, e.g. the entries of an archive
+  pending: Sources,
+  /// the documents yielded so far, and the failures skipped
+  pub report: WalkReport,
 }
 
 /// One of our math documents.
@@ -128,35 +138,36 @@ pub struct Word<'w> {
 impl<'iter> Iterator for DocumentIterator<'iter> {
   type Item = Document<'iter>;
   fn next(&mut self) -> Option<Document<'iter>> {
-    let walker = &mut self.walker;
+    let corpus = self.corpus;
     loop {
-      let next_entry = walker.next();
-      if next_entry.is_none() {
-        break;
-      } else if let Some(Ok(ref entry)) = next_entry {
-        let file_name = entry.file_name().to_str().unwrap_or("").to_owned();
-        let selected = if let Some(ref extension) = self.corpus.extension {
-          file_name.ends_with(extension)
-        } else {
-          file_name.ends_with(".html") || file_name.ends_with(".xhtml")
-        };
-        if selected {
-          let path = entry.path().to_str().unwrap_or("").to_owned();
-          let doc_result = Document::new(path, self.corpus);
-          return match doc_result {
-            Ok(doc) => Some(doc),
-            // TODO: Currently encountering an unparseable file will terminate the entire corpus
-            // walk, which is too severe.
-            // A more viable strategy would be to 1) retry creating the document once and 2)
-            // print an error message and continue the walk
-            _ => None,
-          };
+      // the documents of the last walked file or archive come first
+      if let Some(source) = self.pending.next() {
+        let outcome = source.and_then(|source| corpus.load_source(source));
+        if let Err(ref failure) = outcome {
+          println!("-- Skipping document {failure}");
         }
-      } else {
-        println!("-- Error while walking for entry: {next_entry:?}")
+        match self.report.record(outcome) {
+          Some(document) => return Some(document),
+          None => continue,
+        }
+      }
+      match self.walker.next()? {
+        Ok(entry) => {
+          if !entry.file_type().is_dir() {
+            let path = entry.path().to_str().unwrap_or("").to_owned();
+            self.pending = source::expand(&path, &corpus.extension, &corpus.walk_options);
+          }
+        },
+        Err(e) => {
+          println!("-- Error while walking for entry: {e:?}");
+          self.report.record::<()>(Err(walk::failure(
+            &corpus.path,
+            FailureKind::Walk,
+            e.to_string(),
+          )));
+        },
       }
     }
-    None
   }
 }
 
@@ -171,12 +182,14 @@ impl Default for Corpus {
       senna: RefCell::new(Senna::new(SENNA_PATH.to_owned())),
       senna_options: Cell::new(SennaParseOptions::default()),
       dnm_parameters: DNMParameters::llamapun_normalization(),
+      walk_options: WalkOptions::default(),
     }
   }
 }
 
 impl Corpus {
-  /// Create a new corpus with the base directory `dirpath`
+  /// Create a new corpus with the base directory `dirpath`, which may also be a tar, tar.gz or zip
+  /// archive
   pub fn new(dirpath: String) -> Self {
     Corpus {
       path: dirpath,
@@ -189,6 +202,8 @@ impl Corpus {
     DocumentIterator {
       walker: Box::new(WalkDir::new(self.path.clone()).into_iter()),
       corpus: self,
+      pending: Box::new(std::iter::empty()),
+      report: WalkReport::default(),
     }
   }
 
@@ -196,6 +211,26 @@ impl Corpus {
   pub fn load_doc(&self, path: String) -> Result<Document, XmlParseError> {
     Document::new(path, self)
   }
+
+  /// Load a walked document, as guarded by the `walk_options`
+  fn load_source(&self, source: DocumentSource) -> Result<Document, DocumentFailure> {
+    match source {
+      DocumentSource::File(path) => {
+        walk::check_size(&path, &self.walk_options)?;
+        walk::load_with_retries(&path, &self.walk_options, || {
+          Document::new(path.clone(), self)
+        })
+      },
+      DocumentSource::Bytes {
+        path,
+        archive,
+        content,
+      } => walk::load_with_retries(&path, &self.walk_options, || {
+        Document::from_bytes(path.clone(), &content, self)
+      })
+      .map_err(|failure| failure.in_archive(archive.as_deref())),
+    }
+  }
 }
 
 impl<'d> Document<'d> {
@@ -215,6 +250,25 @@ impl<'d> Document<'d> {
     })
   }
 
+  /// Load a document from its content, e.g. an archive entry, with the path it is known by
+  pub fn from_bytes(
+    path: String,
+    content: &[u8],
+    corpus: &'d Corpus,
+  ) -> Result<Self, XmlParseError> {
+    let dom = if path.ends_with(".xhtml") {
+      corpus.xml_parser.parse_string(content)?
+    } else {
+      corpus.html_parser.parse_string(content)?
+    };
+    Ok(Document {
+      path,
+      dom,
+      corpus,
+      dnm: None,
+    })
+  }
+
   /// Obtain the problem-free logical paragraphs of a libxml `Document`
   pub fn paragraph_nodes(doc: &XmlDoc) -> Vec<RoNode> {
     let xpath_context = Context::new(doc).unwrap();
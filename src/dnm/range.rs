@@ -224,54 +224,68 @@ impl<'dnmrange> DNMRange<'dnmrange> {
     dnm: &'dnmrange DNM,
     xpath_context: &Context,
   ) -> DNMRange<'dnmrange> {
-    assert_eq!(&(string[0..7]), "arange(");
-    assert_eq!(&(string[string.len() - 1..string.len()]), ")");
+    DNMRange::try_deserialize(string, dnm, xpath_context)
+      .unwrap_or_else(|e| panic!("DNMRange::deserialize: {e}"))
+  }
 
+  /// deserializes an xpointer into a `DNMRange`, as `deserialize`, but fails rather than panics on
+  /// malformed xpointers, and on xpointers which do not resolve to a single node of the DNM
+  pub fn try_deserialize(
+    string: &str,
+    dnm: &'dnmrange DNM,
+    xpath_context: &Context,
+  ) -> Result<DNMRange<'dnmrange>, String> {
+    let inner = string
+      .strip_prefix("arange(")
+      .and_then(|inner| inner.strip_suffix(')'))
+      .ok_or_else(|| format!("Malformed string: \"{string}\""))?;
     let main_comma = 1
-      + string.find("),").unwrap_or_else(|| {
-        string
-          .find("],")
-          .unwrap_or_else(|| panic!("DNMRange::deserialize: Malformed string: \"{string}\""))
-      });
+      + inner
+        .find("),")
+        .or_else(|| inner.find("],"))
+        .ok_or_else(|| format!("Malformed string: \"{string}\""))?;
 
-    let start_str = &string[7..main_comma];
-    let end_str = &string[main_comma + 1..string.len() - 1];
+    let start_str = &inner[..main_comma];
+    let end_str = &inner[main_comma + 1..];
 
-    let start = DNMRange::xpointer_to_offset(start_str, dnm, xpath_context);
-    let end = DNMRange::xpointer_to_offset(end_str, dnm, xpath_context);
+    let start = DNMRange::xpointer_to_offset(start_str, dnm, xpath_context)?;
+    let end = DNMRange::xpointer_to_offset(end_str, dnm, xpath_context)?;
 
-    DNMRange { start, end, dnm }
+    Ok(DNMRange { start, end, dnm })
   }
 
   /// Gets the plaintext offset corresponding to an XPath/string-index'ed XPointer,
   /// again, does not cover everything!
-  fn xpointer_to_offset(string: &str, dnm: &'dnmrange DNM, xpath_context: &Context) -> usize {
-    if string.len() > 13 && &(string[0..13]) == "string-index(" {
-      let comma = string
-        .find(',')
-        .unwrap_or_else(|| panic!("DNM::deserialize_part: Malformed string: \"{string}\""));
-      let node_str = &string[13..comma];
-      let node_set = xpath_context.evaluate(node_str).unwrap();
-      assert_eq!(node_set.get_number_of_nodes(), 1);
-      let node = node_set.get_readonly_nodes_as_vec()[0];
+  fn xpointer_to_offset(
+    string: &str,
+    dnm: &'dnmrange DNM,
+    xpath_context: &Context,
+  ) -> Result<usize, String> {
+    if let Some(index) = string
+      .strip_prefix("string-index(")
+      .and_then(|index| index.strip_suffix(')'))
+    {
+      let (node_str, offset_str) = index
+        .rsplit_once(',')
+        .ok_or_else(|| format!("Malformed string: \"{string}\""))?;
+      let node = single_node(node_str, xpath_context)?;
+      let offset = offset_str
+        .trim()
+        .parse::<i32>()
+        .map_err(|_| format!("Malformed string index: \"{string}\""))?
+        - 1;
       match dnm.get_range_of_node(node) {
         Ok(range) => {
           let mut pos = range.start;
-          let offset = &string[comma + 1..string.len() - 1].parse::<i32>().unwrap() - 1;
           while pos < range.end && dnm.back_map[pos].1 < offset {
             pos += 1;
           }
-          pos
+          Ok(pos)
         },
         Err(_) => get_position_of_lowest_parent(node, dnm),
       }
     } else {
-      let node_str = string;
-      let node_set = xpath_context
-        .evaluate(node_str)
-        .unwrap_or_else(|_| panic!("DNMRange::deserialize: Malformed XPath: '{}'", &node_str));
-      assert_eq!(node_set.get_number_of_nodes(), 1);
-      let node = node_set.get_readonly_nodes_as_vec()[0];
+      let node = single_node(string, xpath_context)?;
       get_position_of_lowest_parent(node, dnm)
     }
   }
@@ -283,10 +297,27 @@ impl<'dnmrange> DNMRange<'dnmrange> {
 
 /// Helper function: Gets the start offset of the lowest parent recorded in the
 /// DNM
-fn get_position_of_lowest_parent(node: RoNode, dnm: &DNM) -> usize {
+fn get_position_of_lowest_parent(node: RoNode, dnm: &DNM) -> Result<usize, String> {
   match dnm.get_range_of_node(node) {
-    Ok(range) => range.start,
-    Err(_) => get_position_of_lowest_parent(node.get_parent().unwrap(), dnm),
+    Ok(range) => Ok(range.start),
+    Err(_) => match node.get_parent() {
+      Some(parent) => get_position_of_lowest_parent(parent, dnm),
+      None => Err(String::from("No ancestor of the node is in the DNM")),
+    },
+  }
+}
+
+/// Helper function: Gets the single node an XPath selects
+fn single_node(xpath: &str, xpath_context: &Context) -> Result<RoNode, String> {
+  let node_set = xpath_context
+    .evaluate(xpath)
+    .map_err(|_| format!("Malformed XPath: '{xpath}'"))?;
+  match node_set.get_readonly_nodes_as_vec().as_slice() {
+    [node] => Ok(*node),
+    nodes => Err(format!(
+      "XPath '{xpath}' selects {} nodes, rather than one",
+      nodes.len()
+    )),
   }
 }
 
@@ -26,6 +26,7 @@ extern crate walkdir;
 #[macro_use]
 pub mod util;
 pub mod ams;
+pub mod cooccurrence;
 pub mod data;
 pub mod dnm;
 pub mod ngrams;
@@ -33,5 +34,6 @@ pub mod parallel_data;
 pub mod patterns;
 pub mod stopwords;
 pub mod tokenizer;
+pub mod vocabulary;
 
 pub mod extern_use;
@@ -1,9 +1,21 @@
 //! A small ngram library
 //! ngrams are sequences of n consecutive words
+use crate::dnm::DNMRange;
+use crate::stopwords::Stopwords;
 use circular_queue::CircularQueue;
+use counting::Counter;
 use std::collections::HashMap;
+use std::sync::Arc;
 
-/// Records single words, in order of appearance
+/// Collocation and multiword term ranking over `Ngrams` counts
+pub mod collocation;
+/// Exact and memory-bounded approximate counting backends
+pub mod counting;
+/// Smoothed n-gram language models, trained from `Ngrams` counts
+pub mod language_model;
+
+/// Records single words, in order of appearance (see `vocabulary::Vocabulary` for word
+/// frequencies and ids)
 #[derive(Debug, Default)]
 pub struct Dictionary {
   /// hashmap for the records
@@ -33,7 +45,8 @@ impl Dictionary {
   pub fn count(&self) -> usize { self.index }
 }
 
-/// Ngrams are dictionaries with
+/// Ngrams are dictionaries with occurrence counts of word sequences
+#[derive(Debug, Clone)]
 pub struct Ngrams {
   /// anchor word that must be present in all ngram contexts (in their window)
   pub anchor: Option<String>,
@@ -42,8 +55,16 @@ pub struct Ngrams {
   pub window_size: usize,
   /// n-grams for a sequence of n words
   pub n: usize,
+  /// if given, all n-grams with lengths from `min_n` up to `n` are recorded, rather than only
+  /// those of length `n`
+  pub min_n: Option<usize>,
+  /// the maximum number of words that may be skipped within a single n-gram, i.e. for k-skip
+  /// n-grams, with 0 recording only contiguous n-grams
+  pub skip: usize,
   /// statistics hashmap for the occurence counts
   pub counts: HashMap<String, usize>,
+  /// if given, ngrams which are stopword phrases, or start or end with a stopword, are not counted
+  pub stopwords: Option<Arc<Stopwords>>,
 }
 impl Default for Ngrams {
   fn default() -> Ngrams {
@@ -51,7 +72,10 @@ impl Default for Ngrams {
       anchor: None,
       window_size: 0,
       n: 1,
+      min_n: None,
+      skip: 0,
       counts: HashMap::new(),
+      stopwords: None,
     }
   }
 }
@@ -84,15 +108,40 @@ impl Ngrams {
   /// get the number of distinct ngrams recorded
   pub fn distinct_count(&self) -> usize { self.counts.len() }
 
+  /// add the counts of another `Ngrams` instance, e.g. one filled by another thread, keeping the
+  /// settings of `self`
+  pub fn merge(&mut self, other: Ngrams) { Counter::merge(&mut self.counts, other.counts) }
+
   /// add content for ngram analysis, typically a paragraph or a line of text
   pub fn add_content(&mut self, content: &str) {
     if self.anchor.is_some() && self.window_size > 0 {
       self.add_anchored_content(content)
     } else {
-      unimplemented!(); // TODO: the basic ngram case
+      self.record_words(
+        content
+          .split_ascii_whitespace()
+          .filter(|&w| w.chars().next().unwrap().is_alphanumeric())
+          .collect(),
+      )
     }
   }
 
+  /// add the words of a sentence, padded with the sentence start and end markers of
+  /// `language_model`
+  pub fn add_sentence(&mut self, words: &[&str]) {
+    let mut padded = Vec::with_capacity(words.len() + 2);
+    padded.push(language_model::SENTENCE_START);
+    padded.extend_from_slice(words);
+    padded.push(language_model::SENTENCE_END);
+    self.record_words(padded);
+  }
+
+  /// add the words of a tokenized range, e.g. from `Tokenizer::words`, as a single continuous
+  /// sequence
+  pub fn add_ranges(&mut self, ranges: &[DNMRange]) {
+    self.record_words(ranges.iter().map(DNMRange::get_plaintext).collect())
+  }
+
   /// In essence, for a given window size W, a word at index i is justified to participate in the
   /// ngrams if there is an instance of an anchor word in the range of words [i-W, i+W].
   /// this can be highly irregular e.g. "word word anchor word anchor word word", so we record
@@ -138,21 +187,66 @@ impl Ngrams {
   /// Take an arbitrarily long vector of words, and record all (overlapping) ngrams obtainable from
   /// it
   pub fn record_words(&mut self, words: Vec<&str>) {
-    if words.len() < self.n {
+    let mut counts = std::mem::take(&mut self.counts);
+    self.count_words(&words, &mut counts);
+    self.counts = counts;
+  }
+
+  /// count all (overlapping) ngrams of a word sequence, with the settings of `self`, into another
+  /// counting backend, e.g. a `counting::CountMinSketch` when the exact counts do not fit in memory
+  pub fn count_words<C: Counter>(&self, words: &[&str], counter: &mut C) {
+    let min_n = self.min_n.unwrap_or(self.n).clamp(1, self.n.max(1));
+    if self.n == 0 || words.len() < min_n {
       // nothing to do unless we have at least n words
       return;
     }
-    let mut gram_window = CircularQueue::with_capacity(self.n);
-    for w in words.into_iter() {
-      gram_window.push(w);
-      if gram_window.len() == self.n {
-        let key = gram_window
-          .asc_iter()
-          .copied()
-          .collect::<Vec<_>>()
-          .join(" ");
-        self.insert(key);
+    let mut gram = Vec::with_capacity(self.n);
+    for (start, word) in words.iter().enumerate() {
+      gram.push(*word);
+      self.count_grams_from(words, start, self.skip, min_n, &mut gram, counter);
+      gram.clear();
+    }
+  }
+
+  /// count the ngram in `gram`, whose last word is at index `last`, if it is long enough, as well
+  /// as all its extensions that skip at most `skips_left` further words
+  fn count_grams_from<'w, C: Counter>(
+    &self,
+    words: &[&'w str],
+    last: usize,
+    skips_left: usize,
+    min_n: usize,
+    gram: &mut Vec<&'w str>,
+    counter: &mut C,
+  ) {
+    if gram.len() >= min_n {
+      self.count_gram(gram, counter);
+    }
+    if gram.len() == self.n {
+      return;
+    }
+    let furthest = (last + 1 + skips_left).min(words.len() - 1);
+    for next in last + 1..=furthest {
+      gram.push(words[next]);
+      self.count_grams_from(
+        words,
+        next,
+        skips_left - (next - last - 1),
+        min_n,
+        gram,
+        counter,
+      );
+      gram.pop();
+    }
+  }
+
+  /// count a single ngram, unless it is filtered out by the stopwords
+  fn count_gram<C: Counter>(&self, gram: &[&str], counter: &mut C) {
+    if let Some(ref stopwords) = self.stopwords {
+      if stopwords.is_stop_sequence(gram) {
+        return;
       }
     }
+    counter.add(&gram.join(" "), 1);
   }
 }
@@ -0,0 +1,369 @@
+//! Collocation and multiword term extraction over n-gram counts, ranking candidate phrases by
+//! pointwise mutual information, t-score, Dunning's log-likelihood ratio, or by the C-value and
+//! NC-value of nested multiword terms (Frantzi, Ananiadou & Mima, 2000).
+//!
+//! Typical use: add the words of every sentence into a `Collocations::new(2, 4)` (with
+//! `add_tagged_words` instead, to only keep candidates matching a part-of-speech term pattern),
+//! `merge` the per-document instances, then `rank` the candidates by a `Measure`.
+use super::Ngrams;
+use crate::stopwords::Stopwords;
+use lazy_static::lazy_static;
+use regex::Regex;
+use senna::pos::POS;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+lazy_static! {
+  /// The Justeson & Katz (1995) term patterns over coarse tags, i.e. adjective and noun
+  /// sequences ending in a noun, with at most one embedded "noun preposition"
+  static ref TERM_PATTERN: Regex = Regex::new(r"^[AN]*(?:NP)?[AN]*N$").unwrap();
+}
+
+/// An association measure for ranking candidate phrases
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Measure {
+  /// pointwise mutual information, in bits, of the phrase against its independent words
+  Pmi,
+  /// the t-score of the phrase count against the count expected from its independent words
+  TScore,
+  /// Dunning's log-likelihood ratio (G²), at the weakest split of the phrase into two parts
+  LogLikelihood,
+  /// the C-value, which discounts phrases mostly seen nested in longer candidates
+  CValue,
+  /// the NC-value, which adds the weight of the context words a phrase shares with the top
+  /// C-value terms
+  NcValue,
+}
+
+/// A ranked candidate phrase
+#[derive(Clone, Debug, PartialEq)]
+pub struct Collocation {
+  /// the words of the phrase, joined by single spaces
+  pub phrase: String,
+  /// the number of occurrences of the phrase
+  pub count: usize,
+  /// the score of the phrase under the ranking measure
+  pub score: f64,
+}
+
+/// Collects the n-gram counts needed to score candidate phrases
+#[derive(Debug, Clone)]
+pub struct Collocations {
+  /// the shortest candidate phrase length, in words
+  pub min_n: usize,
+  /// the longest candidate phrase length, in words
+  pub max_n: usize,
+  /// candidates seen fewer times are not ranked
+  pub min_count: usize,
+  /// if given, candidates which are stopword phrases, or start or end with a stopword, are not
+  /// ranked, and stopwords are not used as NC-value context words
+  pub stopwords: Option<Arc<Stopwords>>,
+  /// the number of top C-value terms whose context words are weighted for the NC-value
+  pub context_terms: usize,
+  /// the counts of all n-grams of 1 up to `max_n + 1` words, the longest only serving as the
+  /// NC-value contexts of `max_n`-word candidates
+  pub counts: Ngrams,
+  /// the candidates seen at least once with a part-of-speech term pattern, if any words were
+  /// added with `add_tagged_words`
+  pub tagged: Option<HashSet<String>>,
+}
+
+impl Default for Collocations {
+  fn default() -> Collocations { Collocations::new(2, 3) }
+}
+
+impl Collocations {
+  /// collect candidate phrases of `min_n` up to `max_n` words
+  pub fn new(min_n: usize, max_n: usize) -> Self {
+    let max_n = max_n.max(1);
+    Collocations {
+      min_n: min_n.clamp(1, max_n),
+      max_n,
+      min_count: 2,
+      stopwords: None,
+      context_terms: 100,
+      counts: Ngrams {
+        n: max_n + 1,
+        min_n: Some(1),
+        ..Ngrams::default()
+      },
+      tagged: None,
+    }
+  }
+
+  /// add the words of a sentence, or of another continuous word sequence
+  pub fn add_words(&mut self, words: &[&str]) { self.counts.record_words(words.to_vec()); }
+
+  /// add the part-of-speech tagged words of a sentence, e.g. from `Sentence::senna_iter`. Once
+  /// tagged words are added, only the candidates seen at least once with an adjective and noun
+  /// term pattern (e.g. "compact set", "ring of integers") are ranked
+  pub fn add_tagged_words(&mut self, words: &[(&str, POS)]) {
+    self.add_words(&words.iter().map(|(word, _)| *word).collect::<Vec<_>>());
+    let tags: String = words.iter().map(|(_, pos)| coarse_tag(*pos)).collect();
+    let tagged = self.tagged.get_or_insert_with(HashSet::new);
+    for start in 0..words.len() {
+      for end in start + self.min_n..=(start + self.max_n).min(words.len()) {
+        if is_term_pattern(&tags[start..end]) {
+          let phrase: Vec<&str> = words[start..end].iter().map(|(word, _)| *word).collect();
+          tagged.insert(phrase.join(" "));
+        }
+      }
+    }
+  }
+
+  /// add the counts and tagged candidates of another `Collocations` instance, e.g. one filled by
+  /// another thread, keeping the settings of `self`
+  pub fn merge(&mut self, other: Collocations) {
+    self.counts.merge(other.counts);
+    if let Some(other_tagged) = other.tagged {
+      match self.tagged {
+        Some(ref mut tagged) => tagged.extend(other_tagged),
+        None => self.tagged = Some(other_tagged),
+      }
+    }
+  }
+
+  /// the total number of words seen
+  pub fn word_count(&self) -> usize {
+    self
+      .counts
+      .counts
+      .iter()
+      .filter(|(phrase, _)| !phrase.contains(' '))
+      .map(|(_, count)| count)
+      .sum()
+  }
+
+  /// the phrases eligible for ranking, with their counts
+  pub fn candidates(&self) -> Vec<(&str, usize)> {
+    self
+      .counts
+      .counts
+      .iter()
+      .filter(|(phrase, count)| {
+        let words: Vec<&str> = phrase.split(' ').collect();
+        **count >= self.min_count
+          && words.len() >= self.min_n
+          && words.len() <= self.max_n
+          && !self
+            .stopwords
+            .as_ref()
+            .map(|stopwords| stopwords.is_stop_sequence(&words))
+            .unwrap_or(false)
+          && self
+            .tagged
+            .as_ref()
+            .map(|tagged| tagged.contains(phrase.as_str()))
+            .unwrap_or(true)
+      })
+      .map(|(phrase, count)| (phrase.as_str(), *count))
+      .collect()
+  }
+
+  /// the candidate phrases scored by a measure, in descending order of score
+  pub fn rank(&self, measure: Measure) -> Vec<Collocation> {
+    let candidates = self.candidates();
+    let total = self.word_count() as f64;
+    let scores: HashMap<&str, f64> = match measure {
+      Measure::CValue => self.c_values(&candidates),
+      Measure::NcValue => self.nc_values(&candidates),
+      _ => candidates
+        .iter()
+        .map(|(phrase, count)| {
+          let score = match measure {
+            Measure::Pmi => self.pmi(phrase, *count, total),
+            Measure::TScore => self.t_score(phrase, *count, total),
+            _ => self.log_likelihood(phrase, *count, total),
+          };
+          (*phrase, score)
+        })
+        .collect(),
+    };
+    let mut ranked: Vec<Collocation> = candidates
+      .into_iter()
+      .map(|(phrase, count)| Collocation {
+        phrase: phrase.to_string(),
+        count,
+        score: scores[phrase],
+      })
+      .collect();
+    ranked.sort_by(|a, b| {
+      b.score
+        .partial_cmp(&a.score)
+        .unwrap_or(std::cmp::Ordering::Equal)
+        .then_with(|| a.phrase.cmp(&b.phrase))
+    });
+    ranked
+  }
+
+  /// `log2(P(phrase) / (P(w1) ... P(wn)))`
+  fn pmi(&self, phrase: &str, count: usize, total: f64) -> f64 {
+    (count as f64 / self.expected_count(phrase, total)).log2()
+  }
+
+  /// `(count - expected count) / sqrt(count)`
+  fn t_score(&self, phrase: &str, count: usize, total: f64) -> f64 {
+    (count as f64 - self.expected_count(phrase, total)) / (count as f64).sqrt()
+  }
+
+  /// the smallest G² of the 2x2 contingency tables of splitting the phrase into a prefix and a
+  /// suffix, so that longer phrases need every part to be associated
+  fn log_likelihood(&self, phrase: &str, count: usize, total: f64) -> f64 {
+    let words: Vec<&str> = phrase.split(' ').collect();
+    (1..words.len())
+      .map(|split| {
+        let prefix = self.counts.get(&words[..split].join(" ")) as f64;
+        let suffix = self.counts.get(&words[split..].join(" ")) as f64;
+        let both = count as f64;
+        g_squared(
+          [
+            both,
+            (prefix - both).max(0.0),
+            (suffix - both).max(0.0),
+            (total - prefix - suffix + both).max(0.0),
+          ],
+          total,
+        )
+      })
+      .fold(f64::INFINITY, f64::min)
+  }
+
+  /// the count of a phrase expected if its words were independent
+  fn expected_count(&self, phrase: &str, total: f64) -> f64 {
+    let word_probabilities: f64 = phrase
+      .split(' ')
+      .map(|word| self.counts.get(word) as f64 / total)
+      .product();
+    word_probabilities * total
+  }
+
+  /// `log2|a| * (f(a) - mean count of the longer candidates containing a)`
+  fn c_values<'c>(&self, candidates: &[(&'c str, usize)]) -> HashMap<&'c str, f64> {
+    let counts: HashMap<&str, usize> = candidates.iter().copied().collect();
+    let mut nesting: HashMap<&str, (usize, usize)> = HashMap::new();
+    for (phrase, count) in candidates {
+      let words: Vec<&str> = phrase.split(' ').collect();
+      let mut nested = HashSet::new();
+      for length in self.min_n..words.len() {
+        for start in 0..=words.len() - length {
+          let inner = words[start..start + length].join(" ");
+          if let Some((inner, _)) = counts.get_key_value(inner.as_str()) {
+            nested.insert(*inner);
+          }
+        }
+      }
+      for inner in nested {
+        let entry = nesting.entry(inner).or_insert((0, 0));
+        entry.0 += 1;
+        entry.1 += count;
+      }
+    }
+    candidates
+      .iter()
+      .map(|(phrase, count)| {
+        let length = phrase.split(' ').count() as f64;
+        let frequency = match nesting.get(phrase) {
+          Some((containers, container_counts)) => {
+            *count as f64 - *container_counts as f64 / *containers as f64
+          },
+          None => *count as f64,
+        };
+        (*phrase, length.log2() * frequency)
+      })
+      .collect()
+  }
+
+  /// `0.8 C-value(a) + 0.2 sum over the context words b of a of f_a(b) * weight(b)`, where the
+  /// weight of b is the share of the top C-value terms it is a context word of
+  fn nc_values<'c>(&self, candidates: &[(&'c str, usize)]) -> HashMap<&'c str, f64> {
+    let c_values = self.c_values(candidates);
+    // the adjacent words of every candidate, read off the counts of the phrases one word longer
+    let mut contexts: HashMap<&str, HashMap<&str, usize>> = HashMap::new();
+    for (phrase, count) in &self.counts.counts {
+      let (first, rest) = match phrase.split_once(' ') {
+        Some(split) => split,
+        None => continue,
+      };
+      let (init, last) = phrase.rsplit_once(' ').unwrap();
+      for (inner, context) in [(rest, first), (init, last)] {
+        if !c_values.contains_key(inner) || !self.is_context_word(context) {
+          continue;
+        }
+        *contexts
+          .entry(inner)
+          .or_default()
+          .entry(context)
+          .or_insert(0) += count;
+      }
+    }
+    let mut top: Vec<(&str, f64)> = c_values.iter().map(|(p, v)| (*p, *v)).collect();
+    top.sort_by(|a, b| {
+      b.1
+        .partial_cmp(&a.1)
+        .unwrap_or(std::cmp::Ordering::Equal)
+        .then_with(|| a.0.cmp(b.0))
+    });
+    top.truncate(self.context_terms);
+    let mut weights: HashMap<&str, f64> = HashMap::new();
+    for (term, _) in &top {
+      for context in contexts.get(term).into_iter().flat_map(HashMap::keys) {
+        *weights.entry(context).or_insert(0.0) += 1.0 / top.len() as f64;
+      }
+    }
+    c_values
+      .iter()
+      .map(|(phrase, c_value)| {
+        let context_score: f64 = contexts
+          .get(phrase)
+          .into_iter()
+          .flat_map(HashMap::iter)
+          .map(|(context, count)| *count as f64 * weights.get(context).copied().unwrap_or(0.0))
+          .sum();
+        (*phrase, 0.8 * c_value + 0.2 * context_score)
+      })
+      .collect()
+  }
+
+  /// checks if a word may serve as an NC-value context word
+  fn is_context_word(&self, word: &str) -> bool {
+    word
+      .chars()
+      .next()
+      .map(char::is_alphabetic)
+      .unwrap_or(false)
+      && !self
+        .stopwords
+        .as_ref()
+        .map(|stopwords| stopwords.contains(word))
+        .unwrap_or(false)
+  }
+}
+
+/// The coarse term-pattern tag of a part of speech: 'N'oun, 'A'djective, 'P'reposition or 'O'ther
+pub fn coarse_tag(pos: POS) -> char {
+  match pos {
+    POS::NN | POS::NNS | POS::NNP | POS::NNPS => 'N',
+    POS::JJ | POS::JJR | POS::JJS => 'A',
+    POS::IN => 'P',
+    _ => 'O',
+  }
+}
+
+/// Checks if a sequence of coarse tags (see `coarse_tag`) is a term pattern, e.g. "AN" for
+/// "compact set" or "NPN" for "ring of integers"
+pub fn is_term_pattern(tags: &str) -> bool { TERM_PATTERN.is_match(tags) }
+
+/// Dunning's `G² = 2 sum o ln(o / e)` of a 2x2 contingency table, given as
+/// `[both, first only, second only, neither]`
+fn g_squared(observed: [f64; 4], total: f64) -> f64 {
+  let rows = [observed[0] + observed[1], observed[2] + observed[3]];
+  let columns = [observed[0] + observed[2], observed[1] + observed[3]];
+  let mut sum = 0.0;
+  for (index, o) in observed.iter().enumerate() {
+    let expected = rows[index / 2] * columns[index % 2] / total;
+    if *o > 0.0 && expected > 0.0 {
+      sum += o * (o / expected).ln();
+    }
+  }
+  2.0 * sum
+}
@@ -0,0 +1,539 @@
+//! Counting backends for n-gram phrases, sharing the `Counter` trait: exact counts in a
+//! `HashMap<String, usize>` (as in `Ngrams::counts`), and memory-bounded alternatives for
+//! corpus-scale counts, i.e. a count-min sketch, space-saving top-k counters, and sorted runs
+//! spilled to disk and combined by an external merge.
+//!
+//! Any backend can be filled with the settings of an `Ngrams` instance via `Ngrams::count_words`,
+//! and from a parallel corpus walk via `Corpus::fold_with_parallel_walk`.
+use std::cmp::{Ordering, Reverse};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, BinaryHeap, HashMap};
+use std::error::Error;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, BufReader, BufWriter, Lines, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+/// The number of runs spilled by this process, used to name run files uniquely across threads
+static SPILLED_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+/// A store of (possibly approximate) phrase counts
+pub trait Counter: Send {
+  /// count `count` more occurrences of a phrase
+  fn add(&mut self, phrase: &str, count: usize);
+  /// the count of a phrase, which approximate backends may over- or underestimate
+  fn get(&self, phrase: &str) -> usize;
+  /// the `k` phrases with the highest counts, in descending order of count
+  fn top(&self, k: usize) -> Vec<(String, usize)>;
+  /// add the counts of another counter with the same settings, e.g. one filled by another thread
+  fn merge(&mut self, other: Self)
+  where Self: Sized;
+}
+
+/// Exact counts
+impl Counter for HashMap<String, usize> {
+  fn add(&mut self, phrase: &str, count: usize) {
+    match self.get_mut(phrase) {
+      Some(existing) => *existing += count,
+      None => {
+        self.insert(phrase.to_string(), count);
+      },
+    }
+  }
+
+  fn get(&self, phrase: &str) -> usize { HashMap::get(self, phrase).copied().unwrap_or(0) }
+
+  fn top(&self, k: usize) -> Vec<(String, usize)> {
+    top_k(
+      self.iter().map(|(phrase, count)| (phrase.clone(), *count)),
+      k,
+    )
+  }
+
+  fn merge(&mut self, other: Self) {
+    if self.is_empty() {
+      *self = other;
+      return;
+    }
+    for (phrase, count) in other {
+      *self.entry(phrase).or_insert(0) += count;
+    }
+  }
+}
+
+/// A count-min sketch (Cormode & Muthukrishnan, 2005): a fixed-size table of counters, which
+/// never underestimates a count, and overestimates it by at most `ε * total` with probability
+/// `1 - δ`, for a width of `⌈e / ε⌉` and a depth of `⌈ln(1 / δ)⌉`. The highest estimates seen are
+/// tracked as candidates for `top`.
+#[derive(Debug, Clone)]
+pub struct CountMinSketch {
+  /// the number of counters per row
+  width: usize,
+  /// the number of rows, each with its own hash function
+  depth: usize,
+  /// the `depth * width` counters, row by row
+  table: Vec<usize>,
+  /// the total of all added counts
+  total: usize,
+  /// the number of phrases tracked for `top`
+  pub heavy_hitters: usize,
+  /// the tracked phrases, with their estimates when last seen
+  tracked: HashMap<String, usize>,
+}
+
+impl CountMinSketch {
+  /// a sketch with `depth` rows of `width` counters, tracking 1,000 heavy hitters
+  pub fn new(width: usize, depth: usize) -> Self {
+    let (width, depth) = (width.max(1), depth.max(1));
+    CountMinSketch {
+      width,
+      depth,
+      table: vec![0; width * depth],
+      total: 0,
+      heavy_hitters: 1000,
+      tracked: HashMap::new(),
+    }
+  }
+
+  /// a sketch overestimating counts by at most `epsilon` times the total, with probability
+  /// `1 - delta`
+  pub fn with_error(epsilon: f64, delta: f64) -> Self {
+    let width = (std::f64::consts::E / epsilon).ceil() as usize;
+    let depth = (1.0 / delta).ln().ceil() as usize;
+    CountMinSketch::new(width, depth)
+  }
+
+  /// the total of all added counts
+  pub fn total(&self) -> usize { self.total }
+
+  /// the index of a phrase's counter in a row
+  fn cell(&self, row: usize, phrase: &str) -> usize {
+    let mut hasher = DefaultHasher::new();
+    row.hash(&mut hasher);
+    phrase.hash(&mut hasher);
+    row * self.width + (hasher.finish() % self.width as u64) as usize
+  }
+
+  /// track a phrase as a heavy hitter candidate, pruning the tracked phrases to the
+  /// `heavy_hitters` highest estimates once twice as many are tracked
+  fn track(&mut self, phrase: &str, estimate: usize) {
+    if self.heavy_hitters == 0 {
+      return;
+    }
+    match self.tracked.get_mut(phrase) {
+      Some(tracked) => *tracked = estimate,
+      None => {
+        self.tracked.insert(phrase.to_string(), estimate);
+      },
+    }
+    if self.tracked.len() >= 2 * self.heavy_hitters {
+      let kept = top_k(self.tracked.drain(), self.heavy_hitters);
+      self.tracked = kept.into_iter().collect();
+    }
+  }
+}
+
+impl Counter for CountMinSketch {
+  fn add(&mut self, phrase: &str, count: usize) {
+    let mut estimate = usize::MAX;
+    for row in 0..self.depth {
+      let cell = self.cell(row, phrase);
+      self.table[cell] += count;
+      estimate = estimate.min(self.table[cell]);
+    }
+    self.total += count;
+    self.track(phrase, estimate);
+  }
+
+  fn get(&self, phrase: &str) -> usize {
+    (0..self.depth)
+      .map(|row| self.table[self.cell(row, phrase)])
+      .min()
+      .unwrap_or(0)
+  }
+
+  fn top(&self, k: usize) -> Vec<(String, usize)> {
+    top_k(
+      self
+        .tracked
+        .keys()
+        .map(|phrase| (phrase.clone(), self.get(phrase))),
+      k,
+    )
+  }
+
+  /// # Panics
+  /// if the sketches have different dimensions
+  fn merge(&mut self, other: Self) {
+    assert_eq!(
+      (self.width, self.depth),
+      (other.width, other.depth),
+      "only count-min sketches of the same dimensions can be merged"
+    );
+    for (cell, count) in self.table.iter_mut().zip(other.table) {
+      *cell += count;
+    }
+    self.total += other.total;
+    for phrase in other.tracked.into_keys() {
+      let estimate = self.get(&phrase);
+      self.track(&phrase, estimate);
+    }
+    // the estimates of the phrases tracked before the merge may have grown as well
+    let phrases: Vec<String> = self.tracked.keys().cloned().collect();
+    for phrase in phrases {
+      let estimate = self.get(&phrase);
+      self.tracked.insert(phrase, estimate);
+    }
+  }
+}
+
+/// The space-saving algorithm (Metwally, Agrawal & El Abbadi, 2005): monitors at most `capacity`
+/// phrases, each new phrase replacing the one with the lowest count once full. Every phrase with a
+/// count above `total / capacity` is monitored, and a monitored count overestimates the true count
+/// by at most its `error`.
+#[derive(Debug, Clone)]
+pub struct SpaceSaving {
+  /// the maximum number of monitored phrases
+  capacity: usize,
+  /// the monitored phrases, with their counts and maximum overestimation
+  monitored: HashMap<String, (usize, usize)>,
+  /// the monitored phrases, ordered by count
+  by_count: BTreeSet<(usize, String)>,
+}
+
+impl SpaceSaving {
+  /// space-saving counters monitoring at most `capacity` phrases
+  pub fn new(capacity: usize) -> Self {
+    SpaceSaving {
+      capacity: capacity.max(1),
+      monitored: HashMap::new(),
+      by_count: BTreeSet::new(),
+    }
+  }
+
+  /// the maximum overestimation of a monitored phrase's count
+  pub fn error(&self, phrase: &str) -> Option<usize> {
+    self.monitored.get(phrase).map(|(_, error)| *error)
+  }
+
+  /// the lowest monitored count once all counters are in use, which bounds the count of every
+  /// unmonitored phrase, and 0 before
+  pub fn min_count(&self) -> usize {
+    if self.monitored.len() < self.capacity {
+      0
+    } else {
+      self
+        .by_count
+        .iter()
+        .next()
+        .map(|(count, _)| *count)
+        .unwrap_or(0)
+    }
+  }
+
+  /// set the count and error of a monitored phrase
+  fn set(&mut self, phrase: String, count: usize, error: usize) {
+    if let Some((old_count, _)) = self.monitored.get(&phrase) {
+      self.by_count.remove(&(*old_count, phrase.clone()));
+    }
+    self.by_count.insert((count, phrase.clone()));
+    self.monitored.insert(phrase, (count, error));
+  }
+}
+
+impl Counter for SpaceSaving {
+  fn add(&mut self, phrase: &str, count: usize) {
+    if let Some((old_count, error)) = self.monitored.get(phrase) {
+      let (new_count, error) = (old_count + count, *error);
+      self.set(phrase.to_string(), new_count, error);
+    } else if self.monitored.len() < self.capacity {
+      self.set(phrase.to_string(), count, 0);
+    } else {
+      // replace the phrase with the lowest count, inheriting its count as the error bound
+      let (min_count, evicted) = self.by_count.pop_first().unwrap();
+      self.monitored.remove(&evicted);
+      self.set(phrase.to_string(), min_count + count, min_count);
+    }
+  }
+
+  fn get(&self, phrase: &str) -> usize {
+    self
+      .monitored
+      .get(phrase)
+      .map(|(count, _)| *count)
+      .unwrap_or(0)
+  }
+
+  fn top(&self, k: usize) -> Vec<(String, usize)> {
+    self
+      .by_count
+      .iter()
+      .rev()
+      .take(k)
+      .map(|(count, phrase)| (phrase.clone(), *count))
+      .collect()
+  }
+
+  /// Merges as in Agarwal et al. (2012), where a phrase missing from either side is counted with
+  /// that side's `min_count`, keeping the `capacity` highest merged counts
+  fn merge(&mut self, other: Self) {
+    let (own_min, other_min) = (self.min_count(), other.min_count());
+    let mut merged: HashMap<String, (usize, usize)> = HashMap::new();
+    for (phrase, (count, error)) in self.monitored.drain() {
+      let (other_count, other_error) = other
+        .monitored
+        .get(&phrase)
+        .copied()
+        .unwrap_or((other_min, other_min));
+      merged.insert(phrase, (count + other_count, error + other_error));
+    }
+    for (phrase, (count, error)) in other.monitored {
+      merged
+        .entry(phrase)
+        .or_insert((count + own_min, error + own_min));
+    }
+    let mut entries: Vec<(String, (usize, usize))> = merged.into_iter().collect();
+    entries.sort_by(|a, b| b.1 .0.cmp(&a.1 .0).then_with(|| a.0.cmp(&b.0)));
+    entries.truncate(self.capacity);
+    self.by_count.clear();
+    for (phrase, (count, error)) in entries {
+      self.set(phrase, count, error);
+    }
+  }
+}
+
+/// Exact counts with bounded memory: at most `max_entries` phrases are counted in memory, after
+/// which they are spilled to disk as a run sorted by phrase. Runs are combined by an external
+/// k-way merge, when reading counts or on `compact`, and removed once the counter is dropped.
+///
+/// Each run line is a phrase and its count, separated by a tab, so phrases may not contain tabs
+/// or newlines.
+///
+/// # Panics
+/// The `Counter` methods panic if a run can not be written or read, e.g. when the spill directory
+/// is full.
+#[derive(Debug)]
+pub struct SpillCounter {
+  /// the directory holding the spilled runs
+  pub directory: PathBuf,
+  /// the number of distinct phrases counted in memory before spilling a run
+  pub max_entries: usize,
+  /// the counts since the last spill
+  buffer: HashMap<String, usize>,
+  /// the spilled run files
+  runs: Vec<PathBuf>,
+}
+
+impl SpillCounter {
+  /// a counter spilling runs of `max_entries` phrases into `directory`
+  pub fn new<P: AsRef<Path>>(directory: P, max_entries: usize) -> Self {
+    SpillCounter {
+      directory: directory.as_ref().to_path_buf(),
+      max_entries: max_entries.max(1),
+      buffer: HashMap::new(),
+      runs: Vec::new(),
+    }
+  }
+
+  /// the number of runs on disk
+  pub fn run_count(&self) -> usize { self.runs.len() }
+
+  /// write the in-memory counts to disk as a new sorted run
+  pub fn spill(&mut self) -> Result<(), Box<dyn Error>> {
+    if self.buffer.is_empty() {
+      return Ok(());
+    }
+    let mut entries: Vec<(String, usize)> = self.buffer.drain().collect();
+    entries.sort_unstable();
+    let run = self.write_run(entries.into_iter())?;
+    self.runs.push(run);
+    Ok(())
+  }
+
+  /// spill the in-memory counts, then merge all runs into a single one
+  pub fn compact(&mut self) -> Result<(), Box<dyn Error>> {
+    self.spill()?;
+    if self.runs.len() > 1 {
+      let merged = self.write_run(self.sorted_counts()?)?;
+      for run in self.runs.drain(..) {
+        fs::remove_file(run)?;
+      }
+      self.runs.push(merged);
+    }
+    Ok(())
+  }
+
+  /// all counts, merged from the runs and the in-memory counts, in ascending order of phrase
+  pub fn sorted_counts(&self) -> Result<SortedCounts, Box<dyn Error>> {
+    let mut sources: Vec<Box<dyn Iterator<Item = (String, usize)>>> = Vec::new();
+    for run in &self.runs {
+      sources.push(Box::new(RunReader {
+        lines: BufReader::new(File::open(run)?).lines(),
+      }));
+    }
+    let mut buffered: Vec<(String, usize)> = self
+      .buffer
+      .iter()
+      .map(|(phrase, count)| (phrase.clone(), *count))
+      .collect();
+    buffered.sort_unstable();
+    sources.push(Box::new(buffered.into_iter()));
+    Ok(SortedCounts::new(sources))
+  }
+
+  /// write sorted counts to a new run file
+  fn write_run<I>(&self, counts: I) -> Result<PathBuf, Box<dyn Error>>
+  where
+    I: Iterator<Item = (String, usize)>,
+  {
+    fs::create_dir_all(&self.directory)?;
+    let path = self.directory.join(format!(
+      "llamapun-counts-{}-{}.run",
+      process::id(),
+      SPILLED_RUNS.fetch_add(1, AtomicOrdering::SeqCst)
+    ));
+    let mut writer = BufWriter::new(File::create(&path)?);
+    for (phrase, count) in counts {
+      writeln!(writer, "{phrase}\t{count}")?;
+    }
+    writer.flush()?;
+    Ok(path)
+  }
+}
+
+impl Counter for SpillCounter {
+  fn add(&mut self, phrase: &str, count: usize) {
+    self.buffer.add(phrase, count);
+    if self.buffer.len() >= self.max_entries {
+      self.spill().expect("failed to spill counts to disk");
+    }
+  }
+
+  /// reads through every run, which are sorted, up to the phrase
+  fn get(&self, phrase: &str) -> usize {
+    let mut total = Counter::get(&self.buffer, phrase);
+    for run in &self.runs {
+      let file = File::open(run).expect("failed to open a spilled run");
+      for (run_phrase, count) in (RunReader {
+        lines: BufReader::new(file).lines(),
+      }) {
+        match run_phrase.as_str().cmp(phrase) {
+          Ordering::Less => continue,
+          Ordering::Equal => total += count,
+          Ordering::Greater => {},
+        }
+        break;
+      }
+    }
+    total
+  }
+
+  fn top(&self, k: usize) -> Vec<(String, usize)> {
+    top_k(
+      self
+        .sorted_counts()
+        .expect("failed to open the spilled runs"),
+      k,
+    )
+  }
+
+  fn merge(&mut self, mut other: Self) {
+    self.runs.append(&mut other.runs);
+    for (phrase, count) in other.buffer.drain() {
+      self.add(&phrase, count);
+    }
+  }
+}
+
+impl Drop for SpillCounter {
+  fn drop(&mut self) {
+    for run in &self.runs {
+      let _ = fs::remove_file(run);
+    }
+  }
+}
+
+/// Reads the phrases and counts of a spilled run
+struct RunReader {
+  lines: Lines<BufReader<File>>,
+}
+
+impl Iterator for RunReader {
+  type Item = (String, usize);
+  fn next(&mut self) -> Option<(String, usize)> {
+    let line = self.lines.next()?.expect("failed to read a spilled run");
+    let (phrase, count) = line.rsplit_once('\t').expect("malformed spilled run");
+    Some((
+      phrase.to_string(),
+      count.parse().expect("malformed count in a spilled run"),
+    ))
+  }
+}
+
+/// A k-way merge of sources of counts sorted by phrase, summing the counts of equal phrases
+pub struct SortedCounts {
+  /// the sources of sorted counts
+  sources: Vec<Box<dyn Iterator<Item = (String, usize)>>>,
+  /// the next phrase of each source
+  heads: BinaryHeap<Reverse<(String, usize, usize)>>,
+}
+
+impl SortedCounts {
+  fn new(mut sources: Vec<Box<dyn Iterator<Item = (String, usize)>>>) -> Self {
+    let mut heads = BinaryHeap::new();
+    for (index, source) in sources.iter_mut().enumerate() {
+      if let Some((phrase, count)) = source.next() {
+        heads.push(Reverse((phrase, index, count)));
+      }
+    }
+    SortedCounts { sources, heads }
+  }
+
+  /// the next phrase and count from any source, refilling the heap from that source
+  fn pop(&mut self) -> Option<(String, usize)> {
+    let Reverse((phrase, index, count)) = self.heads.pop()?;
+    if let Some((next_phrase, next_count)) = self.sources[index].next() {
+      self.heads.push(Reverse((next_phrase, index, next_count)));
+    }
+    Some((phrase, count))
+  }
+}
+
+impl Iterator for SortedCounts {
+  type Item = (String, usize);
+  fn next(&mut self) -> Option<(String, usize)> {
+    let (phrase, mut count) = self.pop()?;
+    while let Some(Reverse((next_phrase, _, _))) = self.heads.peek() {
+      if *next_phrase != phrase {
+        break;
+      }
+      count += self.pop().unwrap().1;
+    }
+    Some((phrase, count))
+  }
+}
+
+/// the `k` highest counts, in descending order of count and then ascending order of phrase
+fn top_k<I>(counts: I, k: usize) -> Vec<(String, usize)>
+where
+  I: Iterator<Item = (String, usize)>,
+{
+  if k == 0 {
+    return Vec::new();
+  }
+  // a min-heap of the best k seen so far, with the worst at the top
+  let mut best: BinaryHeap<Reverse<(usize, Reverse<String>)>> = BinaryHeap::with_capacity(k + 1);
+  for (phrase, count) in counts {
+    best.push(Reverse((count, Reverse(phrase))));
+    if best.len() > k {
+      best.pop();
+    }
+  }
+  best
+    .into_sorted_vec()
+    .into_iter()
+    .map(|Reverse((count, Reverse(phrase)))| (phrase, count))
+    .collect()
+}
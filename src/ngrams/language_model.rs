@@ -0,0 +1,418 @@
+//! An n-gram language model with interpolated modified Kneser-Ney smoothing (Chen & Goodman,
+//! 1998), trained from the counts of an `Ngrams` instance, with ARPA import/export and scoring of
+//! token sequences.
+//!
+//! Typical use: count sentences with `Ngrams::add_sentence` into a `LanguageModel::counter(3)`,
+//! `train` the model, then flag paragraphs with an unusually high `perplexity`.
+use super::Ngrams;
+use crate::dnm::DNMRange;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+
+/// The sentence start marker
+pub const SENTENCE_START: &str = "<s>";
+/// The sentence end marker
+pub const SENTENCE_END: &str = "</s>";
+/// The out-of-vocabulary word
+pub const UNKNOWN: &str = "<unk>";
+/// The log10 probability used for impossible events, e.g. predicting the sentence start
+const LOG10_ZERO: f64 = -99.0;
+
+/// A backoff n-gram language model
+#[derive(Debug, Clone, Default)]
+pub struct LanguageModel {
+  /// the longest n-gram length of the model
+  pub order: usize,
+  /// per n-gram (words joined by single spaces), its log10 probability and log10 backoff weight
+  entries: HashMap<String, (f64, f64)>,
+}
+
+/// The log10 probabilities of a scored token sequence
+#[derive(Debug, Clone, Default)]
+pub struct Score {
+  /// the total log10 probability of the sequence
+  pub log10_prob: f64,
+  /// the surprisal in bits, i.e. `-log2 p`, of every scored token, including the sentence end
+  pub surprisals: Vec<f64>,
+  /// the number of tokens missing from the vocabulary
+  pub oov_count: usize,
+}
+
+impl Score {
+  /// the number of scored tokens, including the sentence end
+  pub fn token_count(&self) -> usize { self.surprisals.len() }
+
+  /// the perplexity of the sequence, `10^(-log10_prob / token_count)`
+  pub fn perplexity(&self) -> f64 {
+    if self.surprisals.is_empty() {
+      1.0
+    } else {
+      10f64.powf(-self.log10_prob / self.surprisals.len() as f64)
+    }
+  }
+
+  /// combine the scores of consecutive sequences, e.g. the sentences of a paragraph
+  pub fn merge(&mut self, other: Score) {
+    self.log10_prob += other.log10_prob;
+    self.surprisals.extend(other.surprisals);
+    self.oov_count += other.oov_count;
+  }
+}
+
+impl LanguageModel {
+  /// an `Ngrams` counter suited for training a model of the given order, to be filled via
+  /// `Ngrams::add_sentence`
+  pub fn counter(order: usize) -> Ngrams {
+    Ngrams {
+      n: order,
+      min_n: Some(1),
+      ..Ngrams::default()
+    }
+  }
+
+  /// Estimate a model with interpolated modified Kneser-Ney smoothing from n-gram counts, which
+  /// must include all lengths from 1 to `n`, without skips or stopword filtering (see `counter`)
+  pub fn train(ngrams: &Ngrams) -> Result<Self, Box<dyn Error>> {
+    let order = ngrams.n;
+    if order == 0 || ngrams.min_n != Some(1) || ngrams.skip > 0 || ngrams.stopwords.is_some() {
+      return Err(
+        "language model training needs contiguous, unfiltered n-gram counts of all lengths".into(),
+      );
+    }
+    // raw counts, per order
+    let mut raw: Vec<HashMap<&str, f64>> = vec![HashMap::new(); order];
+    for (phrase, count) in &ngrams.counts {
+      let length = phrase.split(' ').count();
+      if length <= order {
+        raw[length - 1].insert(phrase.as_str(), *count as f64);
+      }
+    }
+    if raw[0].is_empty() {
+      return Err("no unigrams to train on".into());
+    }
+
+    // lower orders use continuation counts, the number of distinct words preceding the n-gram,
+    // except for n-grams at the start of a sentence, which have no predecessor
+    let mut adjusted: Vec<HashMap<&str, f64>> = Vec::with_capacity(order);
+    for n in 1..=order {
+      if n == order {
+        adjusted.push(raw[n - 1].clone());
+        continue;
+      }
+      let mut continuations: HashMap<&str, f64> = HashMap::new();
+      for longer in raw[n].keys() {
+        let suffix = &longer[longer.find(' ').unwrap() + 1..];
+        *continuations.entry(suffix).or_insert(0.0) += 1.0;
+      }
+      adjusted.push(
+        raw[n - 1]
+          .iter()
+          .map(|(phrase, count)| {
+            let continuation = continuations.get(phrase).copied().unwrap_or(0.0);
+            if continuation == 0.0 || phrase.starts_with(SENTENCE_START) {
+              (*phrase, *count)
+            } else {
+              (*phrase, continuation)
+            }
+          })
+          .collect(),
+      );
+    }
+
+    let mut model = LanguageModel {
+      order,
+      entries: HashMap::new(),
+    };
+    // the interpolated probabilities of the previous order
+    let mut lower: HashMap<&str, f64> = HashMap::new();
+    // the vocabulary excludes <s>, which is never predicted, but includes <unk>
+    let vocabulary_size = adjusted[0]
+      .keys()
+      .filter(|word| **word != SENTENCE_START)
+      .count() as f64
+      + 1.0;
+    for n in 1..=order {
+      let counts = &adjusted[n - 1];
+      let discounts = discounts(
+        counts
+          .iter()
+          .filter(|(phrase, _)| **phrase != SENTENCE_START)
+          .map(|(_, count)| count),
+      );
+      // per history: total count, and discounted mass
+      let mut histories: HashMap<&str, (f64, f64)> = HashMap::new();
+      for (phrase, count) in counts {
+        if *phrase == SENTENCE_START {
+          continue;
+        }
+        let history = history_of(phrase);
+        let stats = histories.entry(history).or_insert((0.0, 0.0));
+        stats.0 += count;
+        stats.1 += discount(&discounts, *count);
+      }
+      let mut probabilities: HashMap<&str, f64> = HashMap::with_capacity(counts.len());
+      for (phrase, count) in counts {
+        if *phrase == SENTENCE_START {
+          continue;
+        }
+        let history = history_of(phrase);
+        let (total, mass) = histories[history];
+        let lower_probability = if n == 1 {
+          1.0 / vocabulary_size
+        } else {
+          let suffix = &phrase[phrase.find(' ').unwrap() + 1..];
+          lower.get(suffix).copied().unwrap_or(0.0)
+        };
+        let probability =
+          (count - discount(&discounts, *count)) / total + (mass / total) * lower_probability;
+        probabilities.insert(phrase, probability);
+      }
+      if n == 1 {
+        let (total, mass) = *histories.get("").ok_or("no words to train on")?;
+        probabilities.insert(UNKNOWN, (mass / total) / vocabulary_size);
+        model
+          .entries
+          .insert(SENTENCE_START.to_string(), (LOG10_ZERO, 0.0));
+      }
+      for (phrase, probability) in &probabilities {
+        model
+          .entries
+          .entry(phrase.to_string())
+          .or_insert((0.0, 0.0))
+          .0 = probability.log10();
+      }
+      // the backoff weight of a history is its interpolation weight
+      for (history, (total, mass)) in histories {
+        if history.is_empty() {
+          continue;
+        }
+        if let Some(entry) = model.entries.get_mut(history) {
+          entry.1 = if mass > 0.0 {
+            (mass / total).log10()
+          } else {
+            LOG10_ZERO
+          };
+        }
+      }
+      lower = probabilities;
+    }
+    Ok(model)
+  }
+
+  /// the number of n-grams in the model
+  pub fn len(&self) -> usize { self.entries.len() }
+
+  /// checks if the model has no n-grams
+  pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+
+  /// checks if a word is in the vocabulary of the model
+  pub fn contains_word(&self, word: &str) -> bool {
+    !word.contains(' ') && self.entries.contains_key(word)
+  }
+
+  /// the log10 probability of `word` following `context` (of which only the last `order - 1`
+  /// words matter), backing off to shorter contexts as needed
+  pub fn log10_prob(&self, context: &[&str], word: &str) -> f64 {
+    let word = if self.contains_word(word) {
+      word
+    } else {
+      UNKNOWN
+    };
+    let context: Vec<&str> = context
+      .iter()
+      .skip(context.len().saturating_sub(self.order.saturating_sub(1)))
+      .map(|w| if self.contains_word(w) { *w } else { UNKNOWN })
+      .collect();
+    let mut backoff = 0.0;
+    for start in 0..=context.len() {
+      let history = context[start..].join(" ");
+      let phrase = if history.is_empty() {
+        word.to_string()
+      } else {
+        format!("{history} {word}")
+      };
+      if let Some((log10_prob, _)) = self.entries.get(&phrase) {
+        return backoff + log10_prob;
+      }
+      if let Some((_, log10_backoff)) = self.entries.get(&history) {
+        backoff += log10_backoff;
+      }
+    }
+    backoff
+      + self
+        .entries
+        .get(UNKNOWN)
+        .map(|entry| entry.0)
+        .unwrap_or(LOG10_ZERO)
+  }
+
+  /// Score a sentence, predicting each word and the sentence end from the preceding words
+  pub fn score_words(&self, words: &[&str]) -> Score {
+    let mut score = Score::default();
+    let mut context = vec![SENTENCE_START];
+    for word in words.iter().copied().chain(std::iter::once(SENTENCE_END)) {
+      let log10_prob = self.log10_prob(&context, word);
+      if word != SENTENCE_END && !self.contains_word(word) {
+        score.oov_count += 1;
+      }
+      score.log10_prob += log10_prob;
+      score.surprisals.push(-log10_prob / 2f64.log10());
+      context.push(word);
+    }
+    score
+  }
+
+  /// Score the tokens of a sentence, e.g. from `Tokenizer::words`, as `Ngrams::add_ranges`
+  /// records them
+  pub fn score_ranges(&self, tokens: &[DNMRange]) -> Score {
+    let words: Vec<&str> = tokens.iter().map(DNMRange::get_plaintext).collect();
+    self.score_words(&words)
+  }
+
+  /// Score the plaintext of a range as a single sentence, splitting it into words as
+  /// `Ngrams::add_content` does
+  pub fn score_range(&self, range: &DNMRange) -> Score {
+    let words: Vec<&str> = range
+      .get_plaintext()
+      .split_ascii_whitespace()
+      .filter(|&w| w.chars().next().unwrap().is_alphanumeric())
+      .collect();
+    self.score_words(&words)
+  }
+
+  /// Write the model in the ARPA format
+  pub fn write_arpa<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+    let mut by_order: Vec<Vec<(&String, &(f64, f64))>> = vec![Vec::new(); self.order];
+    for entry in &self.entries {
+      by_order[entry.0.split(' ').count() - 1].push(entry);
+    }
+    writeln!(writer, "\n\\data\\")?;
+    for (index, entries) in by_order.iter().enumerate() {
+      writeln!(writer, "ngram {}={}", index + 1, entries.len())?;
+    }
+    for (index, entries) in by_order.iter_mut().enumerate() {
+      entries.sort_by(|a, b| a.0.cmp(b.0));
+      writeln!(writer, "\n\\{}-grams:", index + 1)?;
+      for (phrase, (log10_prob, log10_backoff)) in entries.iter() {
+        let phrase = phrase.replace(' ', "\t");
+        if index + 1 < self.order && *log10_backoff != 0.0 {
+          writeln!(writer, "{log10_prob:.6}\t{phrase}\t{log10_backoff:.6}")?;
+        } else {
+          writeln!(writer, "{log10_prob:.6}\t{phrase}")?;
+        }
+      }
+    }
+    writeln!(writer, "\n\\end\\")?;
+    Ok(())
+  }
+
+  /// Save the model as an ARPA file
+  pub fn save_arpa(&self, path: &str) -> Result<(), Box<dyn Error>> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    self.write_arpa(&mut writer)?;
+    writer.flush()?;
+    Ok(())
+  }
+
+  /// Read a model in the ARPA format
+  pub fn read_arpa<R: BufRead>(reader: R) -> Result<Self, Box<dyn Error>> {
+    let mut model = LanguageModel::default();
+    let mut current_order = 0;
+    for line in reader.lines() {
+      let line = line?;
+      let line = line.trim();
+      if line.is_empty() || line == "\\data\\" {
+        continue;
+      } else if line == "\\end\\" {
+        break;
+      } else if let Some(declaration) = line.strip_prefix("ngram ") {
+        let order: usize = declaration
+          .split('=')
+          .next()
+          .ok_or("malformed ARPA ngram count")?
+          .trim()
+          .parse()?;
+        model.order = model.order.max(order);
+      } else if let Some(section) = line.strip_prefix('\\') {
+        current_order = section
+          .strip_suffix("-grams:")
+          .ok_or_else(|| format!("unknown ARPA section {line}"))?
+          .parse()?;
+      } else if current_order > 0 {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < current_order + 1 || fields.len() > current_order + 2 {
+          return Err(format!("malformed ARPA {current_order}-gram entry: {line}").into());
+        }
+        let log10_prob: f64 = fields[0].parse()?;
+        let log10_backoff: f64 = match fields.get(current_order + 1) {
+          Some(backoff) => backoff.parse()?,
+          None => 0.0,
+        };
+        model.entries.insert(
+          fields[1..=current_order].join(" "),
+          (log10_prob, log10_backoff),
+        );
+      }
+    }
+    if model.order == 0 {
+      return Err("no ARPA \\data\\ header found".into());
+    }
+    Ok(model)
+  }
+
+  /// Load a model from an ARPA file
+  pub fn load_arpa(path: &str) -> Result<Self, Box<dyn Error>> {
+    LanguageModel::read_arpa(BufReader::new(File::open(path)?))
+  }
+}
+
+/// the history of an n-gram, i.e. all words but the last
+fn history_of(phrase: &str) -> &str {
+  match phrase.rfind(' ') {
+    Some(split) => &phrase[..split],
+    None => "",
+  }
+}
+
+/// The modified Kneser-Ney discounts D1, D2 and D3+ for an order, from its count-of-counts
+fn discounts<'c>(counts: impl Iterator<Item = &'c f64>) -> [f64; 3] {
+  let mut count_of_counts = [0.0; 4];
+  for count in counts {
+    let count = *count as usize;
+    if (1..=4).contains(&count) {
+      count_of_counts[count - 1] += 1.0;
+    }
+  }
+  let [n1, n2, n3, n4] = count_of_counts;
+  let y = if n1 + 2.0 * n2 > 0.0 {
+    n1 / (n1 + 2.0 * n2)
+  } else {
+    0.5
+  };
+  let mut discounts = [0.0; 3];
+  for (index, (n_k, n_next)) in [(n1, n2), (n2, n3), (n3, n4)].iter().enumerate() {
+    let k = (index + 1) as f64;
+    let discount = if *n_k > 0.0 {
+      k - (k + 1.0) * y * n_next / n_k
+    } else {
+      k * y
+    };
+    discounts[index] = discount.max(0.0).min(k);
+  }
+  discounts
+}
+
+/// the discount of an (adjusted) count
+fn discount(discounts: &[f64; 3], count: f64) -> f64 {
+  if count <= 0.0 {
+    0.0
+  } else if count < 2.0 {
+    discounts[0]
+  } else if count < 3.0 {
+    discounts[1]
+  } else {
+    discounts[2]
+  }
+}
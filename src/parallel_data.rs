@@ -3,15 +3,36 @@
 //! as well as DOM primitives that allow parallel iterators on XPath results, etc
 use crate::dnm::{DNMParameters, DNMRange, DNM};
 use libxml::readonly::RoNode;
-use std::vec::IntoIter;
+use self::outline::Outline;
 
 /* ---- Containers ----- */
 /// container and API for a Corpus capable of parallel walks over its documents
 pub mod corpus;
+/// defined terms of a Document, with their defining text, as a glossary
+pub mod definition;
 /// container and API for a Document yielded during a parallel corpus walk
 pub mod document;
+/// results of parallel corpus walks which can be combined, for generic map-reduce walks
+pub mod monoid;
+/// the outline of a Document, i.e. the tree of its sections
+pub mod outline;
+/// pairing of the proofs of a Document with the statements they prove
+pub mod proof_link;
+/// the internal structure of proofs, as trees of their cases, steps, claims and items
+pub mod proof_structure;
+/// the cross-reference graph of a Document, over its labelled elements
+pub mod reference_graph;
+/// selections of the documents of a corpus, by manifest, stdin list or glob patterns
+pub mod selection;
+/// document sources of corpus walks: files, gzipped files and archive entries
+pub mod source;
+/// theorem-like statements of a Document, with their headings and content
+pub mod statement;
+/// fault-tolerant corpus walks, with reports of the documents which failed
+pub mod walk;
 pub use self::corpus::Corpus;
 pub use self::document::Document;
+pub use self::statement::{Statement, StatementIterator, StatementOptions};
 
 /// A DNM with associated document parent (e.g. for paragraphs, headings)
 pub struct ItemDNM<'p> {
@@ -31,11 +52,15 @@ pub struct ItemDNMRange<'s> {
 
 /* ---- Iterators ----- */
 
+/// A lazily evaluated walker, boxed so that eager (`Vec`-backed) and streaming sources can share
+/// the iterator types below
+type Walker<'iter, T> = Box<dyn Iterator<Item = T> + 'iter>;
+
 /// Generic iterater over read-only xml nodes. It is the responsibility of the abstraction returning
 /// `NodeIterator` to specify the grouping principle for collecting the nodes
 pub struct RoNodeIterator<'iter> {
   /// A walker over read-only nodes
-  walker: IntoIter<RoNode>,
+  walker: Walker<'iter, RoNode>,
   /// A reference to the owner document
   pub document: &'iter Document<'iter>,
 }
@@ -43,7 +68,7 @@ pub struct RoNodeIterator<'iter> {
 /// A generic iterator over DNMRanges with their associated document (e.g. for sentences)
 pub struct DNMRangeIterator<'iter> {
   /// The walker over the sentence ranges
-  walker: IntoIter<DNMRange<'iter>>,
+  walker: Walker<'iter, DNMRange<'iter>>,
   /// A reference to the document we are working on
   pub document: &'iter Document<'iter>,
 }
@@ -72,10 +97,16 @@ pub trait XPathFilteredIterator<'p> {
   /// the owner document being selected over
   fn get_document(&'p self) -> &'p Document;
 
+  /// the sentences for the resulting selection, yielded on demand. Defaults to walking over
+  /// `to_sentences`, implementors are encouraged to provide a lazy alternative
+  fn sentence_stream(&'p self) -> Box<dyn Iterator<Item = DNMRange<'p>> + 'p> {
+    Box::new(self.to_sentences().into_iter())
+  }
+
   /// Get an iterator over the sentences in this paragraph
   fn iter(&'p mut self) -> DNMRangeIterator<'p> {
     DNMRangeIterator {
-      walker: self.to_sentences().into_iter(),
+      walker: self.sentence_stream(),
       document: self.get_document(),
     }
   }
@@ -86,6 +117,9 @@ impl<'p> XPathFilteredIterator<'p> for ItemDNM<'p> {
   fn to_sentences(&'p self) -> Vec<DNMRange<'p>> {
     self.document.corpus.tokenizer.sentences(&self.dnm)
   }
+  fn sentence_stream(&'p self) -> Box<dyn Iterator<Item = DNMRange<'p>> + 'p> {
+    Box::new(self.document.corpus.tokenizer.sentence_iter(&self.dnm))
+  }
 }
 
 impl<'iter> Iterator for DNMRangeIterator<'iter> {
@@ -107,48 +141,80 @@ impl<'iter> Iterator for DNMRangeIterator<'iter> {
 }
 
 impl<'s> ItemDNMRange<'s> {
+  /// the section path of the range, e.g. `["3 Methods", "3.2 Model"]`
+  pub fn section_path(&self, outline: &Outline) -> Vec<String> {
+    let node = match self.range.dnm.back_map.get(self.range.start) {
+      Some((node, _)) => *node,
+      None => self.range.dnm.root_node,
+    };
+    outline.section_path(node)
+  }
   /// Get an iterator over the words (using rudimentary heuristics)
   pub fn word_iter(&'s mut self) -> DNMRangeIterator<'s> {
     let tokenizer = &self.document.corpus.tokenizer;
-    let words = tokenizer.words(&self.range);
     DNMRangeIterator {
-      walker: words.into_iter(),
+      walker: Box::new(tokenizer.word_iter(&self.range)),
       document: self.document,
     }
   }
   /// Get an iterator over the words and punctuation (using rudimentary heuristics)
   pub fn word_and_punct_iter(&'s mut self) -> DNMRangeIterator<'s> {
     let tokenizer = &self.document.corpus.tokenizer;
-    let words = tokenizer.words_and_punct(&self.range);
     DNMRangeIterator {
-      walker: words.into_iter(),
+      walker: Box::new(tokenizer.word_and_punct_iter(&self.range)),
+      document: self.document,
+    }
+  }
+  /// Get an iterator over the words and punctuation, keeping numeric expressions and quantities
+  /// (e.g. "3.5 km/s") as single ranges
+  pub fn word_punct_and_quantity_iter(&'s mut self) -> DNMRangeIterator<'s> {
+    let tokenizer = &self.document.corpus.tokenizer;
+    DNMRangeIterator {
+      walker: Box::new(tokenizer.words_punct_and_quantities(&self.range).into_iter()),
       document: self.document,
     }
   }
 }
 
 impl<'s> ItemDNM<'s> {
+  /// the section path of the item, e.g. `["3 Methods", "3.2 Model"]`
+  pub fn section_path(&self, outline: &Outline) -> Vec<String> {
+    outline.section_path(self.dnm.root_node)
+  }
   /// Get an iterator over the words (using rudimentary heuristics)
   pub fn word_iter(&'s mut self) -> DNMRangeIterator<'s> {
     let tokenizer = &self.document.corpus.tokenizer;
-    let words = match self.dnm.get_range() {
-      Ok(range) => tokenizer.words(&range),
-      _ => Vec::new(),
+    let words: Walker<'s, DNMRange<'s>> = match self.dnm.get_range() {
+      Ok(range) => Box::new(tokenizer.word_iter(&range)),
+      _ => Box::new(std::iter::empty()),
     };
     DNMRangeIterator {
-      walker: words.into_iter(),
+      walker: words,
       document: self.document,
     }
   }
   /// Get an iterator over the words and punctuation (using rudimentary heuristics)
   pub fn word_and_punct_iter(&'s mut self) -> DNMRangeIterator<'s> {
     let tokenizer = &self.document.corpus.tokenizer;
-    let words = match self.dnm.get_range() {
-      Ok(range) => tokenizer.words_and_punct(&range),
-      _ => Vec::new(),
+    let words: Walker<'s, DNMRange<'s>> = match self.dnm.get_range() {
+      Ok(range) => Box::new(tokenizer.word_and_punct_iter(&range)),
+      _ => Box::new(std::iter::empty()),
+    };
+    DNMRangeIterator {
+      walker: words,
+      document: self.document,
+    }
+  }
+  /// Get an iterator over the words and punctuation, keeping numeric expressions and quantities
+  /// (e.g. "3.5 km/s") as single ranges
+  pub fn word_punct_and_quantity_iter(&'s mut self) -> DNMRangeIterator<'s> {
+    let tokenizer = &self.document.corpus.tokenizer;
+    let words: Walker<'s, DNMRange<'s>> = match self.dnm.get_range() {
+      Ok(range) => Box::new(tokenizer.words_punct_and_quantities(&range).into_iter()),
+      _ => Box::new(std::iter::empty()),
     };
     DNMRangeIterator {
-      walker: words.into_iter(),
+      walker: words,
       document: self.document,
     }
   }
@@ -2,8 +2,13 @@ use jwalk::WalkDir as ParWalkDir;
 use rayon::iter::ParallelBridge;
 use rayon::iter::ParallelIterator;
 use std::collections::HashMap;
+use std::path::Path;
 
 use super::document::Document;
+use super::monoid::Monoid;
+use super::selection::Selection;
+use super::source::{self, DocumentSource, Sources};
+use super::walk::{self, DocumentFailure, FailureKind, WalkOptions, WalkReport};
 use crate::dnm::DNMParameters;
 use crate::tokenizer::Tokenizer;
 
@@ -22,8 +27,13 @@ pub struct Corpus {
   /// Default setting for `DNM` generation
   pub dnm_parameters: DNMParameters,
   /// Extension of corpus files (for specially tailored resources such as DLMF's .html5)
-  /// defaults to selecting .html AND .xhtml files
+  /// defaults to selecting .html AND .xhtml files, also within archives and gzipped files
   pub extension: Option<String>,
+  /// Retries, limits and panic isolation of the parallel walks over the documents
+  pub walk_options: WalkOptions,
+  /// The documents to walk: all those under `path`, by default, or those of a manifest, stdin
+  /// list or glob patterns, resolved against `path`
+  pub selection: Selection,
 }
 
 impl Default for Corpus {
@@ -35,12 +45,15 @@ impl Default for Corpus {
       xml_parser: Parser::default(),
       html_parser: Parser::default_html(),
       dnm_parameters: DNMParameters::llamapun_normalization(),
+      walk_options: WalkOptions::default(),
+      selection: Selection::default(),
     }
   }
 }
 
 impl Corpus {
-  /// Create a new parallel-processing corpus with the base directory `dirpath`
+  /// Create a new parallel-processing corpus with the base directory `dirpath`, which may also be
+  /// a tar, tar.gz or zip archive
   pub fn new(dirpath: String) -> Self {
     Corpus {
       path: dirpath,
@@ -48,103 +61,282 @@ impl Corpus {
     }
   }
 
-  /// Get a parallel iterator over the documents, returning a single report catalog
-  pub fn catalog_with_parallel_walk<F>(&self, closure: F) -> HashMap<String, u64>
-  where F: Fn(Document) -> HashMap<String, u64> + Send + Sync {
-    ParWalkDir::new(self.path.clone())
-      .num_threads(rayon::current_num_threads())
-      .skip_hidden(true)
-      .sort(false)
-      .into_iter()
-      .filter_map(|each| {
-        if let Ok(entry) = each {
-          let file_name = entry.file_name.to_str().unwrap_or("");
-          let selected = if let Some(ref extension) = self.extension {
-            file_name.ends_with(extension)
-          } else {
-            file_name.ends_with(".html") || file_name.ends_with(".xhtml")
-          };
-          if selected {
-            let path = entry.path().to_str().unwrap_or("").to_owned();
-            if !path.is_empty() {
-              return Some(path);
+  /// Create a corpus of the documents listed in a manifest file, one path or paper id per line,
+  /// resolved against the base directory `dirpath` (see `selection::resolve`)
+  pub fn from_manifest(dirpath: String, manifest: String) -> Self {
+    Corpus {
+      path: dirpath,
+      selection: Selection::Manifest(manifest),
+      ..Corpus::default()
+    }
+  }
+
+  /// Create a corpus of the documents listed on stdin, one path or paper id per line, resolved
+  /// against the base directory `dirpath`
+  pub fn from_stdin(dirpath: String) -> Self {
+    Corpus {
+      path: dirpath,
+      selection: Selection::Stdin,
+      ..Corpus::default()
+    }
+  }
+
+  /// Create a corpus of the documents matching any of the glob patterns, e.g. "0903/*.html",
+  /// relative to the base directory `dirpath` unless absolute
+  pub fn from_glob(dirpath: String, patterns: Vec<String>) -> Self {
+    Corpus {
+      path: dirpath,
+      selection: Selection::Glob(patterns),
+      ..Corpus::default()
+    }
+  }
+
+  /// Walk the corpus directory, or archive, or its `selection`, enumerating the selected
+  /// documents, including the entries of archives and gzipped files (see `source::expand`), and
+  /// the failures to read directory entries and archives, or to resolve selected entries
+  fn walk_sources(
+    &self,
+  ) -> impl Iterator<Item = (usize, Result<DocumentSource, DocumentFailure>)> + Send + '_ {
+    let sources: Box<dyn Iterator<Item = _> + Send + '_> = if self.selection != Selection::Walk {
+      self
+        .selection
+        .sources(&self.path, &self.extension, &self.walk_options)
+    } else if Path::new(&self.path).is_file() {
+      source::expand(&self.path, &self.extension, &self.walk_options)
+    } else {
+      Box::new(
+        ParWalkDir::new(self.path.clone())
+          .num_threads(rayon::current_num_threads())
+          .skip_hidden(true)
+          .sort(false)
+          .into_iter()
+          .flat_map(move |each| -> Sources {
+            match each {
+              // directories are walked into, rather than selected, even if named as documents
+              Ok(entry) if entry.path().is_file() => {
+                let path = entry.path().to_str().unwrap_or("").to_owned();
+                source::expand(&path, &self.extension, &self.walk_options)
+              },
+              Ok(_) => Box::new(std::iter::empty()),
+              Err(e) => Box::new(std::iter::once(Err(walk::failure(
+                &self.path,
+                FailureKind::Walk,
+                format!("{e:?}"),
+              )))),
             }
-          }
-        }
-        // all other cases
-        None
-      })
-      .enumerate()
+          }),
+      )
+    };
+    sources.enumerate()
+  }
+
+  /// Load and process a walked document, as guarded by the `walk_options`, logging its failure
+  fn process<T, F>(
+    &self,
+    walk_name: &str,
+    each: (usize, Result<DocumentSource, DocumentFailure>),
+    closure: F,
+  ) -> Result<T, DocumentFailure>
+  where F: FnOnce(Document) -> T {
+    let (index, source) = each;
+    if index % 1000 == 0 && index > 0 {
+      println!("-- {walk_name} now processing document {:?}", 1 + index);
+    }
+    let outcome = source.and_then(|source| {
+      let run = || {
+        let document = match source {
+          DocumentSource::File(ref path) => {
+            walk::check_size(path, &self.walk_options)?;
+            walk::load_with_retries(path, &self.walk_options, || {
+              Document::new(path.clone(), self)
+            })?
+          },
+          DocumentSource::Bytes {
+            ref path,
+            ref content,
+            ..
+          } => walk::load_with_retries(path, &self.walk_options, || {
+            Document::from_bytes(path.clone(), content, self)
+          })?,
+        };
+        walk::run_guarded(source.path(), &self.walk_options, || closure(document))
+      };
+      run().map_err(|failure| failure.in_archive(source.archive()))
+    });
+    if let Err(ref failure) = outcome {
+      println!("-- {walk_name} skipped {failure}");
+    }
+    outcome
+  }
+
+  /// Map the documents to results, and combine them from `identity`, in any grouping, returning
+  /// the report of the documents which failed and were skipped, with `identity` results
+  fn map_reduce<T, I, C, F>(
+    &self,
+    walk_name: &str,
+    identity: I,
+    combine: C,
+    closure: F,
+  ) -> (T, WalkReport)
+  where
+    T: Send,
+    I: Fn() -> T + Send + Sync,
+    C: Fn(T, T) -> T + Send + Sync,
+    F: Fn(Document) -> T + Send + Sync,
+  {
+    self
+      .walk_sources()
       .par_bridge()
       .map(|each| {
-        let (index, path) = each;
-        let document = Document::new(path, self).unwrap();
-        if index % 1000 == 0 && index > 0 {
-          println!(
-            "-- catalog_with_parallel_walk now processing document {:?}",
-            1 + index
-          );
-        }
-        closure(document)
-      })
-      .reduce(HashMap::new, |mut map1, map2| {
-        for (k, v) in map2 {
-          let entry = map1.entry(k).or_insert(0);
-          *entry += v;
-        }
-        map1
+        let mut report = WalkReport::default();
+        let result = report
+          .record(self.process(walk_name, each, &closure))
+          .unwrap_or_else(&identity);
+        (result, report)
       })
+      .reduce(
+        || (identity(), WalkReport::default()),
+        |(result1, mut report1), (result2, report2)| {
+          report1.merge(report2);
+          (combine(result1, result2), report1)
+        },
+      )
   }
 
-  /// Get a parallel iterator over the documents, returning a pair of report catalogs
-    pub fn catalogs_with_parallel_walk<F>(&self, closure: F) -> (HashMap<String, u64>,HashMap<String, u64>)
-  where F: Fn(Document) -> (HashMap<String, u64>,HashMap<String, u64>) + Send + Sync {
-    ParWalkDir::new(self.path.clone())
-      .num_threads(rayon::current_num_threads())
-      .skip_hidden(true)
-      .sort(false)
-      .into_iter()
-      .filter_map(|each| {
-        if let Ok(entry) = each {
-          let file_name = entry.file_name.to_str().unwrap_or("");
-          let selected = if let Some(ref extension) = self.extension {
-            file_name.ends_with(extension)
-          } else {
-            file_name.ends_with(".html") || file_name.ends_with(".xhtml")
-          };
-          if selected {
-            let path = entry.path().to_str().unwrap_or("").to_owned();
-            if !path.is_empty() {
-              return Some(path);
-            }
-          }
-        }
-        // all other cases
-        None
-      })
-      .enumerate()
+  /// Fold the documents into one result per worker thread, each created by `init`, then merge the
+  /// per-thread results into one, returning the report of the documents which failed and were
+  /// skipped
+  fn fold<T, I, F, M>(&self, walk_name: &str, init: I, closure: F, merge: M) -> (T, WalkReport)
+  where
+    T: Send,
+    I: Fn() -> T + Send + Sync,
+    F: Fn(Document, &mut T) + Send + Sync,
+    M: Fn(&mut T, T) + Send + Sync,
+  {
+    self
+      .walk_sources()
       .par_bridge()
-      .map(|each| {
-        let (index, path) = each;
-        let document = Document::new(path, self).unwrap();
-        if index % 1000 == 0 && index > 0 {
-          println!(
-            "-- catalog_with_parallel_walk now processing document {:?}",
-            1 + index
-          );
-        }
-        closure(document)
-      })
-      .reduce(|| (HashMap::new(),HashMap::new()), |(mut map11, mut map12), (map21,map22)| {
-        for (k, v) in map21 {
-          let entry = map11.entry(k).or_insert(0);
-          *entry += v;
-        }
-        for (k, v) in map22 {
-          let entry = map12.entry(k).or_insert(0);
-          *entry += v;
-        }
-        (map11,map12)
+      .fold(
+        || (init(), WalkReport::default()),
+        |(mut result, mut report), each| {
+          report.record(self.process(walk_name, each, |document| closure(document, &mut result)));
+          (result, report)
+        },
+      )
+      .reduce_with(|(mut result, mut report), (other, other_report)| {
+        merge(&mut result, other);
+        report.merge(other_report);
+        (result, report)
       })
+      .unwrap_or_else(|| (init(), WalkReport::default()))
+  }
+
+  /// Get a parallel iterator over the documents, folding them into one result per worker thread
+  /// with `closure`, each result created by `init`, then merging the per-thread results into one
+  /// with `merge`, for results too large to create per document, e.g. counters or models. The
+  /// result of a document which panics, or goes over the maximum duration, may be partial
+  pub fn fold_with_parallel_walk<T, I, F, M>(&self, init: I, closure: F, merge: M) -> T
+  where
+    T: Send,
+    I: Fn() -> T + Send + Sync,
+    F: Fn(Document, &mut T) + Send + Sync,
+    M: Fn(&mut T, T) + Send + Sync,
+  {
+    let (result, report) = self.fold("fold_with_parallel_walk", init, closure, merge);
+    log_report("fold_with_parallel_walk", &report);
+    result
+  }
+
+  /// Get a parallel iterator over the documents, mapping each to a result with `closure`, and
+  /// combining the results into one, as by their `Monoid` implementation, e.g. summing the counts
+  /// of `HashMap<String, u64>` catalogs, or concatenating vectors. Failed documents are skipped
+  pub fn map_reduce_with_parallel_walk<T, F>(&self, closure: F) -> T
+  where
+    T: Monoid,
+    F: Fn(Document) -> T + Send + Sync,
+  {
+    let (result, report) = self.map_reduce_with_parallel_walk_report(closure);
+    log_report("map_reduce_with_parallel_walk", &report);
+    result
+  }
+
+  /// Get a parallel iterator over the documents, combining their `Monoid` results into one, and
+  /// the report of the documents which failed to load or process, and were skipped. The results of
+  /// documents over the `walk_options` maximum duration are discarded
+  pub fn map_reduce_with_parallel_walk_report<T, F>(&self, closure: F) -> (T, WalkReport)
+  where
+    T: Monoid,
+    F: Fn(Document) -> T + Send + Sync,
+  {
+    self.map_reduce("map_reduce_with_parallel_walk", T::empty, combined, closure)
+  }
+
+  /// Get a parallel iterator over the documents, mapping each to a result with `closure`, and
+  /// combining the results with `combine`, starting from `identity`, for the results which are not
+  /// a `Monoid`. `combine` must be associative, with `identity` as its identity
+  pub fn reduce_with_parallel_walk<T, I, C, F>(&self, identity: I, combine: C, closure: F) -> T
+  where
+    T: Send,
+    I: Fn() -> T + Send + Sync,
+    C: Fn(T, T) -> T + Send + Sync,
+    F: Fn(Document) -> T + Send + Sync,
+  {
+    let (result, report) = self.map_reduce("reduce_with_parallel_walk", identity, combine, closure);
+    log_report("reduce_with_parallel_walk", &report);
+    result
+  }
+
+  /// Get a parallel iterator over the documents, returning a single report catalog. Failed
+  /// documents are skipped, see `catalog_with_parallel_walk_report`
+  pub fn catalog_with_parallel_walk<F>(&self, closure: F) -> HashMap<String, u64>
+  where F: Fn(Document) -> HashMap<String, u64> + Send + Sync {
+    let (catalog, report) = self.catalog_with_parallel_walk_report(closure);
+    log_report("catalog_with_parallel_walk", &report);
+    catalog
+  }
+
+  /// Get a parallel iterator over the documents, returning a single report catalog, and the
+  /// report of the documents which failed to load or process, and were skipped. The catalogs of
+  /// documents over the `walk_options` maximum duration are discarded
+  pub fn catalog_with_parallel_walk_report<F>(
+    &self,
+    closure: F,
+  ) -> (HashMap<String, u64>, WalkReport)
+  where F: Fn(Document) -> HashMap<String, u64> + Send + Sync {
+    self.map_reduce(
+      "catalog_with_parallel_walk",
+      HashMap::new,
+      combined,
+      closure,
+    )
+  }
+
+  /// Get a parallel iterator over the documents, returning a pair of report catalogs
+  pub fn catalogs_with_parallel_walk<F>(
+    &self,
+    closure: F,
+  ) -> (HashMap<String, u64>, HashMap<String, u64>)
+  where F: Fn(Document) -> (HashMap<String, u64>, HashMap<String, u64>) + Send + Sync {
+    let (catalogs, report) = self.map_reduce(
+      "catalogs_with_parallel_walk",
+      Monoid::empty,
+      combined,
+      closure,
+    );
+    log_report("catalogs_with_parallel_walk", &report);
+    catalogs
   }
 }
+
+/// log the summary of a walk's report, if any document failed
+fn log_report(walk_name: &str, report: &WalkReport) {
+  if !report.is_clean() {
+    println!("-- {walk_name}: {}", report.summary());
+  }
+}
+
+/// the combination of two results, for `Iterator::reduce`-style closures
+fn combined<T: Monoid>(mut result: T, other: T) -> T {
+  result.combine(other);
+  result
+}
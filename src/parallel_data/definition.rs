@@ -0,0 +1,409 @@
+//! Extraction of defined terms (definienda) and their defining text (definiens) from definitions,
+//! i.e. `AmsEnv::Definition` statements and the paragraphs of `StructuralEnv::Definition`
+//! sections, into a document-level glossary.
+//!
+//! Term candidates come from, by priority:
+//! - emphasis markup, e.g. "A group is *abelian* if ...", with the rest of the sentence as definiens
+//! - copula patterns, e.g. "... is called abelian" or "we say that G is abelian if ..."
+//! - math declarations, e.g. `$f(x) := x^2$`, "we denote by $X$ the set of ..." or "let $X$ be ..."
+use std::collections::HashSet;
+#[cfg(feature = "json")]
+use std::error::Error;
+#[cfg(feature = "json")]
+use std::io::Write;
+
+use lazy_static::lazy_static;
+use libxml::readonly::RoNode;
+use regex::Regex;
+
+use super::statement::StatementOptions;
+use super::Document;
+use crate::ams::{AmsEnv, StructuralEnv};
+use crate::dnm::{DNMParameters, DNMRange, DNM};
+use crate::tokenizer::Tokenizer;
+
+lazy_static! {
+  /// a term following a naming copula, e.g. "is called abelian"
+  static ref CALLED: Regex = Regex::new(
+    r"(?i)\b(?:is|are)\s+(?:called|termed|said\s+to\s+be|known\s+as|referred\s+to\s+as|defined\s+to\s+be)\s+(?:an?\s+|the\s+)?([^,.;:()]+?)(?:\s+(?:if|iff|when|whenever|provided)\b|\s*[,.;:(]|\s*$)"
+  )
+  .unwrap();
+  /// a term introduced by the author, e.g. "we say that G is abelian if" or "we call G abelian if"
+  static ref WE_SAY: Regex = Regex::new(
+    r"(?i)\bwe\s+(?:say\s+(?:that\s+)?.+?\s+(?:is|are)|call\s+.+?)\s+(?:an?\s+|the\s+)?([^,.;:()]+?)\s+(?:if|iff|when|whenever|provided)\b"
+  )
+  .unwrap();
+  /// a math declaration in words, e.g. "we denote by $X$ the", "let $X$ be" or "$X$ denotes"
+  static ref DECLARED: Regex = Regex::new(
+    r"(?i)\b(?:denoted?\s+by\s+(mathformula)(?:\s+the)?|let\s+(mathformula)\s+(?:be|denote)|(mathformula)\s+(?:denotes|stands\s+for))\b"
+  )
+  .unwrap();
+  /// a definitional equation, e.g. `f(x) := x^2`, with the term on the colon side
+  static ref DEFINED_AS: Regex = Regex::new(
+    r"^\s*(.+?)\s*(?::=|\\coloneqq|\\triangleq|\\stackrel\{\\?(?:def|mathrm\{def\}|text\{def\})\}\{=\})\s*(.+?)\s*$"
+  )
+  .unwrap();
+  static ref DEFINED_AS_REVERSED: Regex = Regex::new(r"^\s*(.+?)\s*(?:=:|\\eqqcolon)\s*(.+?)\s*$").unwrap();
+  /// a naming copula closing the text before a term, e.g. "A group ... is called"
+  static ref TRAILING_COPULA: Regex = Regex::new(
+    r"(?i)\s*\b(?:is|are)\s+(?:called|termed|said\s+to\s+be|known\s+as|referred\s+to\s+as|defined\s+to\s+be)(?:\s+(?:an?|the))?\s*$"
+  )
+  .unwrap();
+}
+
+/// The evidence for a term candidate, from the strongest to the weakest
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TermEvidence {
+  /// the term is emphasized, e.g. `<em>` or `ltx_emph`
+  Emphasis,
+  /// the term follows a naming copula, e.g. "is called"
+  Copula,
+  /// the term is a declared math expression, e.g. `f(x) := x^2`
+  MathDeclaration,
+}
+
+impl std::fmt::Display for TermEvidence {
+  fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fmt.write_str(match self {
+      TermEvidence::Emphasis => "emphasis",
+      TermEvidence::Copula => "copula",
+      TermEvidence::MathDeclaration => "math_declaration",
+    })
+  }
+}
+
+/// A defined term candidate, with its defining text, over the DNM of a definition
+#[derive(Debug, Clone)]
+pub struct TermCandidate<'d> {
+  /// the term, e.g. "abelian", or the TeX of a math term, e.g. "f(x)"
+  pub term: String,
+  /// the `<math>` node of a math term
+  pub math: Option<RoNode>,
+  /// the evidence for the candidate
+  pub evidence: TermEvidence,
+  /// the range of the term
+  pub term_range: DNMRange<'d>,
+  /// the range of the defining text
+  pub definiens: DNMRange<'d>,
+}
+
+impl<'d> TermCandidate<'d> {
+  /// the defining text, with collapsed whitespace
+  pub fn definiens_text(&self) -> String { collapse(self.definiens.get_plaintext()) }
+}
+
+/// Extract the defined term candidates of a definition's DNM, e.g. a `Statement::dnm`, at most one
+/// per term position, by priority of evidence
+pub fn extract_terms<'d>(dnm: &'d DNM, tokenizer: &Tokenizer) -> Vec<TermCandidate<'d>> {
+  let sentences: Vec<(usize, usize)> = tokenizer
+    .sentences(dnm)
+    .iter()
+    .map(|sentence| (sentence.start, sentence.end))
+    .collect();
+  let sentence_of = |offset: usize| {
+    sentences
+      .iter()
+      .copied()
+      .find(|(start, end)| *start <= offset && offset < *end)
+  };
+  let range = |start: usize, end: usize| DNMRange { start, end, dnm };
+  let mut candidates = Vec::new();
+
+  // emphasis, with the sentence around it as definiens
+  let emphasized = Document::dfs_filter_nodes(dnm.root_node, &|node| {
+    matches!(node.get_name().as_str(), "em" | "b" | "strong")
+      || node
+        .get_class_names()
+        .iter()
+        .any(|class| class == "ltx_emph" || class == "ltx_font_bold")
+  });
+  for node in emphasized {
+    let term_range = match dnm.get_range_of_node(node) {
+      Ok(term_range) => term_range,
+      Err(_) => continue,
+    };
+    let term = collapse(term_range.get_plaintext());
+    if term.is_empty() || term.split_whitespace().count() > 6 {
+      continue;
+    }
+    if let Some((start, end)) = sentence_of(term_range.start) {
+      let definiens = definiens_around(dnm, (start, end), (term_range.start, term_range.end));
+      candidates.push(TermCandidate {
+        math: math_node(&term_range),
+        term,
+        evidence: TermEvidence::Emphasis,
+        term_range,
+        definiens,
+      });
+    }
+  }
+
+  for (start, end) in sentences.iter().copied() {
+    let text = range(start, end).get_plaintext();
+    // copulas, with the rest of the sentence as definiens
+    for pattern in [&*CALLED, &*WE_SAY] {
+      for captures in pattern.captures_iter(text) {
+        let term_match = captures.get(1).unwrap();
+        let term = collapse(term_match.as_str());
+        if term.is_empty() || term.split_whitespace().count() > 6 {
+          continue;
+        }
+        let term_start = start + char_offset(text, term_match.start());
+        let term_end = start + char_offset(text, term_match.end());
+        let term_range = range(term_start, term_end);
+        candidates.push(TermCandidate {
+          math: math_node(&term_range),
+          term,
+          evidence: TermEvidence::Copula,
+          definiens: definiens_around(dnm, (start, end), (term_start, term_end)),
+          term_range,
+        });
+      }
+    }
+    // math declared in words, with the rest of the sentence as definiens
+    for captures in DECLARED.captures_iter(text) {
+      let math_match = (1..=3).find_map(|group| captures.get(group)).unwrap();
+      let term_start = start + char_offset(text, math_match.start());
+      let term_end = start + char_offset(text, math_match.end());
+      let definiens_start = start + char_offset(text, captures.get(0).unwrap().end());
+      let term_range = range(term_start, term_end);
+      if let Some(math) = math_node(&term_range) {
+        candidates.push(TermCandidate {
+          term: math_tex(math).unwrap_or_else(|| collapse(term_range.get_plaintext())),
+          math: Some(math),
+          evidence: TermEvidence::MathDeclaration,
+          term_range,
+          definiens: range(definiens_start, end).trim(),
+        });
+      }
+    }
+  }
+
+  // definitional equations, with the formula as definiens
+  for math in Document::dfs_filter_nodes(dnm.root_node, &|node| node.get_name() == "math") {
+    let tex = match math_tex(math) {
+      Some(tex) => tex,
+      None => continue,
+    };
+    let term = match DEFINED_AS.captures(&tex) {
+      Some(captures) => captures[1].to_string(),
+      None => match DEFINED_AS_REVERSED.captures(&tex) {
+        Some(captures) => captures[2].to_string(),
+        None => continue,
+      },
+    };
+    if let Ok(math_range) = dnm.get_range_of_node(math) {
+      candidates.push(TermCandidate {
+        term,
+        math: Some(math),
+        evidence: TermEvidence::MathDeclaration,
+        term_range: math_range.clone(),
+        definiens: math_range,
+      });
+    }
+  }
+
+  // keep the strongest candidate for every term position
+  candidates.sort_by_key(|candidate| (candidate.evidence, candidate.term_range.start));
+  let mut kept: Vec<TermCandidate> = Vec::new();
+  for candidate in candidates {
+    let overlaps = kept.iter().any(|other| {
+      candidate.term_range.start < other.term_range.end
+        && other.term_range.start < candidate.term_range.end
+    });
+    if !overlaps {
+      kept.push(candidate);
+    }
+  }
+  kept.sort_by_key(|candidate| candidate.term_range.start);
+  kept
+}
+
+/// the definiens of a term within a sentence: the rest of the sentence after the term, or, for
+/// terms closing their sentence (e.g. "... is called *abelian*."), the sentence before the term,
+/// without its naming copula
+fn definiens_around(dnm: &DNM, sentence: (usize, usize), term: (usize, usize)) -> DNMRange {
+  let after = DNMRange {
+    start: term.1.min(sentence.1),
+    end: sentence.1,
+    dnm,
+  }
+  .trim();
+  let after_text = after.get_plaintext();
+  if after_text.chars().filter(|c| c.is_alphanumeric()).count() > 3 {
+    after
+  } else {
+    let before = DNMRange {
+      start: sentence.0,
+      end: term.0.max(sentence.0),
+      dnm,
+    }
+    .trim();
+    let before_text = before.get_plaintext();
+    match TRAILING_COPULA.find(before_text) {
+      Some(copula) => DNMRange {
+        start: before.start,
+        end: before.start + char_offset(before_text, copula.start()),
+        dnm,
+      }
+      .trim(),
+      None => before,
+    }
+  }
+}
+
+/// the `<math>` node at the start of a range, if any
+fn math_node(range: &DNMRange) -> Option<RoNode> {
+  let node = range.dnm.back_map.get(range.start)?.0;
+  let mut current = Some(node);
+  while let Some(node) = current {
+    if node.get_name() == "math" {
+      return Some(node);
+    }
+    if node == range.dnm.root_node {
+      break;
+    }
+    current = node.get_parent();
+  }
+  None
+}
+
+/// the TeX source of a `<math>` node, if recorded
+fn math_tex(math: RoNode) -> Option<String> {
+  math
+    .get_attribute("alttext")
+    .map(|tex| tex.trim().to_string())
+    .filter(|tex| !tex.is_empty())
+}
+
+/// the number of characters before a byte offset of a text
+fn char_offset(text: &str, byte_offset: usize) -> usize { text[..byte_offset].chars().count() }
+
+/// a text with collapsed whitespace
+fn collapse(text: &str) -> String { text.split_whitespace().collect::<Vec<_>>().join(" ") }
+
+/// A glossary entry of a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlossaryEntry {
+  /// the term, or the TeX of a math term
+  pub term: String,
+  /// the term is a math expression
+  pub is_math: bool,
+  /// the evidence for the term
+  pub evidence: TermEvidence,
+  /// the defining text
+  pub definiens: String,
+  /// the `xml:id` label of the definition, if any
+  pub source_id: Option<String>,
+}
+
+/// The glossary of a document, from its definitions
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Glossary {
+  /// the path of the document
+  pub document: String,
+  /// the entries of the definition statements, then of the definition sections, in document
+  /// order and without repeated terms
+  pub entries: Vec<GlossaryEntry>,
+}
+
+impl Glossary {
+  /// Collect the glossary of a document, from its definition statements, including the inferred
+  /// ones (see `StatementOptions::infer_unmarked`), after their headings, and the paragraphs of
+  /// its definition sections
+  pub fn new(document: &Document) -> Self {
+    let mut glossary = Glossary {
+      document: document.path.clone(),
+      entries: Vec::new(),
+    };
+    let tokenizer = &document.corpus.tokenizer;
+    let mut seen = HashSet::new();
+    // terms within a statement's heading, e.g. a bold run-in "Definition 2.", are left out
+    let mut add_terms = |dnm: &DNM,
+                         heading: Option<RoNode>,
+                         source_id: Option<String>,
+                         entries: &mut Vec<GlossaryEntry>| {
+      let content_start = heading
+        .and_then(|heading| dnm.get_range_of_node(heading).ok())
+        .map(|heading_range| heading_range.end)
+        .unwrap_or(0);
+      for candidate in extract_terms(dnm, tokenizer) {
+        if candidate.term_range.start < content_start {
+          continue;
+        }
+        if seen.insert(candidate.term.clone()) {
+          entries.push(GlossaryEntry {
+            is_math: candidate.math.is_some(),
+            evidence: candidate.evidence,
+            definiens: candidate.definiens_text(),
+            source_id: source_id.clone(),
+            term: candidate.term,
+          });
+        }
+      }
+    };
+
+    let options = StatementOptions {
+      infer_unmarked: true,
+      ..StatementOptions::default()
+    };
+    let mut definition_nodes = HashSet::new();
+    for statement in document.statement_iter(options) {
+      if statement.env == AmsEnv::Definition {
+        definition_nodes.insert(statement.node.to_hashable());
+        add_terms(
+          &statement.dnm,
+          statement.heading,
+          statement.id.clone(),
+          &mut glossary.entries,
+        );
+      }
+    }
+    let outline = document.outline();
+    for section in &outline.sections {
+      if section.env != StructuralEnv::Definition {
+        continue;
+      }
+      let paragraphs = Document::dfs_filter_nodes(section.node, &|node| {
+        node.get_class_names().contains("ltx_para")
+      });
+      for paragraph in paragraphs {
+        // paragraphs of definition statements are already covered
+        if definition_nodes.contains(&paragraph.to_hashable())
+          || paragraph
+            .get_parent()
+            .map(|parent| definition_nodes.contains(&parent.to_hashable()))
+            .unwrap_or(false)
+        {
+          continue;
+        }
+        let dnm = DNM::new(paragraph, DNMParameters::llamapun_normalization());
+        add_terms(
+          &dnm,
+          None,
+          paragraph.get_attribute("id"),
+          &mut glossary.entries,
+        );
+      }
+    }
+    glossary
+  }
+
+  /// Write the entries as JSON objects, one per line, for a JSON Lines dataset
+  #[cfg(feature = "json")]
+  pub fn write_json_lines<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+    for entry in &self.entries {
+      let record = serde_json::json!({
+        "document": self.document,
+        "term": entry.term,
+        "is_math": entry.is_math,
+        "evidence": entry.evidence.to_string(),
+        "definiens": entry.definiens,
+        "source_id": entry.source_id,
+      });
+      serde_json::to_writer(&mut *writer, &record)?;
+      writeln!(writer)?;
+    }
+    Ok(())
+  }
+}
@@ -1,11 +1,18 @@
+use std::cell::OnceCell;
+
 use libxml::parser::XmlParseError;
 use libxml::readonly::RoNode;
 use libxml::tree::Document as XmlDoc;
 use libxml::xpath::Context;
 
 use super::corpus::Corpus;
+use super::definition::Glossary;
+use super::outline::Outline;
+use super::reference_graph::ReferenceGraph;
+use super::statement::{StatementIterator, StatementOptions};
 use super::{DNMRangeIterator, RoNodeIterator};
-use crate::dnm::DNM;
+use crate::dnm::{DNMParameters, DNM};
+use crate::util::heading_language::{self, HeadingLanguage};
 
 /// One of our math documents, thread-friendly
 pub struct Document<'d> {
@@ -17,6 +24,8 @@ pub struct Document<'d> {
   pub corpus: &'d Corpus,
   /// If it exists, the DNM corresponding to this document
   pub dnm: Option<DNM>,
+  /// The detected language of the document, once detected
+  language: OnceCell<Option<HeadingLanguage>>,
 }
 
 impl<'d> Document<'d> {
@@ -33,6 +42,27 @@ impl<'d> Document<'d> {
       dom,
       corpus,
       dnm: None,
+      language: OnceCell::new(),
+    })
+  }
+
+  /// Load a document from its content, e.g. an archive entry, with the path it is known by
+  pub fn from_bytes(
+    path: String,
+    content: &[u8],
+    corpus: &'d Corpus,
+  ) -> Result<Self, XmlParseError> {
+    let dom = if path.ends_with(".xhtml") {
+      corpus.xml_parser.parse_string(content)?
+    } else {
+      corpus.html_parser.parse_string(content)?
+    };
+    Ok(Document {
+      path,
+      dom,
+      corpus,
+      dnm: None,
+      language: OnceCell::new(),
     })
   }
 
@@ -47,11 +77,14 @@ impl<'d> Document<'d> {
   /// Get an iterator over the headings of the document
   pub fn heading_iter(&self) -> RoNodeIterator {
     RoNodeIterator {
-      walker: Document::heading_nodes(&self.dom).into_iter(),
+      walker: Box::new(Document::heading_nodes(&self.dom).into_iter()),
       document: self,
     }
   }
 
+  /// Build the outline of the document, i.e. the tree of its sections
+  pub fn outline(&self) -> Outline { Outline::new(self) }
+
   /// Obtain the problem-free logical paragraphs of a libxml `Document`
   pub fn get_paragraph_nodes(&self) -> Vec<RoNode> { Document::paragraph_nodes(&self.dom) }
 
@@ -65,11 +98,31 @@ impl<'d> Document<'d> {
   /// Get an iterator over the paragraphs of the document
   pub fn paragraph_iter(&self) -> RoNodeIterator {
     RoNodeIterator {
-      walker: Document::paragraph_nodes(&self.dom).into_iter(),
+      walker: Box::new(Document::paragraph_nodes(&self.dom).into_iter()),
       document: self,
     }
   }
 
+  /// Detect the language of the document from the text of its first paragraphs, if it is one of
+  /// the `HeadingLanguage`s with heading normalization tables. Detected once, on the first call
+  pub fn language(&self) -> Option<HeadingLanguage> {
+    *self.language.get_or_init(|| self.detect_language())
+  }
+
+  /// detect the language of the document, see `language`
+  fn detect_language(&self) -> Option<HeadingLanguage> {
+    let mut text = String::new();
+    for paragraph in Document::paragraph_nodes(&self.dom) {
+      let dnm = DNM::new(paragraph, DNMParameters::llamapun_normalization());
+      text.push_str(&heading_language::without_placeholders(&dnm.plaintext));
+      text.push('\n');
+      if text.len() > 2000 {
+        break;
+      }
+    }
+    HeadingLanguage::detect(text.trim())
+  }
+
   /// Obtain the first paragraph of a marked up article abstract (<div class="ltx_abstract"><p>)
   fn abstract_p_node(doc: &XmlDoc) -> Option<RoNode> {
     Document::xpath_node(doc,
@@ -116,11 +169,23 @@ impl<'d> Document<'d> {
     paras.extend(Document::caption_nodes(&self.dom));
 
     RoNodeIterator {
-      walker: paras.into_iter(),
+      walker: Box::new(paras.into_iter()),
       document: self,
     }
   }
 
+  /// Get an iterator over the theorem-like statements of the document (AMS environments and
+  /// proofs), selected by the given options
+  pub fn statement_iter(&self, options: StatementOptions) -> StatementIterator {
+    StatementIterator::new(self, options)
+  }
+
+  /// Resolve the cross-references of the document into a graph over its labelled elements
+  pub fn reference_graph(&self) -> ReferenceGraph { ReferenceGraph::new(self) }
+
+  /// Collect the glossary of the document, i.e. the terms of its definitions
+  pub fn glossary(&self) -> Glossary { Glossary::new(self) }
+
   /// Obtain the MathML <math> nodes of a libxml `Document`
   pub fn get_math_nodes(&self) -> Vec<RoNode> { Document::math_nodes(&self.dom) }
 
@@ -144,9 +209,9 @@ impl<'d> Document<'d> {
       }
     }
     let tokenizer = &self.corpus.tokenizer;
-    let sentences = tokenizer.sentences(self.dnm.as_ref().unwrap());
+    let sentences = tokenizer.sentence_iter(self.dnm.as_ref().unwrap());
     DNMRangeIterator {
-      walker: sentences.into_iter(),
+      walker: Box::new(sentences),
       document: self,
     }
   }
@@ -190,30 +255,76 @@ impl<'d> Document<'d> {
   /// Get an iterator over a custom xpath selector over the document
   pub fn xpath_selector_iter(&self, xpath_str: &str) -> RoNodeIterator {
     RoNodeIterator {
-      walker: Document::xpath_nodes(&self.dom, xpath_str).into_iter(),
+      walker: Box::new(Document::xpath_nodes(&self.dom, xpath_str).into_iter()),
       document: self,
     }
   }
 
   /// Associated function for `get_filtered_nodes`
   pub(crate) fn dfs_filter_nodes(node: RoNode, filter: &dyn Fn(&RoNode) -> bool) -> Vec<RoNode> {
-    let mut found = Vec::new();
-    if filter(&node) {
-      found.push(node);
-    }
-    for child in node.get_child_nodes().into_iter() {
-      found.extend(Document::dfs_filter_nodes(child, filter));
-    }
-    found
+    DescendantFilter::new(node, filter).collect()
+  }
+
+  /// The first node of a subtree accepted by a filter, in depth-first order, if any
+  pub(crate) fn dfs_first_node(node: RoNode, filter: &dyn Fn(&RoNode) -> bool) -> Option<RoNode> {
+    DescendantFilter::new(node, filter).next()
   }
 
   /// Get an iterator using a custom closure predicate filter over the document (depth-first
   /// descent)
-  pub fn filter_iter(&self, filter: &dyn Fn(&RoNode) -> bool) -> RoNodeIterator {
-    // TODO: Can this be lazy? Eager for now...
+  pub fn filter_iter<'f>(&'f self, filter: &'f dyn Fn(&RoNode) -> bool) -> RoNodeIterator<'f> {
     RoNodeIterator {
-      walker: Document::dfs_filter_nodes(self.dom.get_root_readonly().unwrap(), filter).into_iter(),
+      walker: Box::new(DescendantFilter::new(
+        self.dom.get_root_readonly().unwrap(),
+        filter,
+      )),
       document: self,
     }
   }
 }
+
+/// Lazy depth-first (pre-order) walk over a subtree, yielding the nodes accepted by a filter.
+/// Navigates via sibling and parent links, so no intermediate node collections are allocated.
+struct DescendantFilter<'f> {
+  root: RoNode,
+  next_node: Option<RoNode>,
+  filter: &'f dyn Fn(&RoNode) -> bool,
+}
+
+impl<'f> DescendantFilter<'f> {
+  fn new(root: RoNode, filter: &'f dyn Fn(&RoNode) -> bool) -> Self {
+    DescendantFilter {
+      root,
+      next_node: Some(root),
+      filter,
+    }
+  }
+
+  /// the pre-order successor of `node`, without leaving the subtree of `self.root`
+  fn successor(&self, node: RoNode) -> Option<RoNode> {
+    if let Some(child) = node.get_first_child() {
+      return Some(child);
+    }
+    let mut current = node;
+    while current != self.root {
+      if let Some(sibling) = current.get_next_sibling() {
+        return Some(sibling);
+      }
+      current = current.get_parent()?;
+    }
+    None
+  }
+}
+
+impl<'f> Iterator for DescendantFilter<'f> {
+  type Item = RoNode;
+  fn next(&mut self) -> Option<RoNode> {
+    while let Some(node) = self.next_node {
+      self.next_node = self.successor(node);
+      if (self.filter)(&node) {
+        return Some(node);
+      }
+    }
+    None
+  }
+}
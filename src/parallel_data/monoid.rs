@@ -0,0 +1,112 @@
+//! Results of parallel corpus walks which can be combined, for the generic map-reduce walks of
+//! `Corpus`: every document is mapped to a result, and the results of all documents are combined
+//! into one, in no particular order.
+//!
+//! Numbers are summed, maps are combined by key (so that `HashMap<String, u64>` catalogs add up,
+//! and nested maps combine their inner values), vectors are concatenated, and tuples are combined
+//! element by element.
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::Hash;
+
+use super::walk::WalkReport;
+
+/// A result with an empty value, which can be combined with other results. `combine` is
+/// associative, and `empty` its identity, so that the results of a parallel walk can be combined
+/// in any grouping
+pub trait Monoid: Send {
+  /// the empty result, e.g. of a document which failed
+  fn empty() -> Self;
+  /// combine the result of other documents into this one
+  fn combine(&mut self, other: Self);
+}
+
+macro_rules! sum_monoid {
+  ($($number:ty),*) => {$(
+    impl Monoid for $number {
+      fn empty() -> Self { <$number>::default() }
+      fn combine(&mut self, other: Self) { *self += other; }
+    }
+  )*};
+}
+
+sum_monoid!(u32, u64, usize, i32, i64, f32, f64);
+
+impl<K, V> Monoid for HashMap<K, V>
+where
+  K: Eq + Hash + Send,
+  V: Monoid,
+{
+  fn empty() -> Self { HashMap::new() }
+  fn combine(&mut self, other: Self) {
+    for (key, value) in other {
+      match self.get_mut(&key) {
+        Some(existing) => existing.combine(value),
+        None => {
+          self.insert(key, value);
+        },
+      }
+    }
+  }
+}
+
+impl<K, V> Monoid for BTreeMap<K, V>
+where
+  K: Ord + Send,
+  V: Monoid,
+{
+  fn empty() -> Self { BTreeMap::new() }
+  fn combine(&mut self, other: Self) {
+    for (key, value) in other {
+      match self.get_mut(&key) {
+        Some(existing) => existing.combine(value),
+        None => {
+          self.insert(key, value);
+        },
+      }
+    }
+  }
+}
+
+impl<T: Eq + Hash + Send> Monoid for HashSet<T> {
+  fn empty() -> Self { HashSet::new() }
+  fn combine(&mut self, other: Self) {
+    self.extend(other);
+  }
+}
+
+impl<T: Send> Monoid for Vec<T> {
+  fn empty() -> Self { Vec::new() }
+  fn combine(&mut self, mut other: Self) {
+    self.append(&mut other);
+  }
+}
+
+/// `None` is empty, for results whose own empty value is costly, or keeps settings of its own
+impl<T: Monoid> Monoid for Option<T> {
+  fn empty() -> Self { None }
+  fn combine(&mut self, other: Self) {
+    match (self.as_mut(), other) {
+      (Some(result), Some(other)) => result.combine(other),
+      (None, other) => *self = other,
+      (Some(_), None) => {},
+    }
+  }
+}
+
+macro_rules! tuple_monoid {
+  ($($name:ident : $index:tt),*) => {
+    impl<$($name: Monoid),*> Monoid for ($($name,)*) {
+      fn empty() -> Self { ($($name::empty(),)*) }
+      fn combine(&mut self, other: Self) { $(self.$index.combine(other.$index);)* }
+    }
+  };
+}
+
+tuple_monoid!(A: 0, B: 1);
+tuple_monoid!(A: 0, B: 1, C: 2);
+tuple_monoid!(A: 0, B: 1, C: 2, D: 3);
+
+impl Monoid for WalkReport {
+  fn empty() -> Self { WalkReport::default() }
+  fn combine(&mut self, other: Self) { self.merge(other) }
+}
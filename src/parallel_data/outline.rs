@@ -0,0 +1,242 @@
+//! The outline of a document, i.e. the tree of its parts, chapters, (sub)sections, paragraphs,
+//! appendices and bibliography, with their numbering and normalized titles, from which any node can
+//! report its section path, e.g. `["3 Methods", "3.2 Model"]`
+use std::collections::HashMap;
+use std::fmt;
+
+use libxml::readonly::RoNode;
+
+use super::statement::tag_number;
+use super::Document;
+use crate::ams::StructuralEnv;
+use crate::util::data_helpers;
+use crate::util::heading_language::HeadingLanguage;
+
+/// The kinds of sectioning elements, with their LaTeXML classes
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SectionKind {
+  /// `ltx_part`
+  Part,
+  /// `ltx_chapter`
+  Chapter,
+  /// `ltx_section`
+  Section,
+  /// `ltx_subsection`
+  Subsection,
+  /// `ltx_subsubsection`
+  Subsubsection,
+  /// `ltx_paragraph`
+  Paragraph,
+  /// `ltx_subparagraph`
+  Subparagraph,
+  /// `ltx_appendix`
+  Appendix,
+  /// `ltx_bibliography`
+  Bibliography,
+}
+
+/// The sectioning classes, in the order of `SectionKind`
+const SECTION_CLASSES: [(&str, SectionKind); 9] = [
+  ("ltx_part", SectionKind::Part),
+  ("ltx_chapter", SectionKind::Chapter),
+  ("ltx_section", SectionKind::Section),
+  ("ltx_subsection", SectionKind::Subsection),
+  ("ltx_subsubsection", SectionKind::Subsubsection),
+  ("ltx_paragraph", SectionKind::Paragraph),
+  ("ltx_subparagraph", SectionKind::Subparagraph),
+  ("ltx_appendix", SectionKind::Appendix),
+  ("ltx_bibliography", SectionKind::Bibliography),
+];
+
+impl SectionKind {
+  /// the kind of a sectioning element, by its classes, if it is one
+  pub fn of_node(node: &RoNode) -> Option<Self> {
+    let classes = node.get_class_names();
+    SECTION_CLASSES
+      .iter()
+      .find(|(class, _)| classes.contains(*class))
+      .map(|(_, kind)| *kind)
+  }
+}
+
+impl fmt::Display for SectionKind {
+  fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+    let class = SECTION_CLASSES
+      .iter()
+      .find(|(_, kind)| kind == self)
+      .map(|(class, _)| *class)
+      .unwrap_or_default();
+    fmt.write_str(class.trim_start_matches("ltx_"))
+  }
+}
+
+/// A sectioning element of a document
+#[derive(Debug, Clone)]
+pub struct Section {
+  /// the sectioning element
+  pub node: RoNode,
+  /// the kind of sectioning element
+  pub kind: SectionKind,
+  /// the `xml:id` label of the section
+  pub id: Option<String>,
+  /// the tag of the section heading, e.g. "3.2." or "Appendix A"
+  pub tag: Option<String>,
+  /// the number of the section, e.g. "3.2" or "A"
+  pub number: Option<String>,
+  /// the title of the section heading, without its tag, e.g. "Model"
+  pub title: String,
+  /// the title, normalized by `data_helpers::normalize_heading_title_in` the document language
+  pub normalized_title: String,
+  /// the structural environment of the title, in the document language
+  pub env: StructuralEnv,
+  /// the index of the enclosing section, if any
+  pub parent: Option<usize>,
+  /// the indices of the directly nested sections
+  pub children: Vec<usize>,
+}
+
+impl Section {
+  /// the number and title of the section, e.g. "3.2 Model"
+  pub fn label(&self) -> String {
+    match self.number {
+      Some(ref number) if self.title.is_empty() => number.clone(),
+      Some(ref number) => format!("{} {}", number, self.title),
+      None => self.title.clone(),
+    }
+  }
+}
+
+/// The outline of a document, as a tree of its sections
+#[derive(Debug, Clone, Default)]
+pub struct Outline {
+  /// the sections, in document order, each after its enclosing section
+  pub sections: Vec<Section>,
+  /// the detected language of the document, which the titles are normalized in
+  pub language: HeadingLanguage,
+  /// the index of every sectioning element, by node
+  indices: HashMap<usize, usize>,
+}
+
+impl Outline {
+  /// Build the outline of a document
+  pub fn new(document: &Document) -> Self {
+    let xpath = SECTION_CLASSES
+      .iter()
+      .map(|(class, _)| format!("contains(concat(' ',@class,' '),' {class} ')"))
+      .collect::<Vec<_>>()
+      .join(" or ");
+    let mut outline = Outline {
+      language: document.language().unwrap_or_default(),
+      ..Outline::default()
+    };
+    for node in document.get_xpath_nodes(&format!("//*[{xpath}]")) {
+      let kind = match SectionKind::of_node(&node) {
+        Some(kind) => kind,
+        None => continue,
+      };
+      // sections are in document order, so the enclosing ones are already indexed
+      let parent = node
+        .get_parent()
+        .and_then(|parent| outline.section_of(parent));
+      let heading = node
+        .get_child_elements()
+        .into_iter()
+        .find(|child| child.get_class_names().contains("ltx_title"));
+      let (tag, title) = match heading {
+        Some(heading) => heading_tag_and_title(heading),
+        None => (None, String::new()),
+      };
+      let number = tag
+        .as_deref()
+        .and_then(|tag| tag_number(tag.trim_end_matches(['.', ':'])));
+      let index = outline.sections.len();
+      if let Some(parent) = parent {
+        outline.sections[parent].children.push(index);
+      }
+      outline.indices.insert(node.to_hashable(), index);
+      outline.sections.push(Section {
+        node,
+        kind,
+        id: node.get_attribute("id"),
+        tag,
+        number,
+        normalized_title: data_helpers::normalize_heading_title_in(
+          &title.to_lowercase(),
+          outline.language,
+        ),
+        env: StructuralEnv::from_heading(&title, outline.language),
+        title,
+        parent,
+        children: Vec::new(),
+      });
+    }
+    outline
+  }
+
+  /// the top-level sections
+  pub fn roots(&self) -> impl Iterator<Item = &Section> {
+    self
+      .sections
+      .iter()
+      .filter(|section| section.parent.is_none())
+  }
+
+  /// the index of the innermost section containing a node, if any
+  pub fn section_of(&self, node: RoNode) -> Option<usize> {
+    let mut current = Some(node);
+    while let Some(node) = current {
+      if let Some(index) = self.indices.get(&node.to_hashable()) {
+        return Some(*index);
+      }
+      current = node.get_parent();
+    }
+    None
+  }
+
+  /// the sections containing a node, from the outermost to the innermost
+  pub fn ancestry(&self, node: RoNode) -> Vec<&Section> {
+    let mut ancestry = Vec::new();
+    let mut current = self.section_of(node);
+    while let Some(index) = current {
+      let section = &self.sections[index];
+      ancestry.push(section);
+      current = section.parent;
+    }
+    ancestry.reverse();
+    ancestry
+  }
+
+  /// The section path of a node, i.e. the labels of the sections containing it, from the
+  /// outermost to the innermost, e.g. `["3 Methods", "3.2 Model"]`
+  pub fn section_path(&self, node: RoNode) -> Vec<String> {
+    self
+      .ancestry(node)
+      .into_iter()
+      .map(Section::label)
+      .collect()
+  }
+
+  /// the structural environments of the sections containing a node, from the outermost to the
+  /// innermost
+  pub fn env_path(&self, node: RoNode) -> Vec<StructuralEnv> {
+    self
+      .ancestry(node)
+      .into_iter()
+      .map(|section| section.env)
+      .collect()
+  }
+}
+
+/// the tag (e.g. "3.2.") and title (e.g. "Model") of a section heading, with collapsed whitespace
+fn heading_tag_and_title(heading: RoNode) -> (Option<String>, String) {
+  let tag = Document::dfs_first_node(heading, &|n| n.get_class_names().contains("ltx_tag"))
+    .map(|tag_node| tag_node.get_content().trim().to_string())
+    .filter(|tag| !tag.is_empty());
+  let heading_text = heading.get_content();
+  let title = match tag {
+    Some(ref tag) => heading_text.replacen(tag.as_str(), "", 1),
+    None => heading_text,
+  };
+  let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+  (tag, title)
+}
@@ -0,0 +1,253 @@
+//! Pairing of proofs with the statements they prove, from the references and text of proof
+//! headings (e.g. "Proof of Theorem 3.2"), falling back to adjacency, and an export of the pairs
+//! as a dataset for premise selection and proof generation experiments
+use std::collections::HashMap;
+#[cfg(feature = "json")]
+use std::error::Error;
+#[cfg(feature = "json")]
+use std::io::Write;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use super::{Document, Statement};
+use crate::ams::AmsEnv;
+#[cfg(feature = "json")]
+use crate::util::data_helpers::serialize_display;
+
+lazy_static! {
+  static ref PROOF_OF: Regex = Regex::new(
+    r"(?i)\bproof\s+of\s+(?:the\s+)?([[:alpha:]]+)\s*~?\s*([[:alnum:]]+(?:\.[[:alnum:]]+)*)"
+  )
+  .unwrap();
+}
+
+/// The evidence pairing a proof with a statement, from the strongest to the weakest
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+  feature = "json",
+  derive(serde::Serialize),
+  serde(rename_all = "lowercase")
+)]
+pub enum LinkEvidence {
+  /// the proof heading references the statement, e.g. "Proof of Theorem \ref{main}"
+  Reference,
+  /// the proof heading names the statement's tag, e.g. "Proof of Theorem 3.2"
+  Heading,
+  /// the proof directly follows the statement
+  Adjacency,
+  /// the statement is the closest provable one preceding the proof
+  Preceding,
+}
+
+impl LinkEvidence {
+  /// the confidence of a link with this evidence, in [0,1]
+  pub fn confidence(self) -> f64 {
+    match self {
+      LinkEvidence::Reference => 0.95,
+      LinkEvidence::Heading => 0.9,
+      LinkEvidence::Adjacency => 0.8,
+      LinkEvidence::Preceding => 0.5,
+    }
+  }
+}
+
+impl std::fmt::Display for LinkEvidence {
+  fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fmt.write_str(match self {
+      LinkEvidence::Reference => "reference",
+      LinkEvidence::Heading => "heading",
+      LinkEvidence::Adjacency => "adjacency",
+      LinkEvidence::Preceding => "preceding",
+    })
+  }
+}
+
+/// A proof paired with the statement it proves, as indices into a document's statements
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ProofLink {
+  /// the index of the proven statement
+  pub statement: usize,
+  /// the index of the proof
+  pub proof: usize,
+  /// the strongest evidence for the pairing
+  pub evidence: LinkEvidence,
+  /// the confidence of the pairing, in [0,1]
+  pub confidence: f64,
+}
+
+/// Checks if statements of an environment are usually followed by a proof
+pub fn is_provable(env: AmsEnv) -> bool {
+  matches!(
+    env,
+    AmsEnv::Theorem
+      | AmsEnv::Lemma
+      | AmsEnv::Proposition
+      | AmsEnv::Corollary
+      | AmsEnv::Claim
+      | AmsEnv::Fact
+      | AmsEnv::Observation
+      | AmsEnv::Conjecture
+  )
+}
+
+/// Pair every proof among the statements of a document (see `Document::statement_iter`) with the
+/// statement it proves, using its heading's references, then its heading's text, then adjacency.
+/// Proofs without any provable statement before them are left unpaired
+pub fn link_proofs(statements: &[Statement]) -> Vec<ProofLink> {
+  let ids: HashMap<&str, usize> = statements
+    .iter()
+    .enumerate()
+    .filter_map(|(index, statement)| statement.id.as_deref().map(|id| (id, index)))
+    .collect();
+  let mut links = Vec::new();
+  for (proof, statement) in statements.iter().enumerate() {
+    if statement.env != AmsEnv::Proof {
+      continue;
+    }
+    let link = referenced_statement(statement, statements, &ids)
+      .map(|target| (target, LinkEvidence::Reference))
+      .or_else(|| {
+        named_statement(statement, statements).map(|target| (target, LinkEvidence::Heading))
+      })
+      .or_else(|| preceding_statement(proof, statements));
+    if let Some((target, evidence)) = link {
+      links.push(ProofLink {
+        statement: target,
+        proof,
+        evidence,
+        confidence: evidence.confidence(),
+      });
+    }
+  }
+  links
+}
+
+/// the statement referenced by an `ltx_ref` link in a proof heading, if any
+fn referenced_statement(
+  proof: &Statement,
+  statements: &[Statement],
+  ids: &HashMap<&str, usize>,
+) -> Option<usize> {
+  let heading = proof.heading?;
+  Document::dfs_filter_nodes(heading, &|node| node.get_class_names().contains("ltx_ref"))
+    .into_iter()
+    .filter_map(|node| node.get_attribute("href"))
+    .filter_map(|href| href.rsplit('#').next().and_then(|id| ids.get(id)).copied())
+    .find(|target| statements[*target].env != AmsEnv::Proof)
+}
+
+/// the statement named by the text of a proof heading, e.g. "Proof of Theorem 3.2", if any
+fn named_statement(proof: &Statement, statements: &[Statement]) -> Option<usize> {
+  let heading_text = proof.heading?.get_content();
+  let captures = PROOF_OF.captures(&heading_text)?;
+  let (name, number) = (&captures[1], &captures[2]);
+  let candidates: Vec<usize> = statements
+    .iter()
+    .enumerate()
+    .filter(|(_, statement)| {
+      statement.env != AmsEnv::Proof && statement.number.as_deref() == Some(number)
+    })
+    .map(|(index, _)| index)
+    .collect();
+  let named = candidates.iter().copied().find(|index| {
+    let tag = statements[*index].tag.as_deref().unwrap_or_default();
+    tag
+      .split_whitespace()
+      .next()
+      .map(|tag_name| tag_name.eq_ignore_ascii_case(name))
+      .unwrap_or(false)
+  });
+  match (named, candidates.len()) {
+    (Some(index), _) => Some(index),
+    (None, 1) => Some(candidates[0]),
+    _ => None,
+  }
+}
+
+/// the provable statement preceding a proof, if any, as adjacent if there is nothing in between
+fn preceding_statement(proof: usize, statements: &[Statement]) -> Option<(usize, LinkEvidence)> {
+  let target = (0..proof)
+    .rev()
+    .find(|index| is_provable(statements[*index].env))?;
+  let mut previous = statements[proof].node.get_prev_sibling();
+  while let Some(node) = previous {
+    if node.is_element_node() {
+      break;
+    }
+    previous = node.get_prev_sibling();
+  }
+  let evidence = if previous == Some(statements[target].node) {
+    LinkEvidence::Adjacency
+  } else {
+    LinkEvidence::Preceding
+  };
+  Some((target, evidence))
+}
+
+/// A statement and its proof, as a dataset record
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct ProofPair {
+  /// the path of the document
+  pub document: String,
+  /// the environment of the statement
+  #[cfg_attr(feature = "json", serde(serialize_with = "serialize_display"))]
+  pub env: AmsEnv,
+  /// the `xml:id` label of the statement
+  pub statement_id: Option<String>,
+  /// the tag of the statement, e.g. "Theorem 3.2"
+  pub statement_tag: Option<String>,
+  /// the text of the statement
+  pub statement_text: String,
+  /// the `xml:id` label of the proof
+  pub proof_id: Option<String>,
+  /// the text of the proof
+  pub proof_text: String,
+  /// the evidence for the pairing
+  pub evidence: LinkEvidence,
+  /// the confidence of the pairing, in [0,1]
+  pub confidence: f64,
+}
+
+impl ProofPair {
+  /// The pairs of linked statements and proofs, with at least `min_confidence`. The texts are the
+  /// normalized words of the statements, if valid (see `StatementOptions`), or else their plain
+  /// text
+  pub fn from_links(
+    statements: &[Statement],
+    links: &[ProofLink],
+    min_confidence: f64,
+  ) -> Vec<Self> {
+    let text = |statement: &Statement| match statement.text() {
+      Some(text) => text,
+      None => statement.dnm.plaintext.trim().to_string(),
+    };
+    links
+      .iter()
+      .filter(|link| link.confidence >= min_confidence)
+      .map(|link| {
+        let (statement, proof) = (&statements[link.statement], &statements[link.proof]);
+        ProofPair {
+          document: statement.document.path.clone(),
+          env: statement.env,
+          statement_id: statement.id.clone(),
+          statement_tag: statement.tag.clone(),
+          statement_text: text(statement),
+          proof_id: proof.id.clone(),
+          proof_text: text(proof),
+          evidence: link.evidence,
+          confidence: link.confidence,
+        }
+      })
+      .collect()
+  }
+
+  /// Write the pair as a JSON object on a single line, for a JSON Lines dataset
+  #[cfg(feature = "json")]
+  pub fn write_json_line<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+    serde_json::to_writer(&mut *writer, self)?;
+    writeln!(writer)?;
+    Ok(())
+  }
+}
@@ -0,0 +1,300 @@
+//! The internal structure of a proof, as a tree of its parts: cases, steps and claims, marked up as
+//! nested AMS environments (e.g. `ltx_theorem_case`) or introduced by run-in headers (e.g.
+//! "Case 1:" or "Step 2."), and the items of its enumerations. Every part spans a range of the
+//! proof's DNM.
+//!
+//! Run-in parts extend over the following paragraphs, up to the next header of the same or an
+//! enclosing kind, where the kinds which appear first in a proof enclose the later ones, e.g. the
+//! steps within "Case 1: ... Step 1. ... Step 2. ... Case 2: ...". Sub-parts, e.g. "Subcase 1a:",
+//! rank apart from their kind, so that they nest within the parts which precede them.
+use lazy_static::lazy_static;
+use libxml::readonly::RoNode;
+use regex::Regex;
+
+use super::Document;
+use crate::ams::{self, AmsEnv};
+use crate::dnm::{DNMParameters, DNMRange, SpecialTagsOption, DNM};
+
+lazy_static! {
+  /// a run-in proof part header, e.g. "Case 1:", "Step (ii).", "Claim:" or "Subcase 1a:"
+  static ref PART_HEADER: Regex =
+    Regex::new(r"(?i)^\s*(sub-?)?(case|step|claim)(?:\s+([^\s.:]{1,16}))?\s*[.:]").unwrap();
+}
+
+/// The kinds of proof parts
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProofPartKind {
+  /// the whole proof, or a nested proof, e.g. of a claim
+  Proof,
+  /// a case of a case distinction
+  Case,
+  /// a step of an argument
+  Step,
+  /// an auxiliary claim
+  Claim,
+  /// an item of an enumeration
+  Item,
+}
+
+impl ProofPartKind {
+  /// the kind of a proof part environment, if it is one
+  pub fn from_env(env: AmsEnv) -> Option<Self> {
+    match env {
+      AmsEnv::Proof => Some(ProofPartKind::Proof),
+      AmsEnv::Case => Some(ProofPartKind::Case),
+      AmsEnv::Step => Some(ProofPartKind::Step),
+      AmsEnv::Claim => Some(ProofPartKind::Claim),
+      _ => None,
+    }
+  }
+}
+
+impl std::fmt::Display for ProofPartKind {
+  fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+    fmt.write_str(match self {
+      ProofPartKind::Proof => "proof",
+      ProofPartKind::Case => "case",
+      ProofPartKind::Step => "step",
+      ProofPartKind::Claim => "claim",
+      ProofPartKind::Item => "item",
+    })
+  }
+}
+
+/// A part of a proof
+#[derive(Debug, Clone)]
+pub struct ProofPart {
+  /// the kind of part
+  pub kind: ProofPartKind,
+  /// the label of the part, e.g. "1" for "Case 1:", or "(ii)" for an item
+  pub label: Option<String>,
+  /// the first node of the part, i.e. its environment, first paragraph or item
+  pub node: RoNode,
+  /// the part is marked up as an environment or item, rather than introduced by a run-in header
+  pub marked: bool,
+  /// the start offset of the part in the proof's DNM
+  pub start: usize,
+  /// the end offset of the part in the proof's DNM
+  pub end: usize,
+  /// the nested parts, in document order
+  pub children: Vec<ProofPart>,
+}
+
+impl ProofPart {
+  /// all nested parts, depth-first in document order, with their depths below this part
+  pub fn descendants(&self) -> Vec<(usize, &ProofPart)> {
+    let mut descendants = Vec::new();
+    let mut stack: Vec<(usize, &ProofPart)> = self.children.iter().rev().map(|c| (1, c)).collect();
+    while let Some((depth, part)) = stack.pop() {
+      descendants.push((depth, part));
+      stack.extend(part.children.iter().rev().map(|child| (depth + 1, child)));
+    }
+    descendants
+  }
+}
+
+/// The structure of a proof, as a tree of parts over the proof's DNM
+pub struct ProofStructure {
+  /// the DNM of the proof, without its heading
+  pub dnm: DNM,
+  /// the whole proof, with its parts as children
+  pub root: ProofPart,
+}
+
+impl ProofStructure {
+  /// Segment a proof node, e.g. `<div class="ltx_proof">`
+  pub fn new(proof: RoNode) -> Self {
+    let mut parameters = DNMParameters::llamapun_normalization();
+    parameters
+      .special_tag_class_options
+      .insert("ltx_title_proof".to_string(), SpecialTagsOption::Skip);
+    let dnm = DNM::new(proof, parameters);
+    let (start, end) = dnm
+      .get_range()
+      .map(|range| (range.start, range.end))
+      .unwrap_or((0, 0));
+    let children = segment_container(&dnm, proof);
+    ProofStructure {
+      root: ProofPart {
+        kind: ProofPartKind::Proof,
+        label: None,
+        node: proof,
+        marked: true,
+        start,
+        end,
+        children,
+      },
+      dnm,
+    }
+  }
+
+  /// the DNM range of a part
+  pub fn range(&self, part: &ProofPart) -> DNMRange {
+    DNMRange {
+      start: part.start,
+      end: part.end,
+      dnm: &self.dnm,
+    }
+  }
+
+  /// the plain text of a part
+  pub fn text(&self, part: &ProofPart) -> &str { self.range(part).get_plaintext().trim() }
+
+  /// all parts below the root, depth-first in document order, with their depths
+  pub fn parts(&self) -> Vec<(usize, &ProofPart)> { self.root.descendants() }
+}
+
+/// the parts among the children of a proof, a marked part or an item
+fn segment_container(dnm: &DNM, container: RoNode) -> Vec<ProofPart> {
+  let mut parts: Vec<ProofPart> = Vec::new();
+  // the open run-in parts, from the outermost
+  let mut open: Vec<ProofPart> = Vec::new();
+  // the run-in kinds, and whether they are sub-parts, in order of appearance, earlier ones
+  // enclosing later ones
+  let mut kind_order: Vec<(ProofPartKind, bool)> = Vec::new();
+  // the kinds of the open run-in parts
+  let mut open_kinds: Vec<(ProofPartKind, bool)> = Vec::new();
+  for child in container.get_child_elements() {
+    let (start, end) = match dnm.get_range_of_node(child) {
+      Ok(range) => (range.start, range.end),
+      Err(_) => continue,
+    };
+    let env = ams::class_to_env(&child.get_attribute("class").unwrap_or_default());
+    if let Some(kind) = env.and_then(ProofPartKind::from_env) {
+      let part = ProofPart {
+        kind,
+        label: heading_label(child),
+        node: child,
+        marked: true,
+        start,
+        end,
+        children: segment_container(dnm, child),
+      };
+      attach(&mut open, &mut parts, part, end);
+      continue;
+    }
+    if !child.get_class_names().contains("ltx_para") {
+      if let Some(top) = open.last_mut() {
+        top.end = top.end.max(end);
+      }
+      continue;
+    }
+    let text = DNMRange { start, end, dnm }.get_plaintext();
+    if let Some(captures) = PART_HEADER.captures(text) {
+      let kind = match captures[2].to_lowercase().as_str() {
+        "case" => ProofPartKind::Case,
+        "step" => ProofPartKind::Step,
+        _ => ProofPartKind::Claim,
+      };
+      let key = (kind, captures.get(1).is_some());
+      let rank = match kind_order.iter().position(|known| *known == key) {
+        Some(rank) => rank,
+        None => {
+          kind_order.push(key);
+          kind_order.len() - 1
+        },
+      };
+      // close the open parts of the same or enclosed kinds
+      while let Some(top_key) = open_kinds.last() {
+        let top_rank = kind_order.iter().position(|known| known == top_key);
+        if top_rank.map(|top_rank| top_rank >= rank).unwrap_or(true) {
+          open_kinds.pop();
+          let closed = open.pop().unwrap();
+          push_part(&mut open, &mut parts, closed);
+        } else {
+          break;
+        }
+      }
+      open_kinds.push(key);
+      open.push(ProofPart {
+        kind,
+        label: captures.get(3).map(|label| label.as_str().to_string()),
+        node: child,
+        marked: false,
+        start,
+        end,
+        children: enumeration_items(dnm, child),
+      });
+    } else {
+      for item in enumeration_items(dnm, child) {
+        attach(&mut open, &mut parts, item, end);
+      }
+      if let Some(top) = open.last_mut() {
+        top.end = top.end.max(end);
+      }
+    }
+  }
+  while let Some(part) = open.pop() {
+    push_part(&mut open, &mut parts, part);
+  }
+  parts
+}
+
+/// add a part to the innermost open run-in part, extending the open parts to `end`, or else to
+/// the parts of the container
+fn attach(open: &mut [ProofPart], parts: &mut Vec<ProofPart>, part: ProofPart, end: usize) {
+  for open_part in open.iter_mut() {
+    open_part.end = open_part.end.max(end);
+  }
+  match open.last_mut() {
+    Some(top) => top.children.push(part),
+    None => parts.push(part),
+  }
+}
+
+/// add a closed run-in part to its enclosing open part, or else to the parts of the container
+fn push_part(open: &mut [ProofPart], parts: &mut Vec<ProofPart>, part: ProofPart) {
+  match open.last_mut() {
+    Some(top) => {
+      top.end = top.end.max(part.end);
+      top.children.push(part)
+    },
+    None => parts.push(part),
+  }
+}
+
+/// the items of the enumerations directly within a paragraph, as parts
+fn enumeration_items(dnm: &DNM, paragraph: RoNode) -> Vec<ProofPart> {
+  let mut items = Vec::new();
+  for list in paragraph.get_child_elements() {
+    let classes = list.get_class_names();
+    if !classes.contains("ltx_enumerate") && !classes.contains("ltx_itemize") {
+      continue;
+    }
+    for item in list.get_child_elements() {
+      if !item.get_class_names().contains("ltx_item") {
+        continue;
+      }
+      if let Ok(range) = dnm.get_range_of_node(item) {
+        let label = Document::dfs_first_node(item, &|n| n.get_class_names().contains("ltx_tag"))
+          .map(|tag| tag.get_content().trim().to_string())
+          .filter(|tag| !tag.is_empty() && tag != "•");
+        items.push(ProofPart {
+          kind: ProofPartKind::Item,
+          label,
+          node: item,
+          marked: true,
+          start: range.start,
+          end: range.end,
+          children: segment_container(dnm, item),
+        });
+      }
+    }
+  }
+  items
+}
+
+/// the label of a marked part, i.e. the number of its heading tag, e.g. "1" for "Case 1"
+fn heading_label(node: RoNode) -> Option<String> {
+  let heading = node
+    .get_child_elements()
+    .into_iter()
+    .find(|child| child.get_class_names().contains("ltx_title"))?;
+  let tag = Document::dfs_first_node(heading, &|n| n.get_class_names().contains("ltx_tag"))?;
+  let tag = tag.get_content();
+  let words: Vec<&str> = tag.split_whitespace().collect();
+  match words.as_slice() {
+    [_, label, ..] => Some(label.trim_end_matches(['.', ':']).to_string()),
+    _ => None,
+  }
+}
@@ -0,0 +1,470 @@
+//! The cross-reference graph of a document. Its nodes are the labelled elements, i.e. sections,
+//! equations, statements (see `ams::AmsEnv`), figures, tables and bibliography items, identified
+//! by their `xml:id`. Its edges are the resolved `ltx_ref` references, from the innermost labelled
+//! element containing the reference, with the sentence in which it occurs.
+//!
+//! Proofs are usually not labelled, and are identified by their position instead, as "proof1",
+//! "proof2", etc., so that the results a proof relies on remain its outgoing references.
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use libxml::readonly::RoNode;
+
+use super::outline::SectionKind;
+use super::Document;
+use crate::ams::{self, AmsEnv};
+use crate::dnm::{DNMParameters, DNMRange, DNM};
+use crate::tokenizer::Tokenizer;
+
+/// The id of the node standing in for the document, as the source of the references outside of
+/// any labelled element, e.g. in the abstract
+pub const DOCUMENT_ID: &str = "document";
+
+/// The classes of the elements whose sentences are the contexts of references
+const CONTEXT_CLASSES: [&str; 4] = ["ltx_para", "ltx_caption", "ltx_title", "ltx_bibitem"];
+
+/// The kinds of labelled elements
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LabelKind {
+  /// the document itself
+  Document,
+  /// a part, chapter, (sub)section, paragraph, appendix or bibliography
+  Section,
+  /// an equation or equation group
+  Equation,
+  /// an AMS statement or proof
+  Statement(AmsEnv),
+  /// a figure
+  Figure,
+  /// a table
+  Table,
+  /// a bibliography item
+  Bibitem,
+}
+
+impl LabelKind {
+  /// the kind of a labelled element, by its classes, if it is one
+  pub fn of_node(node: &RoNode) -> Option<Self> {
+    let classes = node.get_class_names();
+    let has = |class: &str| classes.contains(class);
+    if has("ltx_theorem") || has("ltx_proof") {
+      ams::class_to_env(&node.get_attribute("class").unwrap_or_default()).map(LabelKind::Statement)
+    } else if SectionKind::of_node(node).is_some() {
+      Some(LabelKind::Section)
+    } else if has("ltx_equation") || has("ltx_equationgroup") {
+      Some(LabelKind::Equation)
+    } else if has("ltx_figure") {
+      Some(LabelKind::Figure)
+    } else if has("ltx_table") {
+      Some(LabelKind::Table)
+    } else if has("ltx_bibitem") {
+      Some(LabelKind::Bibitem)
+    } else {
+      None
+    }
+  }
+
+  /// the environment of a statement
+  pub fn env(self) -> Option<AmsEnv> {
+    match self {
+      LabelKind::Statement(env) => Some(env),
+      _ => None,
+    }
+  }
+}
+
+impl fmt::Display for LabelKind {
+  fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+    fmt.write_str(match self {
+      LabelKind::Document => "document",
+      LabelKind::Section => "section",
+      LabelKind::Equation => "equation",
+      LabelKind::Statement(_) => "statement",
+      LabelKind::Figure => "figure",
+      LabelKind::Table => "table",
+      LabelKind::Bibitem => "bibitem",
+    })
+  }
+}
+
+/// A labelled element of a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelledNode {
+  /// the `xml:id` of the element
+  pub id: String,
+  /// the kind of element
+  pub kind: LabelKind,
+  /// the tag of the element, e.g. "Theorem 3.2", "(2.1)" or "[12]"
+  pub tag: Option<String>,
+}
+
+/// A reference between two labelled elements
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferenceEdge {
+  /// the index of the innermost labelled element containing the reference
+  pub source: usize,
+  /// the index of the referenced element
+  pub target: usize,
+  /// the text of the reference, e.g. "3.2"
+  pub text: String,
+  /// the sentence in which the reference occurs, if any
+  pub context: String,
+}
+
+/// The export formats of a reference graph
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GraphFormat {
+  /// a Graphviz `digraph`
+  Dot,
+  /// a GraphML document, with `kind`, `env` and `tag` node data, and `text` and `context` edge
+  /// data
+  GraphMl,
+  /// a JSON object with the `nodes` and `edges` arrays, with the `json` feature
+  Json,
+}
+
+/// The cross-reference graph of a document
+#[derive(Debug, Clone)]
+pub struct ReferenceGraph {
+  /// the path of the document
+  pub document: String,
+  /// the labelled elements, in document order, after the `DOCUMENT_ID` node
+  pub nodes: Vec<LabelledNode>,
+  /// the references, in document order
+  pub edges: Vec<ReferenceEdge>,
+  /// the number of references to ids outside of the document's labelled elements
+  pub unresolved: usize,
+  /// the index of every node id
+  ids: HashMap<String, usize>,
+}
+
+impl ReferenceGraph {
+  /// Resolve the `ltx_ref` references of a document into a graph
+  pub fn new(document: &Document) -> Self {
+    let mut graph = ReferenceGraph {
+      document: document.path.clone(),
+      nodes: Vec::new(),
+      edges: Vec::new(),
+      unresolved: 0,
+      ids: HashMap::new(),
+    };
+    graph.push(DOCUMENT_ID.to_string(), LabelKind::Document, None);
+    let mut node_indices = HashMap::new();
+    let mut proof_count = 0;
+    for node in document.get_xpath_nodes("//*[@id or @class='ltx_proof']") {
+      let kind = match LabelKind::of_node(&node) {
+        Some(kind) => kind,
+        None => continue,
+      };
+      let id = match node.get_attribute("id") {
+        Some(id) => id,
+        None => {
+          proof_count += 1;
+          format!("proof{}", proof_count)
+        },
+      };
+      let tag = Document::dfs_first_node(node, &|n| n.get_class_names().contains("ltx_tag"))
+        .map(|tag_node| tag_node.get_content().trim().to_string())
+        .filter(|tag| !tag.is_empty());
+      node_indices.insert(node.to_hashable(), graph.nodes.len());
+      graph.push(id, kind, tag);
+    }
+
+    let mut contexts = HashMap::new();
+    for reference in document.get_ref_nodes() {
+      let href = reference.get_attribute("href").unwrap_or_default();
+      let target = match href.split_once('#') {
+        Some((_, id)) => match graph.ids.get(id) {
+          Some(target) => *target,
+          None => {
+            graph.unresolved += 1;
+            continue;
+          },
+        },
+        None => continue, // external links, e.g. URLs
+      };
+      let mut source = 0;
+      let mut context_root = None;
+      let mut ancestor = reference.get_parent();
+      while let Some(node) = ancestor {
+        if let Some(index) = node_indices.get(&node.to_hashable()) {
+          source = *index;
+          break;
+        }
+        if context_root.is_none()
+          && CONTEXT_CLASSES
+            .iter()
+            .any(|class| node.get_class_names().contains(*class))
+        {
+          context_root = Some(node);
+        }
+        ancestor = node.get_parent();
+      }
+      let context = match context_root {
+        Some(root) => sentence_context(&mut contexts, root, reference, &document.corpus.tokenizer),
+        None => String::new(),
+      };
+      graph.edges.push(ReferenceEdge {
+        source,
+        target,
+        text: reference.get_content().trim().to_string(),
+        context,
+      });
+    }
+    graph
+  }
+
+  /// add a node with the next index
+  fn push(&mut self, id: String, kind: LabelKind, tag: Option<String>) {
+    self.ids.insert(id.clone(), self.nodes.len());
+    self.nodes.push(LabelledNode { id, kind, tag });
+  }
+
+  /// the index of the node with an id
+  pub fn index(&self, id: &str) -> Option<usize> { self.ids.get(id).copied() }
+
+  /// the node with an id
+  pub fn node(&self, id: &str) -> Option<&LabelledNode> {
+    self.index(id).map(|index| &self.nodes[index])
+  }
+
+  /// the references from within the element with an id
+  pub fn references_from<'g>(&'g self, id: &str) -> impl Iterator<Item = &'g ReferenceEdge> + 'g {
+    let source = self.index(id);
+    self
+      .edges
+      .iter()
+      .filter(move |edge| Some(edge.source) == source)
+  }
+
+  /// the references to the element with an id
+  pub fn references_to<'g>(&'g self, id: &str) -> impl Iterator<Item = &'g ReferenceEdge> + 'g {
+    let target = self.index(id);
+    self
+      .edges
+      .iter()
+      .filter(move |edge| Some(edge.target) == target)
+  }
+
+  /// The distinct elements referenced from within the element with an id, in order of first
+  /// reference, e.g. the results a proof relies on
+  pub fn dependencies(&self, id: &str) -> Vec<&LabelledNode> {
+    let mut seen = HashSet::new();
+    self
+      .references_from(id)
+      .filter(|edge| seen.insert(edge.target))
+      .map(|edge| &self.nodes[edge.target])
+      .collect()
+  }
+
+  /// Write the graph in an export format
+  pub fn write<W: Write>(&self, writer: &mut W, format: GraphFormat) -> Result<(), Box<dyn Error>> {
+    match format {
+      GraphFormat::Dot => self.write_dot(writer),
+      GraphFormat::GraphMl => self.write_graphml(writer),
+      GraphFormat::Json => self.write_json(writer),
+    }
+  }
+
+  /// Save the graph to a file, in an export format
+  pub fn save(&self, path: &str, format: GraphFormat) -> Result<(), Box<dyn Error>> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    self.write(&mut writer, format)?;
+    writer.flush()?;
+    Ok(())
+  }
+
+  /// write a Graphviz `digraph`, labelling nodes by their tags
+  fn write_dot<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+    writeln!(writer, "digraph \"{}\" {{", dot_escape(&self.document))?;
+    for node in &self.nodes {
+      write!(
+        writer,
+        "  \"{}\" [label=\"{}\", kind=\"{}\"",
+        dot_escape(&node.id),
+        dot_escape(node.tag.as_deref().unwrap_or(&node.id)),
+        node.kind
+      )?;
+      if let Some(env) = node.kind.env() {
+        write!(writer, ", env=\"{env}\"")?;
+      }
+      writeln!(writer, "];")?;
+    }
+    for edge in &self.edges {
+      writeln!(
+        writer,
+        "  \"{}\" -> \"{}\" [context=\"{}\"];",
+        dot_escape(&self.nodes[edge.source].id),
+        dot_escape(&self.nodes[edge.target].id),
+        dot_escape(&edge.context)
+      )?;
+    }
+    writeln!(writer, "}}")?;
+    Ok(())
+  }
+
+  /// write a GraphML document
+  fn write_graphml<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+    writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(
+      writer,
+      "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">"
+    )?;
+    for (key, domain) in [
+      ("kind", "node"),
+      ("env", "node"),
+      ("tag", "node"),
+      ("text", "edge"),
+      ("context", "edge"),
+    ] {
+      writeln!(
+        writer,
+        "  <key id=\"{key}\" for=\"{domain}\" attr.name=\"{key}\" attr.type=\"string\"/>"
+      )?;
+    }
+    writeln!(
+      writer,
+      "  <graph id=\"{}\" edgedefault=\"directed\">",
+      xml_escape(&self.document)
+    )?;
+    for node in &self.nodes {
+      writeln!(writer, "    <node id=\"{}\">", xml_escape(&node.id))?;
+      writeln!(writer, "      <data key=\"kind\">{}</data>", node.kind)?;
+      if let Some(env) = node.kind.env() {
+        writeln!(writer, "      <data key=\"env\">{env}</data>")?;
+      }
+      if let Some(ref tag) = node.tag {
+        writeln!(writer, "      <data key=\"tag\">{}</data>", xml_escape(tag))?;
+      }
+      writeln!(writer, "    </node>")?;
+    }
+    for edge in &self.edges {
+      writeln!(
+        writer,
+        "    <edge source=\"{}\" target=\"{}\">",
+        xml_escape(&self.nodes[edge.source].id),
+        xml_escape(&self.nodes[edge.target].id)
+      )?;
+      writeln!(
+        writer,
+        "      <data key=\"text\">{}</data>",
+        xml_escape(&edge.text)
+      )?;
+      writeln!(
+        writer,
+        "      <data key=\"context\">{}</data>",
+        xml_escape(&edge.context)
+      )?;
+      writeln!(writer, "    </edge>")?;
+    }
+    writeln!(writer, "  </graph>")?;
+    writeln!(writer, "</graphml>")?;
+    Ok(())
+  }
+
+  /// write a JSON object with the `nodes` and `edges` arrays
+  #[cfg(feature = "json")]
+  fn write_json<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+    let nodes: Vec<serde_json::Value> = self
+      .nodes
+      .iter()
+      .map(|node| {
+        serde_json::json!({
+          "id": node.id,
+          "kind": node.kind.to_string(),
+          "env": node.kind.env().map(|env| env.to_string()),
+          "tag": node.tag,
+        })
+      })
+      .collect();
+    let edges: Vec<serde_json::Value> = self
+      .edges
+      .iter()
+      .map(|edge| {
+        serde_json::json!({
+          "source": self.nodes[edge.source].id,
+          "target": self.nodes[edge.target].id,
+          "text": edge.text,
+          "context": edge.context,
+        })
+      })
+      .collect();
+    let graph = serde_json::json!({
+      "document": self.document,
+      "nodes": nodes,
+      "edges": edges,
+    });
+    serde_json::to_writer_pretty(&mut *writer, &graph)?;
+    writeln!(writer)?;
+    Ok(())
+  }
+
+  /// JSON exports are only available with the `json` feature
+  #[cfg(not(feature = "json"))]
+  fn write_json<W: Write>(&self, _writer: &mut W) -> Result<(), Box<dyn Error>> {
+    Err("JSON exports of reference graphs require the json feature".into())
+  }
+}
+
+/// the DNM of a context element, with the bounds of its sentences
+type SentenceContexts = (DNM, Vec<(usize, usize)>);
+
+/// the sentence of a context element in which a reference occurs, splitting each context element
+/// into sentences only once
+fn sentence_context(
+  contexts: &mut HashMap<usize, SentenceContexts>,
+  root: RoNode,
+  reference: RoNode,
+  tokenizer: &Tokenizer,
+) -> String {
+  let (dnm, sentences) = contexts.entry(root.to_hashable()).or_insert_with(|| {
+    // keep the text of the references, e.g. "Theorem 3.2" rather than "REF"
+    let mut parameters = DNMParameters::llamapun_normalization();
+    parameters.special_tag_class_options.remove("ltx_ref");
+    let dnm = DNM::new(root, parameters);
+    let sentences = tokenizer
+      .sentences(&dnm)
+      .iter()
+      .map(|sentence| (sentence.start, sentence.end))
+      .collect();
+    (dnm, sentences)
+  });
+  let position = match dnm.get_range_of_node(reference) {
+    Ok(range) => range.start,
+    Err(_) => return String::new(),
+  };
+  sentences
+    .iter()
+    .find(|(start, end)| *start <= position && position < *end)
+    .map(|(start, end)| {
+      DNMRange {
+        start: *start,
+        end: *end,
+        dnm,
+      }
+      .get_plaintext()
+      .trim()
+      .to_string()
+    })
+    .unwrap_or_default()
+}
+
+/// escape a string for a DOT quoted string
+fn dot_escape(text: &str) -> String {
+  text
+    .replace('\\', "\\\\")
+    .replace('"', "\\\"")
+    .replace('\n', "\\n")
+}
+
+/// escape a string for XML text and attribute values
+fn xml_escape(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+}
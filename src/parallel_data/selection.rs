@@ -0,0 +1,249 @@
+//! Selections of the documents of a corpus: all the documents under its root directory, by
+//! default, or a curated subset, listed in a manifest file, on stdin, or matched by glob patterns.
+//!
+//! Manifest and stdin entries are document paths, or paper ids, e.g. `0903.1000`, resolved
+//! against the corpus root (see `resolve`). Manifests may also be CSV or TSV files, of which the
+//! first column is read, skipping a header row. The entries which resolve to no file, and the
+//! patterns which match none, are reported as `FailureKind::Unresolved` failures of the walk.
+use jwalk::WalkDir as ParWalkDir;
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+use super::source::{self, Sources};
+use super::walk::{self, FailureKind, WalkOptions};
+
+/// The names of the first column of a manifest header row
+const HEADER_NAMES: [&str; 6] = ["id", "ids", "paper", "paper_id", "arxiv_id", "path"];
+
+/// The documents of a corpus to walk
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Selection {
+  /// all the documents under the corpus root, selected by extension
+  #[default]
+  Walk,
+  /// the documents listed in a manifest file, one path or id per line
+  Manifest(String),
+  /// the documents listed on stdin, one path or id per line
+  Stdin,
+  /// the documents matching any of the glob patterns, relative to the corpus root unless absolute
+  Glob(Vec<String>),
+}
+
+impl Selection {
+  /// The documents of the selection, resolved against a corpus root. `Selection::Walk` is walked
+  /// by the corpus itself, and selects nothing here
+  pub fn sources(&self, root: &str, extension: &Option<String>, options: &WalkOptions) -> Sources {
+    match self {
+      Selection::Walk => Box::new(std::iter::empty()),
+      Selection::Manifest(manifest) => match File::open(manifest) {
+        Ok(file) => listed_sources(BufReader::new(file), root, extension, options),
+        Err(e) => Box::new(std::iter::once(Err(walk::failure(
+          manifest,
+          FailureKind::Io,
+          e.to_string(),
+        )))),
+      },
+      Selection::Stdin => listed_sources(BufReader::new(io::stdin()), root, extension, options),
+      Selection::Glob(patterns) => glob_sources(patterns, root, extension, options),
+    }
+  }
+}
+
+/// The entry of a manifest line: its first CSV or TSV column, unquoted, if any
+pub fn manifest_entry(line: &str) -> Option<&str> {
+  let line = line.trim();
+  if line.is_empty() || line.starts_with('#') {
+    return None;
+  }
+  let entry = line.split([',', '\t']).next().unwrap_or("").trim();
+  let entry = entry.trim_matches('"').trim();
+  if entry.is_empty() {
+    None
+  } else {
+    Some(entry)
+  }
+}
+
+/// checks if a manifest entry is the column name of a header row, e.g. "paper_id"
+fn is_header(entry: &str) -> bool { HEADER_NAMES.contains(&entry.to_lowercase().as_str()) }
+
+/// Resolve a manifest entry to a document file under a corpus root. The entry is taken as a path,
+/// absolute or relative to the root, if such a file exists, or else as a paper id, e.g.
+/// `0903.1000` or `astro-ph/9710163`, looked up in the usual arXMLiv layouts:
+/// `<root>/<id><ext>`, `<root>/<id>/<id><ext>`, `<root>/<prefix>/<id><ext>` and
+/// `<root>/<prefix>/<id>/<id><ext>`, where `<prefix>` is the part of the id before its first dot,
+/// e.g. `0903`, and `<ext>` the corpus extension, or .html and .xhtml, possibly gzipped. Old-style
+/// ids are also looked up without their slash, e.g. `astro-ph9710163`
+pub fn resolve(entry: &str, root: &str, extension: &Option<String>) -> Option<String> {
+  let root = Path::new(root);
+  let as_path = Path::new(entry);
+  if as_path.is_absolute() {
+    return as_path.is_file().then(|| entry.to_string());
+  }
+  let relative = root.join(entry);
+  if relative.is_file() {
+    return relative.to_str().map(String::from);
+  }
+  let extensions: Vec<&str> = match extension {
+    Some(extension) => vec![extension.as_str()],
+    None => vec![".html", ".xhtml"],
+  };
+  let mut names = vec![entry.to_string()];
+  if entry.contains('/') {
+    names.push(entry.replace('/', ""));
+  }
+  for name in names.iter() {
+    let mut directories = vec![root.to_path_buf(), root.join(name)];
+    if let Some((prefix, _)) = name.split_once('.') {
+      directories.push(root.join(prefix));
+      directories.push(root.join(prefix).join(name));
+    }
+    for directory in directories.iter() {
+      for extension in extensions.iter() {
+        for suffix in ["", ".gz"] {
+          let candidate = directory.join(format!("{name}{extension}{suffix}"));
+          if candidate.is_file() {
+            return candidate.to_str().map(String::from);
+          }
+        }
+      }
+    }
+  }
+  None
+}
+
+/// The documents listed by a reader, one entry per line, as by `manifest_entry` and `resolve`
+pub fn listed_sources<R: BufRead + Send + 'static>(
+  reader: R,
+  root: &str,
+  extension: &Option<String>,
+  options: &WalkOptions,
+) -> Sources {
+  let root = root.to_string();
+  let extension = extension.clone();
+  let options = *options;
+  Box::new(
+    reader
+      .lines()
+      .enumerate()
+      .flat_map(move |(index, line)| -> Sources {
+        let line = match line {
+          Ok(line) => line,
+          Err(e) => {
+            return Box::new(std::iter::once(Err(walk::failure(
+              &format!("line {}", index + 1),
+              FailureKind::Io,
+              e.to_string(),
+            ))))
+          },
+        };
+        let entry = match manifest_entry(&line) {
+          Some(entry) if index > 0 || !is_header(entry) => entry,
+          _ => return Box::new(std::iter::empty()),
+        };
+        match resolve(entry, &root, &extension) {
+          Some(path) => source::expand(&path, &extension, &options),
+          None => Box::new(std::iter::once(Err(walk::failure(
+            entry,
+            FailureKind::Unresolved,
+            format!("no document found under {root}"),
+          )))),
+        }
+      }),
+  )
+}
+
+/// Checks if a path matches a glob pattern, component by component: `*` matches any characters
+/// of a component, `?` any single character, and a `**` component any number of components
+pub fn glob_matches(pattern: &str, path: &str) -> bool {
+  let pattern: Vec<&str> = pattern.split('/').filter(|c| !c.is_empty()).collect();
+  let path: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+  matches_components(&pattern, &path)
+}
+
+/// checks if path components match pattern components
+fn matches_components(pattern: &[&str], path: &[&str]) -> bool {
+  match pattern.split_first() {
+    None => path.is_empty(),
+    Some((&"**", rest)) => (0..=path.len()).any(|skip| matches_components(rest, &path[skip..])),
+    Some((component, rest)) => match path.split_first() {
+      Some((name, path_rest)) => {
+        let component: Vec<char> = component.chars().collect();
+        let name: Vec<char> = name.chars().collect();
+        matches_component(&component, &name) && matches_components(rest, path_rest)
+      },
+      None => false,
+    },
+  }
+}
+
+/// checks if a path component matches a pattern component
+fn matches_component(pattern: &[char], name: &[char]) -> bool {
+  match pattern.split_first() {
+    None => name.is_empty(),
+    Some(('*', rest)) => (0..=name.len()).any(|skip| matches_component(rest, &name[skip..])),
+    Some(('?', rest)) => !name.is_empty() && matches_component(rest, &name[1..]),
+    Some((c, rest)) => name.first() == Some(c) && matches_component(rest, &name[1..]),
+  }
+}
+
+/// The documents of the files matching glob patterns, each walked from its directory prefix
+/// without wildcards, and selected once, however many patterns match them. Relative patterns are
+/// resolved against the corpus root. The patterns without such a prefix, which would walk the
+/// whole filesystem, are rejected, and reported as unresolved
+fn glob_sources(
+  patterns: &[String],
+  root: &str,
+  extension: &Option<String>,
+  options: &WalkOptions,
+) -> Sources {
+  let root = if root.is_empty() { "." } else { root };
+  let mut failures = Vec::new();
+  let mut matched = BTreeSet::new();
+  for pattern in patterns {
+    let pattern = if Path::new(pattern).is_absolute() {
+      pattern.clone()
+    } else {
+      Path::new(root).join(pattern).to_string_lossy().into_owned()
+    };
+    let prefix: Vec<&str> = pattern
+      .split('/')
+      .take_while(|component| !component.contains(['*', '?']))
+      .collect();
+    if prefix.iter().all(|component| component.is_empty()) {
+      failures.push(walk::failure(
+        &pattern,
+        FailureKind::Unresolved,
+        String::from("the pattern has no directory prefix without wildcards"),
+      ));
+      continue;
+    }
+    let found: Vec<String> = ParWalkDir::new(prefix.join("/"))
+      .skip_hidden(true)
+      .sort(true)
+      .into_iter()
+      .filter_map(|each| each.ok())
+      .filter_map(|entry| entry.path().to_str().map(String::from))
+      .filter(|path| glob_matches(&pattern, path) && Path::new(path).is_file())
+      .collect();
+    if found.is_empty() {
+      failures.push(walk::failure(
+        &pattern,
+        FailureKind::Unresolved,
+        String::from("no file matches the pattern"),
+      ));
+    }
+    matched.extend(found);
+  }
+  let extension = extension.clone();
+  let options = *options;
+  Box::new(
+    failures.into_iter().map(Err).chain(
+      matched
+        .into_iter()
+        .flat_map(move |path| source::expand(&path, &extension, &options)),
+    ),
+  )
+}
@@ -0,0 +1,305 @@
+//! Document sources of corpus walks: plain files, gzipped files (e.g. `0903.1000.html.gz`) and the
+//! entries of tar, tar.gz and zip archives, e.g. the arXMLiv bundles, which are read in place
+//! rather than unpacked.
+//!
+//! The documents read from an archive have their in-archive path as document path, e.g.
+//! `0903/0903.1000.html` for an entry of `arXMLiv_0903.zip`, and record the archive they were read
+//! from, which their failures also report. A gzipped file has its path without the `.gz`
+//! extension as document path, e.g. `/data/0903/0903.1000.html` for
+//! `/data/0903/0903.1000.html.gz`.
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::Read;
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::thread;
+use zip::ZipArchive;
+
+use super::walk::{self, DocumentFailure, FailureKind, WalkOptions};
+
+/// The number of archive entries read ahead of the walk
+const READ_AHEAD: usize = 64;
+
+/// The kinds of compressed files and archives
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ArchiveFormat {
+  /// a tar archive, `.tar`
+  Tar,
+  /// a gzipped tar archive, `.tar.gz` or `.tgz`
+  TarGz,
+  /// a zip archive, `.zip`
+  Zip,
+  /// a single gzipped file, e.g. `.html.gz`
+  Gz,
+}
+
+impl ArchiveFormat {
+  /// the format of a file, by its extension, if it is a compressed file or archive
+  pub fn of_path(path: &str) -> Option<Self> {
+    let path = path.to_lowercase();
+    if path.ends_with(".tar") {
+      Some(ArchiveFormat::Tar)
+    } else if path.ends_with(".tar.gz") || path.ends_with(".tgz") {
+      Some(ArchiveFormat::TarGz)
+    } else if path.ends_with(".zip") {
+      Some(ArchiveFormat::Zip)
+    } else if path.ends_with(".gz") {
+      Some(ArchiveFormat::Gz)
+    } else {
+      None
+    }
+  }
+}
+
+/// A document to load during a walk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DocumentSource {
+  /// a document file
+  File(String),
+  /// the decompressed content of a gzipped file or archive entry, with its in-archive path
+  Bytes {
+    /// the in-archive path of the document, or the path of a gzipped file without `.gz`
+    path: String,
+    /// the path of the archive of an entry, `None` for a gzipped file
+    archive: Option<String>,
+    /// the content of the document
+    content: Vec<u8>,
+  },
+}
+
+impl DocumentSource {
+  /// the path of the document, in its archive if any
+  pub fn path(&self) -> &str {
+    match self {
+      DocumentSource::File(path) => path,
+      DocumentSource::Bytes { path, .. } => path,
+    }
+  }
+
+  /// the path of the archive of the document, if an archive entry
+  pub fn archive(&self) -> Option<&str> {
+    match self {
+      DocumentSource::File(_) => None,
+      DocumentSource::Bytes { archive, .. } => archive.as_deref(),
+    }
+  }
+}
+
+/// The walked documents of a source
+pub type Sources = Box<dyn Iterator<Item = Result<DocumentSource, DocumentFailure>> + Send>;
+
+/// checks if a document file name is selected by a corpus extension, which defaults to selecting
+/// .html AND .xhtml files
+fn selects(file_name: &str, extension: &Option<String>) -> bool {
+  if let Some(ref extension) = extension {
+    file_name.ends_with(extension)
+  } else {
+    file_name.ends_with(".html") || file_name.ends_with(".xhtml")
+  }
+}
+
+/// The documents of a walked file: its entries, if an archive, its decompressed content, if a
+/// selected gzipped file, or else the file itself, if selected
+pub fn expand(path: &str, extension: &Option<String>, options: &WalkOptions) -> Sources {
+  match ArchiveFormat::of_path(path) {
+    Some(ArchiveFormat::Gz) => {
+      let inner = &path[..path.len() - 3];
+      if selects(inner, extension) {
+        Box::new(std::iter::once(read_gz(path, inner, options)))
+      } else {
+        Box::new(std::iter::empty())
+      }
+    },
+    Some(format) => Box::new(ArchiveEntries::open(path, format, extension, options)),
+    None if selects(path, extension) => {
+      Box::new(std::iter::once(Ok(DocumentSource::File(path.to_string()))))
+    },
+    None => Box::new(std::iter::empty()),
+  }
+}
+
+/// read a gzipped file, as the document of its path without the `.gz` extension
+fn read_gz(
+  path: &str,
+  inner: &str,
+  options: &WalkOptions,
+) -> Result<DocumentSource, DocumentFailure> {
+  walk::check_size(path, options)?;
+  let file = File::open(path).map_err(|e| walk::failure(path, FailureKind::Io, e.to_string()))?;
+  let mut content = Vec::new();
+  GzDecoder::new(file)
+    .read_to_end(&mut content)
+    .map_err(|e| walk::failure(path, FailureKind::Io, e.to_string()))?;
+  Ok(DocumentSource::Bytes {
+    path: inner.to_string(),
+    archive: None,
+    content,
+  })
+}
+
+/// The selected entries of an archive, read in a background thread, ahead of the walk
+pub struct ArchiveEntries {
+  receiver: Receiver<Result<DocumentSource, DocumentFailure>>,
+}
+
+impl ArchiveEntries {
+  /// Start reading the entries of an archive
+  pub fn open(
+    path: &str,
+    format: ArchiveFormat,
+    extension: &Option<String>,
+    options: &WalkOptions,
+  ) -> Self {
+    let (sender, receiver) = sync_channel(READ_AHEAD);
+    let reader = EntryReader {
+      archive: path.to_string(),
+      extension: extension.clone(),
+      max_file_size: options.max_file_size,
+    };
+    thread::spawn(move || {
+      // the walk may stop early, hanging up on the reader
+      let mut send = |entry: Result<DocumentSource, DocumentFailure>| sender.send(entry).is_ok();
+      let outcome = match File::open(&reader.archive) {
+        Ok(file) => match format {
+          ArchiveFormat::Tar => reader.read_tar(file, &mut send),
+          ArchiveFormat::TarGz => reader.read_tar(GzDecoder::new(file), &mut send),
+          ArchiveFormat::Zip => reader.read_zip(file, &mut send),
+          ArchiveFormat::Gz => Err(String::from("not an archive")),
+        },
+        Err(e) => Err(e.to_string()),
+      };
+      if let Err(message) = outcome {
+        send(Err(walk::failure(
+          &reader.archive,
+          FailureKind::Io,
+          message,
+        )));
+      }
+    });
+    ArchiveEntries { receiver }
+  }
+}
+
+impl Iterator for ArchiveEntries {
+  type Item = Result<DocumentSource, DocumentFailure>;
+  fn next(&mut self) -> Option<Self::Item> { self.receiver.recv().ok() }
+}
+
+/// The reader of the selected entries of an archive
+struct EntryReader {
+  archive: String,
+  extension: Option<String>,
+  max_file_size: Option<u64>,
+}
+
+impl EntryReader {
+  /// read the entries of a tar archive, until the walk hangs up
+  fn read_tar<R: Read>(
+    &self,
+    reader: R,
+    send: &mut dyn FnMut(Result<DocumentSource, DocumentFailure>) -> bool,
+  ) -> Result<(), String> {
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries().map_err(|e| e.to_string())? {
+      let mut entry = entry.map_err(|e| e.to_string())?;
+      if !entry.header().entry_type().is_file() {
+        continue;
+      }
+      let path = match entry.path() {
+        Ok(path) => path.to_string_lossy().into_owned(),
+        Err(e) => {
+          if !send(Err(walk::failure(
+            &self.archive,
+            FailureKind::Io,
+            e.to_string(),
+          ))) {
+            return Ok(());
+          }
+          continue;
+        },
+      };
+      if let Some(document) = self.read_entry(path, entry.size(), &mut entry) {
+        if !send(document) {
+          return Ok(());
+        }
+      }
+    }
+    Ok(())
+  }
+
+  /// read the entries of a zip archive, until the walk hangs up
+  fn read_zip(
+    &self,
+    file: File,
+    send: &mut dyn FnMut(Result<DocumentSource, DocumentFailure>) -> bool,
+  ) -> Result<(), String> {
+    let mut archive = ZipArchive::new(file).map_err(|e| e.to_string())?;
+    for index in 0..archive.len() {
+      let mut entry = match archive.by_index(index) {
+        Ok(entry) => entry,
+        Err(e) => {
+          if !send(Err(walk::failure(
+            &self.archive,
+            FailureKind::Io,
+            e.to_string(),
+          ))) {
+            return Ok(());
+          }
+          continue;
+        },
+      };
+      if entry.is_dir() {
+        continue;
+      }
+      let path = entry.name().to_string();
+      let size = entry.size();
+      if let Some(document) = self.read_entry(path, size, &mut entry) {
+        if !send(document) {
+          return Ok(());
+        }
+      }
+    }
+    Ok(())
+  }
+
+  /// read an archive entry, if selected, and if within the size limit; gzipped entries are
+  /// decompressed
+  fn read_entry(
+    &self,
+    path: String,
+    size: u64,
+    entry: &mut dyn Read,
+  ) -> Option<Result<DocumentSource, DocumentFailure>> {
+    let gzipped = ArchiveFormat::of_path(&path) == Some(ArchiveFormat::Gz);
+    let path = if gzipped {
+      path[..path.len() - 3].to_string()
+    } else {
+      path
+    };
+    if !selects(&path, &self.extension) {
+      return None;
+    }
+    if let Some(max_file_size) = self.max_file_size {
+      if size > max_file_size {
+        let message = format!("{size} bytes, over the limit of {max_file_size}");
+        let failure = walk::failure(&path, FailureKind::TooLarge, message);
+        return Some(Err(failure.in_archive(Some(&self.archive))));
+      }
+    }
+    let mut content = Vec::new();
+    let read = if gzipped {
+      GzDecoder::new(entry).read_to_end(&mut content)
+    } else {
+      entry.read_to_end(&mut content)
+    };
+    Some(match read {
+      Ok(_) => Ok(DocumentSource::Bytes {
+        path,
+        archive: Some(self.archive.clone()),
+        content,
+      }),
+      Err(e) => {
+        Err(walk::failure(&path, FailureKind::Io, e.to_string()).in_archive(Some(&self.archive)))
+      },
+    })
+  }
+}
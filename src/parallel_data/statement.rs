@@ -0,0 +1,499 @@
+//! Theorem-like statements of a document, i.e. its AMS environments (see `ams::AmsEnv`) and
+//! proofs, with their headings and content.
+//!
+//! Statements written by hand rather than with amsthm, e.g. `\textbf{Theorem 1.}` at the start of a
+//! paragraph, have no AMS markup. They can be inferred from their bold or italic run-in headings,
+//! see `StatementOptions::infer_unmarked`.
+use std::collections::HashSet;
+use std::ops::RangeInclusive;
+
+use lazy_static::lazy_static;
+use libxml::readonly::RoNode;
+use libxml::xpath::Context;
+use regex::Regex;
+
+use super::outline::Outline;
+use super::proof_structure::ProofStructure;
+use super::{Document, RoNodeIterator, Walker};
+use crate::ams::{self, AmsEnv};
+use crate::dnm::{DNMParameters, DNMRange, SpecialTagsOption, DNM};
+use crate::util::data_helpers::{self, LexicalOptions};
+use crate::util::heading_language::HeadingLanguage;
+
+lazy_static! {
+  /// a run-in heading, e.g. "Theorem 1.", "Lemma 2.3 (Zorn):" or "Proof."
+  static ref RUNIN_HEADING: Regex = Regex::new(
+    r"^(\p{Alphabetic}+)(?:\s*~?\s*([[:digit:]]+(?:\.[[:alnum:]]+)*))?\s*(?:\(([^)]*)\))?\s*([.:])?$"
+  )
+  .unwrap();
+}
+
+/// The environments which are too noisy, or too rare, for statement classification experiments
+pub const NOISY_ENVS: [AmsEnv; 22] = [
+  AmsEnv::Affirmation,
+  AmsEnv::Algorithm,
+  AmsEnv::Answer,
+  AmsEnv::Bound,
+  AmsEnv::Caption,
+  AmsEnv::Comment,
+  AmsEnv::Constraint,
+  AmsEnv::Convention,
+  AmsEnv::Criterion,
+  AmsEnv::Expansion,
+  AmsEnv::Expectation,
+  AmsEnv::Explanation,
+  AmsEnv::Hint,
+  AmsEnv::Issue,
+  AmsEnv::Keywords,
+  AmsEnv::Note,
+  AmsEnv::Notice,
+  AmsEnv::Paragraph,
+  AmsEnv::Principle,
+  AmsEnv::Rule,
+  AmsEnv::Solution,
+  AmsEnv::Other,
+];
+
+/// Options for selecting and normalizing the statements of a document. The default selects all
+/// statements, with their full content
+#[derive(Debug, Clone, Default)]
+pub struct StatementOptions {
+  /// the environments to leave out
+  pub excluded_envs: Vec<AmsEnv>,
+  /// the content of a statement is only its first paragraph, when set
+  pub first_paragraph_only: bool,
+  /// statements without valid English+Latin content are left out, when set (see
+  /// `data_helpers::invalid_for_english_latin`)
+  pub english_only: bool,
+  /// statements which are not in one of the languages with heading normalization tables are left
+  /// out, when set (see `data_helpers::invalid_for_heading_languages`)
+  pub heading_languages_only: bool,
+  /// if set, statements with a normalized word count outside of the range are left out, as are
+  /// the statements which fail normalization
+  pub word_count: Option<RangeInclusive<usize>>,
+  /// statements repeating the environment and normalized content of an earlier statement of the
+  /// document are left out, when set
+  pub dedup: bool,
+  /// the normalization of the statement words
+  pub lexical_options: LexicalOptions,
+  /// statements without AMS markup are inferred from the run-in headings of paragraphs, when set,
+  /// e.g. a bold "Theorem 1." at the start of a paragraph
+  pub infer_unmarked: bool,
+}
+
+impl StatementOptions {
+  /// The selection of the AMS statement paragraphs model: the first paragraphs of statements,
+  /// other than the `NOISY_ENVS`, in English or any other `HeadingLanguage`, with 4 to 1024 words,
+  /// and keeping punctuation
+  pub fn paragraph_model(discard_math: bool) -> Self {
+    StatementOptions {
+      excluded_envs: NOISY_ENVS.to_vec(),
+      first_paragraph_only: true,
+      english_only: false,
+      heading_languages_only: true,
+      word_count: Some(4..=1024),
+      dedup: true,
+      lexical_options: LexicalOptions {
+        discard_math,
+        discard_punct: false,
+        discard_case: true,
+      },
+      infer_unmarked: false,
+    }
+  }
+}
+
+/// A theorem-like statement, e.g. a definition, lemma or proof
+pub struct Statement<'s> {
+  /// the environment of the statement
+  pub env: AmsEnv,
+  /// the statement's node, e.g. `<div class="ltx_theorem ltx_theorem_lemma">`, or the paragraph
+  /// of an inferred statement
+  pub node: RoNode,
+  /// the statement has no AMS markup, and was inferred from a run-in heading
+  pub inferred: bool,
+  /// the `xml:id` label of the statement, for resolving references to it
+  pub id: Option<String>,
+  /// the heading of the statement, e.g. `<h6 class="ltx_title ltx_title_theorem">`, or the run-in
+  /// heading of an inferred statement, e.g. `<span class="ltx_text ltx_font_bold">`
+  pub heading: Option<RoNode>,
+  /// the full tag of the heading, e.g. "Lemma 2.1"
+  pub tag: Option<String>,
+  /// the number of the statement, e.g. "2.1"
+  pub number: Option<String>,
+  /// the title in parentheses following the tag, e.g. "Zorn" for "Lemma 2.1 (Zorn)."
+  pub title: Option<String>,
+  /// the content of the statement, without its heading, except for the statements inferred from
+  /// the run-in heading of a paragraph, which keep it
+  pub dnm: DNM,
+  /// the paragraphs of the content
+  pub paragraphs: Vec<RoNode>,
+  /// the normalized words of the content, without the heading, or None if it has overly long
+  /// words, which tend to be conversion errors
+  pub words: Option<Vec<String>>,
+  /// the document containing the statement
+  pub document: &'s Document<'s>,
+}
+
+impl<'s> Statement<'s> {
+  /// Get an iterator over the paragraphs of the statement
+  pub fn paragraph_iter(&self) -> RoNodeIterator<'s> {
+    RoNodeIterator {
+      walker: Box::new(self.paragraphs.clone().into_iter()),
+      document: self.document,
+    }
+  }
+
+  /// the normalized words of the content, separated by spaces, if valid
+  pub fn text(&self) -> Option<String> { self.words.as_ref().map(|words| words.join(" ")) }
+
+  /// the section path of the statement, e.g. `["3 Methods", "3.2 Model"]`
+  pub fn section_path(&self, outline: &Outline) -> Vec<String> { outline.section_path(self.node) }
+
+  /// the structure of a proof, i.e. its cases, steps, claims and items, if the statement is one
+  pub fn proof_structure(&self) -> Option<ProofStructure> {
+    if self.env == AmsEnv::Proof {
+      Some(ProofStructure::new(self.node))
+    } else {
+      None
+    }
+  }
+}
+
+/// An iterator over the statements of a document, which satisfy the `StatementOptions`
+pub struct StatementIterator<'iter> {
+  /// A walker over the statement nodes
+  walker: Walker<'iter, RoNode>,
+  /// The selection and normalization options
+  options: StatementOptions,
+  /// The xpath context used for math lexemes
+  context: Context,
+  /// The environments and contents of the statements seen so far, when deduplicating
+  seen: HashSet<String>,
+  /// The number of statements left out by the content filters
+  discarded: usize,
+  /// The detected language of the document, which environment names are normalized in
+  language: HeadingLanguage,
+  /// A reference to the owner document
+  pub document: &'iter Document<'iter>,
+}
+
+impl<'iter> StatementIterator<'iter> {
+  pub(crate) fn new(document: &'iter Document<'iter>, options: StatementOptions) -> Self {
+    let nodes = if options.infer_unmarked {
+      document.get_xpath_nodes(
+        "//*[(local-name()='div' and (contains(@class,'ltx_theorem') or @class='ltx_proof')) or \
+         (contains(@class,'ltx_para') and \
+          not(ancestor::*[contains(@class,'ltx_theorem') or @class='ltx_proof']))]",
+      )
+    } else {
+      document.get_xpath_nodes(
+        "//*[local-name()='div' and (contains(@class,'ltx_theorem') or @class='ltx_proof')]",
+      )
+    };
+    StatementIterator {
+      walker: Box::new(nodes.into_iter()),
+      options,
+      context: Context::new(&document.dom).unwrap(),
+      seen: HashSet::new(),
+      discarded: 0,
+      language: document.language().unwrap_or_default(),
+      document,
+    }
+  }
+
+  /// the number of statements left out so far for their content, i.e. by the language, word count
+  /// and deduplication filters
+  pub fn discarded(&self) -> usize { self.discarded }
+
+  /// the statement of a node, if of a selected environment
+  fn statement(&mut self, node: RoNode) -> Option<Statement<'iter>> {
+    let class = node.get_attribute("class").unwrap_or_default();
+    let (env, heading, tag, title, inferred) = match ams::class_to_env_in(&class, self.language) {
+      Some(env) => {
+        let heading = node.get_child_elements().into_iter().find(|child| {
+          child.get_class_names().contains("ltx_title") && child.get_name().starts_with('h')
+        });
+        let (tag, title) = match heading {
+          Some(heading) => heading_tag_and_title(heading),
+          None => (None, None),
+        };
+        (env, heading, tag, title, false)
+      },
+      None if self.options.infer_unmarked => {
+        let runin = RunInHeading::of_node(node, self.language)?;
+        (
+          runin.env,
+          Some(runin.node),
+          Some(runin.tag),
+          runin.title,
+          true,
+        )
+      },
+      None => return None,
+    };
+    if self.options.excluded_envs.contains(&env) {
+      return None;
+    }
+    let candidates = if node.get_class_names().contains("ltx_para") {
+      vec![node]
+    } else {
+      node.get_child_elements()
+    };
+    let mut paragraphs: Vec<RoNode> = candidates
+      .into_iter()
+      .filter(|child| {
+        child.get_class_names().contains("ltx_para")
+          && Document::dfs_filter_nodes(*child, &|n| {
+            n.get_attribute("class")
+              .map(|class| class.contains("ltx_ERROR"))
+              .unwrap_or(false)
+          })
+          .is_empty()
+      })
+      .collect();
+    let dnm = if self.options.first_paragraph_only {
+      paragraphs.truncate(1);
+      DNM::new(
+        *paragraphs.first()?,
+        DNMParameters::llamapun_normalization(),
+      )
+    } else {
+      let mut parameters = DNMParameters::llamapun_normalization();
+      for title_class in &["ltx_title_theorem", "ltx_title_proof", "ltx_runin"] {
+        parameters
+          .special_tag_class_options
+          .insert(title_class.to_string(), SpecialTagsOption::Skip);
+      }
+      DNM::new(node, parameters)
+    };
+    // the run-in heading of a paragraph is part of its content, and left out of the words
+    let runin = heading.filter(|_| inferred);
+    let words = content_range(&dnm, runin).and_then(|range| self.normalized_words(&range));
+    Some(Statement {
+      env,
+      node,
+      inferred,
+      id: node
+        .get_attribute("id")
+        .or_else(|| node.get_attribute("xml:id")),
+      heading,
+      number: tag.as_deref().and_then(tag_number),
+      tag,
+      title,
+      dnm,
+      paragraphs,
+      words,
+      document: self.document,
+    })
+  }
+
+  /// the normalized words of a statement's content, if none is overly long
+  fn normalized_words(&mut self, range: &DNMRange) -> Option<Vec<String>> {
+    let tokenizer = &self.document.corpus.tokenizer;
+    let mut words = Vec::new();
+    for word in tokenizer.word_and_punct_iter(range) {
+      if word.is_empty() {
+        continue;
+      }
+      let word_string = data_helpers::ams_normalize_word_range(
+        &word,
+        &mut self.context,
+        self.options.lexical_options,
+      )
+      .ok()?;
+      if !word_string.is_empty() {
+        words.push(word_string);
+      }
+    }
+    Some(words)
+  }
+
+  /// checks if a statement's content passes the language, word count and deduplication filters
+  fn is_valid(&mut self, statement: &Statement) -> bool {
+    if self.options.english_only && data_helpers::invalid_for_english_latin(&statement.dnm) {
+      return false;
+    }
+    if self.options.heading_languages_only
+      && data_helpers::invalid_for_heading_languages(&statement.dnm)
+    {
+      return false;
+    }
+    if let Some(ref word_count) = self.options.word_count {
+      match statement.words {
+        Some(ref words) if word_count.contains(&words.len()) => {},
+        _ => return false,
+      }
+    }
+    if self.options.dedup {
+      let content = match statement.text() {
+        Some(text) => text,
+        None => statement.dnm.plaintext.clone(),
+      };
+      if !self.seen.insert(format!("{}/{}", statement.env, content)) {
+        return false;
+      }
+    }
+    true
+  }
+}
+
+impl<'iter> Iterator for StatementIterator<'iter> {
+  type Item = Statement<'iter>;
+  fn next(&mut self) -> Option<Statement<'iter>> {
+    while let Some(node) = self.walker.next() {
+      if let Some(statement) = self.statement(node) {
+        if self.is_valid(&statement) {
+          return Some(statement);
+        }
+        self.discarded += 1;
+      }
+    }
+    None
+  }
+}
+
+/// the range of a statement's content, after its run-in heading, if any
+fn content_range<'d>(dnm: &'d DNM, runin: Option<RoNode>) -> Option<DNMRange<'d>> {
+  let range = dnm.get_range().ok()?;
+  match runin.and_then(|heading| dnm.get_range_of_node(heading).ok()) {
+    Some(heading_range) if heading_range.end <= range.end => Some(DNMRange {
+      start: heading_range.end,
+      end: range.end,
+      dnm,
+    }),
+    _ => Some(range),
+  }
+}
+
+/// The run-in heading of a statement without AMS markup
+struct RunInHeading {
+  /// the heading node
+  node: RoNode,
+  /// the environment of the heading's name, through `ams::normalize_env_in`
+  env: AmsEnv,
+  /// the name and number of the heading, e.g. "Theorem 1"
+  tag: String,
+  /// the title in parentheses following the tag, if any
+  title: Option<String>,
+}
+
+impl RunInHeading {
+  /// The run-in heading of a paragraph or `\paragraph` section, if any: an emphasized first child
+  /// of a paragraph, or the run-in title of a section, naming an environment followed by a number
+  /// and a period or colon, e.g. "Theorem 1." or "Lemma 2.3 (Zorn):", in the document language.
+  /// Proofs need no number
+  fn of_node(node: RoNode, language: HeadingLanguage) -> Option<Self> {
+    let classes = node.get_class_names();
+    if classes.contains("ltx_paragraph") {
+      let heading = node.get_child_elements().into_iter().find(|child| {
+        let classes = child.get_class_names();
+        classes.contains("ltx_title") && classes.contains("ltx_runin")
+      })?;
+      RunInHeading::parse(heading, None, language)
+    } else if classes.contains("ltx_para") {
+      let paragraph = node
+        .get_child_elements()
+        .into_iter()
+        .next()
+        .filter(|child| child.get_class_names().contains("ltx_p"))?;
+      let mut first = paragraph.get_first_child();
+      while let Some(child) = first {
+        if child.is_text_node() && child.get_content().trim().is_empty() {
+          first = child.get_next_sibling();
+        } else {
+          break;
+        }
+      }
+      let heading = first.filter(|child| child.is_element_node() && is_emphasis(child))?;
+      let following = heading
+        .get_next_sibling()
+        .filter(|sibling| sibling.is_text_node())
+        .map(|sibling| sibling.get_content())
+        .unwrap_or_default();
+      RunInHeading::parse(heading, Some(&following), language)
+    } else {
+      None
+    }
+  }
+
+  /// parse a heading node, whose period or colon may also start the `following` text of an
+  /// emphasized heading
+  fn parse(node: RoNode, following: Option<&str>, language: HeadingLanguage) -> Option<Self> {
+    let text = node.get_content();
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let captures = RUNIN_HEADING.captures(&text)?;
+    if let Some(following) = following {
+      if captures.get(4).is_none() && !following.trim_start().starts_with(['.', ':']) {
+        return None;
+      }
+    }
+    let name = &captures[1];
+    let env = ams::normalize_env_in(&name.to_lowercase(), language);
+    let number = captures.get(2).map(|number| number.as_str());
+    if matches!(env, AmsEnv::Other | AmsEnv::Caption) || (number.is_none() && env != AmsEnv::Proof)
+    {
+      return None;
+    }
+    Some(RunInHeading {
+      node,
+      env,
+      tag: match number {
+        Some(number) => format!("{name} {number}"),
+        None => name.to_string(),
+      },
+      title: captures
+        .get(3)
+        .map(|title| title.as_str().trim().to_string())
+        .filter(|title| !title.is_empty()),
+    })
+  }
+}
+
+/// checks if a node is bold or italic, or a run-in title
+fn is_emphasis(node: &RoNode) -> bool {
+  matches!(node.get_name().as_str(), "b" | "strong" | "i" | "em")
+    || node
+      .get_class_names()
+      .iter()
+      .any(|class| class == "ltx_font_bold" || class == "ltx_font_italic" || class == "ltx_runin")
+}
+
+/// the tag (e.g. "Lemma 2.1") and parenthesized title (e.g. "Zorn") of a statement heading
+fn heading_tag_and_title(heading: RoNode) -> (Option<String>, Option<String>) {
+  let tag = Document::dfs_filter_nodes(heading, &|n| n.get_class_names().contains("ltx_tag"))
+    .first()
+    .map(|tag_node| tag_node.get_content().trim().to_string())
+    .filter(|tag| !tag.is_empty());
+  let heading_text = heading.get_content();
+  let rest = match tag {
+    Some(ref tag) => heading_text.replacen(tag.as_str(), "", 1),
+    None => heading_text,
+  };
+  let rest = rest.trim().trim_end_matches('.').trim();
+  let title = rest
+    .strip_prefix('(')
+    .and_then(|rest| rest.strip_suffix(')'))
+    .map(|title| title.trim().to_string())
+    .filter(|title| !title.is_empty());
+  (tag, title)
+}
+
+/// the number of a statement tag, i.e. its last word, if it has a digit, is a roman numeral or is a
+/// letter after a name, e.g. "Theorem 1.2", "Part IV" or "Appendix A", but not "Main Theorem"
+pub(crate) fn tag_number(tag: &str) -> Option<String> {
+  let words: Vec<&str> = tag.split_whitespace().collect();
+  let last = *words.last()?;
+  let is_roman = |numerals: &str| last.chars().all(|c| numerals.contains(c));
+  let is_letter =
+    words.len() > 1 && last.chars().count() == 1 && last.chars().all(char::is_alphabetic);
+  if last.chars().any(|c| c.is_ascii_digit())
+    || is_roman("IVXLCDM")
+    || is_roman("ivxlcdm")
+    || is_letter
+  {
+    Some(last.to_string())
+  } else {
+    None
+  }
+}
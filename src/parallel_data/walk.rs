@@ -0,0 +1,314 @@
+//! Fault tolerance for corpus walks: documents which can not be read, parsed or processed are
+//! retried, or skipped and recorded in a `WalkReport`, rather than ending the walk.
+//!
+//! Panics of the loading of documents, and of the per-document closures, are isolated with
+//! `catch_unwind`. Rayon workers can not be interrupted, so there are no timeouts:
+//! `WalkOptions::max_duration` is checked once a document is processed, and the results of the
+//! documents which took too long are reported and, where the walk allows, discarded. A document
+//! which never finishes still stalls its worker.
+use std::any::Any;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::Write;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, Instant};
+
+/// The options of a fault-tolerant corpus walk
+#[derive(Debug, Clone, Copy)]
+pub struct WalkOptions {
+  /// the number of times a document is reloaded after it fails to parse
+  pub retries: usize,
+  /// the documents whose processing took longer are reported as failed, and their results
+  /// discarded, if set. Checked after the processing, which is not interrupted
+  pub max_duration: Option<Duration>,
+  /// the files larger than this number of bytes are skipped, if set
+  pub max_file_size: Option<u64>,
+  /// the panics of the loading and per-document closures are caught, and their documents skipped,
+  /// when set
+  pub catch_panics: bool,
+}
+
+impl Default for WalkOptions {
+  fn default() -> Self {
+    WalkOptions {
+      retries: 1,
+      max_duration: None,
+      max_file_size: None,
+      catch_panics: true,
+    }
+  }
+}
+
+/// The ways in which a document can fail during a walk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FailureKind {
+  /// the corpus directory entry could not be read
+  Walk,
+  /// the file metadata could not be read
+  Io,
+  /// the file is larger than `WalkOptions::max_file_size`
+  TooLarge,
+  /// the document could not be parsed, even after `WalkOptions::retries`
+  Parse,
+  /// the per-document closure panicked
+  Panic,
+  /// the document took longer than `WalkOptions::max_duration`
+  TooSlow,
+  /// the manifest entry or glob pattern selected no document
+  Unresolved,
+}
+
+impl fmt::Display for FailureKind {
+  fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+    fmt.write_str(match self {
+      FailureKind::Walk => "walk",
+      FailureKind::Io => "io",
+      FailureKind::TooLarge => "too_large",
+      FailureKind::Parse => "parse",
+      FailureKind::Panic => "panic",
+      FailureKind::TooSlow => "too_slow",
+      FailureKind::Unresolved => "unresolved",
+    })
+  }
+}
+
+/// A document which failed during a walk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentFailure {
+  /// the path of the document, or of the directory entry
+  pub path: String,
+  /// the path of the archive of the document, if an archive entry
+  pub archive: Option<String>,
+  /// the kind of failure
+  pub kind: FailureKind,
+  /// a description of the failure, e.g. the panic message
+  pub message: String,
+  /// the number of attempts made at the document
+  pub attempts: usize,
+}
+
+impl DocumentFailure {
+  /// the path of the document, after the path of its archive if any, e.g.
+  /// "arXMLiv_0903.zip:0903/0903.1000.html"
+  pub fn location(&self) -> String {
+    match self.archive {
+      Some(ref archive) => format!("{archive}:{}", self.path),
+      None => self.path.clone(),
+    }
+  }
+
+  /// the failure of an entry of an archive, if any
+  pub(crate) fn in_archive(mut self, archive: Option<&str>) -> Self {
+    if self.archive.is_none() {
+      self.archive = archive.map(String::from);
+    }
+    self
+  }
+}
+
+impl fmt::Display for DocumentFailure {
+  fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+    write!(fmt, "{} ({}): {}", self.location(), self.kind, self.message)
+  }
+}
+
+/// The report of a walk: the number of documents processed, and the failures
+#[derive(Debug, Clone, Default)]
+pub struct WalkReport {
+  /// the number of documents processed successfully
+  pub processed: usize,
+  /// the documents which failed, in no particular order
+  pub failures: Vec<DocumentFailure>,
+}
+
+impl WalkReport {
+  /// record the outcome of a document
+  pub fn record<T>(&mut self, outcome: Result<T, DocumentFailure>) -> Option<T> {
+    match outcome {
+      Ok(result) => {
+        self.processed += 1;
+        Some(result)
+      },
+      Err(failure) => {
+        self.failures.push(failure);
+        None
+      },
+    }
+  }
+
+  /// add the counts and failures of another report, e.g. of another worker thread
+  pub fn merge(&mut self, other: WalkReport) {
+    self.processed += other.processed;
+    self.failures.extend(other.failures);
+  }
+
+  /// checks if no document failed
+  pub fn is_clean(&self) -> bool { self.failures.is_empty() }
+
+  /// the number of failures of every kind
+  pub fn failure_counts(&self) -> HashMap<String, u64> {
+    let mut counts = HashMap::new();
+    for failure in self.failures.iter() {
+      *counts.entry(failure.kind.to_string()).or_insert(0) += 1;
+    }
+    counts
+  }
+
+  /// a one-line summary of the report, e.g. "98 documents processed, 2 failed (1 panic, 1 parse)"
+  pub fn summary(&self) -> String {
+    let mut counts: Vec<(String, u64)> = self.failure_counts().into_iter().collect();
+    counts.sort();
+    let mut summary = format!(
+      "{} documents processed, {} failed",
+      self.processed,
+      self.failures.len()
+    );
+    if !counts.is_empty() {
+      let kinds: Vec<String> = counts
+        .iter()
+        .map(|(kind, count)| format!("{count} {kind}"))
+        .collect();
+      summary.push_str(&format!(" ({})", kinds.join(", ")));
+    }
+    summary
+  }
+
+  /// Write the failures as tab-separated "<location> <kind> <attempts> <message>" lines, sorted
+  /// by location (see `DocumentFailure::location`)
+  pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+    let mut failures: Vec<(String, &DocumentFailure)> = self
+      .failures
+      .iter()
+      .map(|failure| (failure.location(), failure))
+      .collect();
+    failures.sort_by(|a, b| a.0.cmp(&b.0));
+    for (location, failure) in failures {
+      let message = failure.message.replace(['\t', '\n'], " ");
+      writeln!(
+        writer,
+        "{}\t{}\t{}\t{}",
+        location, failure.kind, failure.attempts, message
+      )?;
+    }
+    Ok(())
+  }
+
+  /// save the failures to a file, as by `write`
+  pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+    let mut file = File::create(path)?;
+    self.write(&mut file)
+  }
+}
+
+/// A failure of a path, with a single attempt
+pub(crate) fn failure(path: &str, kind: FailureKind, message: String) -> DocumentFailure {
+  DocumentFailure {
+    path: path.to_string(),
+    archive: None,
+    kind,
+    message,
+    attempts: 1,
+  }
+}
+
+/// the failure of a file exceeding the size limit, if it does, or which can not be read
+pub(crate) fn check_size(path: &str, options: &WalkOptions) -> Result<(), DocumentFailure> {
+  let max_file_size = match options.max_file_size {
+    Some(max_file_size) => max_file_size,
+    None => return Ok(()),
+  };
+  match fs::metadata(path) {
+    Ok(metadata) if metadata.len() > max_file_size => Err(failure(
+      path,
+      FailureKind::TooLarge,
+      format!(
+        "{} bytes, over the limit of {max_file_size}",
+        metadata.len()
+      ),
+    )),
+    Ok(_) => Ok(()),
+    Err(e) => Err(failure(path, FailureKind::Io, e.to_string())),
+  }
+}
+
+/// Load a document, retrying `options.retries` times if it fails to parse. A panic of the loading,
+/// e.g. of the parser, is caught if `options.catch_panics` is set, and not retried
+pub fn load_with_retries<D, E, L>(
+  path: &str,
+  options: &WalkOptions,
+  load: L,
+) -> Result<D, DocumentFailure>
+where
+  E: fmt::Debug,
+  L: Fn() -> Result<D, E>,
+{
+  let mut attempts = 0;
+  loop {
+    attempts += 1;
+    let loaded = if options.catch_panics {
+      panic::catch_unwind(AssertUnwindSafe(&load)).map_err(|payload| DocumentFailure {
+        path: path.to_string(),
+        archive: None,
+        kind: FailureKind::Panic,
+        message: panic_message(payload),
+        attempts,
+      })?
+    } else {
+      load()
+    };
+    match loaded {
+      Ok(document) => return Ok(document),
+      Err(e) if attempts > options.retries => {
+        return Err(DocumentFailure {
+          path: path.to_string(),
+          archive: None,
+          kind: FailureKind::Parse,
+          message: format!("{e:?}"),
+          attempts,
+        })
+      },
+      Err(_) => continue,
+    }
+  }
+}
+
+/// Run the closure of a document, catching its panics if `options.catch_panics` is set, and
+/// checking its time against `options.max_duration` once it returns
+pub(crate) fn run_guarded<T, F>(
+  path: &str,
+  options: &WalkOptions,
+  closure: F,
+) -> Result<T, DocumentFailure>
+where F: FnOnce() -> T {
+  let start = Instant::now();
+  let result = if options.catch_panics {
+    panic::catch_unwind(AssertUnwindSafe(closure))
+      .map_err(|payload| failure(path, FailureKind::Panic, panic_message(payload)))?
+  } else {
+    closure()
+  };
+  match options.max_duration {
+    Some(max_duration) if start.elapsed() > max_duration => Err(failure(
+      path,
+      FailureKind::TooSlow,
+      format!(
+        "took {:?}, over the limit of {max_duration:?}",
+        start.elapsed()
+      ),
+    )),
+    _ => Ok(result),
+  }
+}
+
+/// the message of a panic payload
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+  if let Some(message) = payload.downcast_ref::<&str>() {
+    message.to_string()
+  } else if let Some(message) = payload.downcast_ref::<String>() {
+    message.clone()
+  } else {
+    String::from("unknown panic")
+  }
+}
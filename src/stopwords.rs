@@ -1,672 +1,883 @@
 //! A tiny stopwords library
 //! Stopwords are words frequent words like "the", "it", "then", which would add too much noise
-//! to certain statistical methods
-use std::collections::HashSet;
+//! to certain statistical methods.
+//!
+//! Besides the built-in English list, stopword sets can be loaded from files, combined, and derived
+//! from a corpus via document frequency or TF-IDF thresholds, which also captures multi-word
+//! phrases such as "in this paper" or "we show".
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs;
+use std::sync::Arc;
 
-/// Load a set of stopwords
-/// Annoyingly, `HashSet`s are not allowed as static variables in Rust (yet?)
-pub fn load<'a>() -> HashSet<&'a str> {
-  [
-    "'t",
-    "doesn",
-    "wasn",
-    "isn",
-    "wouldn",
-    "a",
-    "able",
-    "about",
-    "above",
-    "abroad",
-    "according",
-    "accordingly",
-    "across",
-    "actually",
-    "adj",
-    "after",
-    "afterwards",
-    "again",
-    "against",
-    "ago",
-    "ahead",
-    "ain't",
-    "al",
-    "all",
-    "allow",
-    "allows",
-    "almost",
-    "alone",
-    "along",
-    "alongside",
-    "already",
-    "also",
-    "although",
-    "always",
-    "am",
-    "amid",
-    "amidst",
-    "among",
-    "amongst",
-    "an",
-    "and",
-    "another",
-    "any",
-    "anybody",
-    "anyhow",
-    "anyone",
-    "anything",
-    "anyway",
-    "anyways",
-    "anywhere",
-    "apart",
-    "appear",
-    "appreciate",
-    "appropriate",
-    "arbitrary",
-    "are",
-    "aren't",
-    "around",
-    "a's",
-    "as",
-    "aside",
-    "ask",
-    "asking",
-    "associated",
-    "at",
-    "available",
-    "away",
-    "awfully",
-    "backward",
-    "backwards",
-    "be",
-    "became",
-    "because",
-    "become",
-    "becomes",
-    "becoming",
-    "been",
-    "before",
-    "beforehand",
-    "begin",
-    "behind",
-    "being",
-    "believe",
-    "below",
-    "beside",
-    "besides",
-    "best",
-    "better",
-    "between",
-    "beyond",
-    "both",
-    "brief",
-    "but",
-    "by",
-    "call",
-    "called",
-    "came",
-    "can",
-    "cannot",
-    "can't",
-    "cant",
-    "caption",
-    "cause",
-    "causes",
-    "certain",
-    "certainly",
-    "case",
-    "changes",
-    "clearly",
-    "c'mon",
-    "co.",
-    "co",
-    "com",
-    "come",
-    "comes",
-    "concerning",
-    "consequently",
-    "consider",
-    "considering",
-    "consist",
-    "consisting",
-    "contain",
-    "containing",
-    "contains",
-    "corresponding",
-    "could",
-    "couldn't",
-    "course",
-    "c's",
-    "currently",
-    "dare",
-    "daren't",
-    "definitely",
-    "defined",
-    "denote",
-    "denoted",
-    "described",
-    "despite",
-    "did",
-    "didn't",
-    "different",
-    "directly",
-    "do",
-    "does",
-    "doesn't",
-    "doing",
-    "done",
-    "don't",
-    "down",
-    "downwards",
-    "during",
-    "each",
-    "easy",
-    "edu",
-    "eg",
-    "eight",
-    "eighty",
-    "either",
-    "else",
-    "elsewhere",
-    "ending",
-    "enough",
-    "entirely",
-    "entry",
-    "especially",
-    "et",
-    "etc",
-    "ever",
-    "evermore",
-    "every",
-    "everybody",
-    "everyone",
-    "everything",
-    "everywhere",
-    "ex",
-    "exactly",
-    "example",
-    "except",
-    "expressed",
-    "express",
-    "fairly",
-    "far",
-    "farther",
-    "few",
-    "fewer",
-    "followed",
-    "following",
-    "follows",
-    "for",
-    "forever",
-    "former",
-    "formerly",
-    "forth",
-    "forward",
-    "found",
-    "from",
-    "further",
-    "furthermore",
-    "get",
-    "gets",
-    "getting",
-    "give",
-    "given",
-    "gives",
-    "goes",
-    "going",
-    "gone",
-    "got",
-    "gotten",
-    "greetings",
-    "had",
-    "hadn't",
-    "happens",
-    "hardly",
-    "has",
-    "hasn't",
-    "have",
-    "haven't",
-    "having",
-    "he",
-    "he'd",
-    "he'll",
-    "hello",
-    "help",
-    "hence",
-    "her",
-    "here",
-    "hereafter",
-    "hereby",
-    "herein",
-    "here's",
-    "hereupon",
-    "hers",
-    "herself",
-    "he's",
-    "hi",
-    "him",
-    "himself",
-    "his",
-    "hither",
-    "hopefully",
-    "how",
-    "howbeit",
-    "however",
-    "i",
-    "i'd",
-    "ie",
-    "if",
-    "ignored",
-    "i'll",
-    "i'm",
-    "immediate",
-    "in",
-    "inasmuch",
-    "inc.",
-    "inc",
-    "include",
-    "includes",
-    "indeed",
-    "indicate",
-    "indicated",
-    "indicates",
-    "inside",
-    "insofar",
-    "instead",
-    "into",
-    "inward",
-    "is",
-    "isn't",
-    "it",
-    "it'd",
-    "it'll",
-    "it's",
-    "its",
-    "itself",
-    "i've",
-    "just",
-    "keep",
-    "keeps",
-    "kept",
-    "know",
-    "known",
-    "knows",
-    "last",
-    "lately",
-    "later",
-    "latter",
-    "latterly",
-    "least",
-    "less",
-    "lest",
-    "let",
-    "let's",
-    "like",
-    "liked",
-    "likely",
-    "likewise",
-    "look",
-    "looking",
-    "looks",
-    "ltd",
-    "made",
-    "mainly",
-    "make",
-    "makes",
-    "many",
-    "may",
-    "maybe",
-    "mayn't",
-    "me",
-    "meantime",
-    "meanwhile",
-    "merely",
-    "might",
-    "mightn't",
-    "mine",
-    "miss",
-    "more",
-    "moreover",
-    "most",
-    "mostly",
-    "mr",
-    "mrs",
-    "much",
-    "must",
-    "mustn't",
-    "my",
-    "myself",
-    "name",
-    "namely",
-    "nd",
-    "near",
-    "nearly",
-    "necessary",
-    "need",
-    "needn't",
-    "needs",
-    "neither",
-    "never",
-    "neverf",
-    "neverless",
-    "nevertheless",
-    "new",
-    "next",
-    "nine",
-    "ninety",
-    "no",
-    "nobody",
-    "non",
-    "none",
-    "nonetheless",
-    "no-one",
-    "noone",
-    "nor",
-    "normally",
-    "not",
-    "note",
-    "notion",
-    "nothing",
-    "notwithstanding",
-    "novel",
-    "now",
-    "nowhere",
-    "obtain",
-    "obtained",
-    "obviously",
-    "of",
-    "off",
-    "often",
-    "oh",
-    "ok",
-    "okay",
-    "old",
-    "on",
-    "once",
-    "one",
-    "one's",
-    "ones",
-    "only",
-    "onto",
-    "opposite",
-    "or",
-    "originally",
-    "other",
-    "others",
-    "otherwise",
-    "ought",
-    "oughtn't",
-    "our",
-    "ours",
-    "ourselves",
-    "out",
-    "outside",
-    "over",
-    "overall",
-    "own",
-    "particular",
-    "particularly",
-    "past",
-    "per",
-    "perhaps",
-    "placed",
-    "please",
-    "possible",
-    "presumably",
-    "probably",
-    "prove",
-    "proves",
-    "proved",
-    "provided",
-    "provides",
-    "que",
-    "quite",
-    "qv",
-    "rather",
-    "rd",
-    "re",
-    "really",
-    "reasonably",
-    "recent",
-    "recently",
-    "reference",
-    "regarding",
-    "regardless",
-    "regards",
-    "relatively",
-    "required",
-    "respective",
-    "respectively",
-    "said",
-    "same",
-    "saw",
-    "say",
-    "saying",
-    "says",
-    "secondly",
-    "see",
-    "seeing",
-    "seem",
-    "seemed",
-    "seeming",
-    "seems",
-    "seen",
-    "self",
-    "selves",
-    "sensible",
-    "sent",
-    "serious",
-    "seriously",
-    "seven",
-    "several",
-    "shall",
-    "shan't",
-    "she",
-    "she'd",
-    "she'll",
-    "she's",
-    "show",
-    "shows",
-    "showed",
-    "should",
-    "shouldn't",
-    "side",
-    "similarly",
-    "since",
-    "six",
-    "so",
-    "solve",
-    "solving",
-    "solved",
-    "some",
-    "somebody",
-    "someday",
-    "somehow",
-    "someone",
-    "something",
-    "sometime",
-    "sometimes",
-    "somewhat",
-    "somewhere",
-    "soon",
-    "sorry",
-    "specified",
-    "specify",
-    "specifying",
-    "still",
-    "sub",
-    "such",
-    "sup",
-    "suppose",
-    "sure",
-    "take",
-    "taken",
-    "taking",
-    "tell",
-    "tends",
-    "th",
-    "than",
-    "thank",
-    "thanks",
-    "thanx",
-    "that",
-    "that'll",
-    "that's",
-    "thats",
-    "that've",
-    "the",
-    "their",
-    "theirs",
-    "them",
-    "themselves",
-    "then",
-    "thence",
-    "there",
-    "thereafter",
-    "thereby",
-    "there'd",
-    "therefore",
-    "therein",
-    "there'll",
-    "there're",
-    "there's",
-    "theres",
-    "thereupon",
-    "there've",
-    "these",
-    "they",
-    "they'd",
-    "they'll",
-    "they're",
-    "they've",
-    "thing",
-    "things",
-    "think",
-    "thirty",
-    "this",
-    "thorough",
-    "thoroughly",
-    "those",
-    "though",
-    "three",
-    "through",
-    "throughout",
-    "thru",
-    "thus",
-    "till",
-    "to",
-    "together",
-    "too",
-    "took",
-    "toward",
-    "towards",
-    "tried",
-    "tries",
-    "truly",
-    "try",
-    "trying",
-    "t's",
-    "twice",
-    "two",
-    "un",
-    "under",
-    "underneath",
-    "undoing",
-    "unfortunately",
-    "unless",
-    "unlike",
-    "unlikely",
-    "until",
-    "unto",
-    "up",
-    "upon",
-    "upwards",
-    "us",
-    "use",
-    "used",
-    "useful",
-    "uses",
-    "using",
-    "usually",
-    "various",
-    "versus",
-    "very",
-    "via",
-    "viz",
-    "vs",
-    "want",
-    "wants",
-    "was",
-    "wasn't",
-    "way",
-    "we",
-    "we'd",
-    "welcome",
-    "we'll",
-    "well",
-    "went",
-    "we're",
-    "were",
-    "weren't",
-    "we've",
-    "what",
-    "whatever",
-    "what'll",
-    "what's",
-    "what've",
-    "when",
-    "whence",
-    "whenever",
-    "where",
-    "whereafter",
-    "whereas",
-    "whereby",
-    "wherein",
-    "where's",
-    "whereupon",
-    "wherever",
-    "whether",
-    "which",
-    "whichever",
-    "while",
-    "whilst",
-    "whither",
-    "who",
-    "who'd",
-    "whoever",
-    "whole",
-    "who'll",
-    "whom",
-    "whomever",
-    "who's",
-    "whose",
-    "why",
-    "will",
-    "willing",
-    "wish",
-    "with",
-    "within",
-    "without",
-    "wonder",
-    "won't",
-    "work",
-    "would",
-    "wouldn't",
-    "write",
-    "written",
-    "yes",
-    "yet",
-    "you",
-    "you'd",
-    "you'll",
-    "your",
-    "you're",
-    "yours",
-    "yourself",
-    "yourselves",
-    "you've",
-    "inspired",
-  ]
-  .iter()
-  .cloned()
-  .collect()
+use lazy_static::lazy_static;
+
+lazy_static! {
+  static ref ENGLISH: Arc<Stopwords> = Arc::new(Stopwords::from_words("en", ENGLISH_WORDS));
+}
+
+/// Load the built-in English stopwords as a plain set, see `english` for the shared `Stopwords`
+pub fn load<'a>() -> HashSet<&'a str> { ENGLISH_WORDS.iter().cloned().collect() }
+
+/// The built-in English stopwords, shared rather than rebuilt on every call
+pub fn english() -> Arc<Stopwords> { Arc::clone(&ENGLISH) }
+
+/// A set of stopwords (or stopword phrases, with words separated by single spaces) for a language
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stopwords {
+  /// the language of the set, e.g. "en"
+  pub language: String,
+  words: HashSet<String>,
 }
+
+impl Stopwords {
+  /// an empty set of stopwords for a language
+  pub fn new(language: &str) -> Self {
+    Stopwords {
+      language: language.to_string(),
+      words: HashSet::new(),
+    }
+  }
+
+  /// a set of stopwords from a list of words
+  pub fn from_words<S: AsRef<str>>(language: &str, words: &[S]) -> Self {
+    let mut stopwords = Stopwords::new(language);
+    for word in words {
+      stopwords.insert(word.as_ref());
+    }
+    stopwords
+  }
+
+  /// the built-in stopwords for a language, if we have any
+  pub fn builtin(language: &str) -> Option<Arc<Stopwords>> {
+    match language {
+      "en" | "eng" | "english" => Some(english()),
+      _ => None,
+    }
+  }
+
+  /// Load a stopwords file with one word or phrase per line, skipping empty lines and "#" comments
+  pub fn load(language: &str, path: &str) -> Result<Self, Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
+    let words: Vec<&str> = content
+      .lines()
+      .map(str::trim)
+      .filter(|line| !line.is_empty() && !line.starts_with('#'))
+      .collect();
+    Ok(Stopwords::from_words(language, &words))
+  }
+
+  /// add a word or phrase, normalizing its whitespace
+  pub fn insert(&mut self, word: &str) -> bool {
+    self
+      .words
+      .insert(word.split_whitespace().collect::<Vec<_>>().join(" "))
+  }
+
+  /// remove a word or phrase
+  pub fn remove(&mut self, word: &str) -> bool { self.words.remove(word) }
+
+  /// check if a word (or phrase) is a stopword
+  pub fn contains(&self, word: &str) -> bool { self.words.contains(word) }
+
+  /// check if a sequence of words is a stopword phrase, or starts or ends with a stopword; such
+  /// ngrams are rarely meaningful on their own
+  pub fn is_stop_sequence(&self, words: &[&str]) -> bool {
+    match (words.first(), words.last()) {
+      (Some(first), Some(last)) => {
+        self.contains(first) || self.contains(last) || self.contains(&words.join(" "))
+      },
+      _ => false,
+    }
+  }
+
+  /// the number of stopwords and phrases
+  pub fn len(&self) -> usize { self.words.len() }
+
+  /// checks if there are no stopwords
+  pub fn is_empty(&self) -> bool { self.words.is_empty() }
+
+  /// iterate over the stopwords and phrases, in no particular order
+  pub fn iter(&self) -> impl Iterator<Item = &str> { self.words.iter().map(String::as_str) }
+
+  /// the stopwords in either set, keeping the language of `self`
+  pub fn union(&self, other: &Stopwords) -> Stopwords {
+    Stopwords {
+      language: self.language.clone(),
+      words: self.words.union(&other.words).cloned().collect(),
+    }
+  }
+
+  /// the stopwords of `self` which are not in `other`
+  pub fn difference(&self, other: &Stopwords) -> Stopwords {
+    Stopwords {
+      language: self.language.clone(),
+      words: self.words.difference(&other.words).cloned().collect(),
+    }
+  }
+}
+
+/// Document frequencies of words and phrases over a corpus, for deriving corpus-specific stopwords
+#[derive(Debug, Clone)]
+pub struct DocumentFrequencies {
+  /// the longest phrase (in words) to record
+  pub max_n: usize,
+  /// the number of documents added so far
+  pub document_count: usize,
+  /// per word or phrase, the number of documents containing it and its total count
+  pub counts: HashMap<String, (usize, usize)>,
+  /// per phrase length, the total number of phrases of that length
+  phrase_totals: Vec<usize>,
+}
+
+impl DocumentFrequencies {
+  /// record words and phrases of up to `max_n` words
+  pub fn new(max_n: usize) -> Self {
+    DocumentFrequencies {
+      max_n: max_n.max(1),
+      document_count: 0,
+      counts: HashMap::new(),
+      phrase_totals: vec![0; max_n.max(1)],
+    }
+  }
+
+  /// Record the (already normalized, e.g. lowercased) words of a document. Empty words, and words
+  /// with spaces, such as the quantities of `Tokenizer::words_punct_and_quantities` (e.g. "3.5
+  /// km/s"), are not recorded, nor are the phrases containing them, so that every recorded phrase
+  /// of n words has n space-separated parts
+  pub fn add_document(&mut self, words: &[&str]) {
+    self.document_count += 1;
+    let mut document_counts: HashMap<String, usize> = HashMap::new();
+    for n in 1..=self.max_n.min(words.len()) {
+      for phrase in words.windows(n) {
+        if phrase
+          .iter()
+          .any(|word| word.is_empty() || word.contains(char::is_whitespace))
+        {
+          continue;
+        }
+        self.phrase_totals[n - 1] += 1;
+        *document_counts.entry(phrase.join(" ")).or_insert(0) += 1;
+      }
+    }
+    for (phrase, count) in document_counts {
+      let entry = self.counts.entry(phrase).or_insert((0, 0));
+      entry.0 += 1;
+      entry.1 += count;
+    }
+  }
+
+  /// the number of documents containing a word or phrase
+  pub fn document_frequency(&self, phrase: &str) -> usize {
+    self.counts.get(phrase).map(|counts| counts.0).unwrap_or(0)
+  }
+
+  /// the words and phrases which occur in at least a `min_ratio` fraction of all documents
+  pub fn stopwords_by_document_frequency(&self, language: &str, min_ratio: f64) -> Stopwords {
+    let min_documents = min_ratio * self.document_count as f64;
+    let words: Vec<&str> = self
+      .counts
+      .iter()
+      .filter(|(_, (documents, _))| *documents as f64 >= min_documents)
+      .map(|(phrase, _)| phrase.as_str())
+      .collect();
+    Stopwords::from_words(language, &words)
+  }
+
+  /// the words and phrases seen in at least a `min_ratio` fraction of all documents whose
+  /// corpus-level TF-IDF (relative frequency among phrases of the same length, times the inverse
+  /// document frequency `ln(N/df)`) is at most `max_score`, i.e. which are both frequent and evenly
+  /// spread. Rare words have a low TF-IDF as well, which a `min_ratio` of e.g. 0.1 leaves out
+  pub fn stopwords_by_tfidf(&self, language: &str, max_score: f64, min_ratio: f64) -> Stopwords {
+    let min_documents = min_ratio * self.document_count as f64;
+    let words: Vec<&str> = self
+      .counts
+      .iter()
+      .filter(|(phrase, (documents, _))| {
+        *documents as f64 >= min_documents && self.tfidf(phrase) <= max_score
+      })
+      .map(|(phrase, _)| phrase.as_str())
+      .collect();
+    Stopwords::from_words(language, &words)
+  }
+
+  /// the corpus-level TF-IDF of a word or phrase, see `stopwords_by_tfidf`
+  pub fn tfidf(&self, phrase: &str) -> f64 {
+    match self.counts.get(phrase) {
+      Some((documents, count)) => {
+        let length = phrase.split(' ').count();
+        let total = self
+          .phrase_totals
+          .get(length - 1)
+          .copied()
+          .unwrap_or(0)
+          .max(1) as f64;
+        let idf = (self.document_count as f64 / *documents as f64).ln();
+        (*count as f64 / total) * idf
+      },
+      None => 0.0,
+    }
+  }
+}
+
+/// The built-in English stopwords
+const ENGLISH_WORDS: &[&str] = &[
+  "'t",
+  "doesn",
+  "wasn",
+  "isn",
+  "wouldn",
+  "a",
+  "able",
+  "about",
+  "above",
+  "abroad",
+  "according",
+  "accordingly",
+  "across",
+  "actually",
+  "adj",
+  "after",
+  "afterwards",
+  "again",
+  "against",
+  "ago",
+  "ahead",
+  "ain't",
+  "al",
+  "all",
+  "allow",
+  "allows",
+  "almost",
+  "alone",
+  "along",
+  "alongside",
+  "already",
+  "also",
+  "although",
+  "always",
+  "am",
+  "amid",
+  "amidst",
+  "among",
+  "amongst",
+  "an",
+  "and",
+  "another",
+  "any",
+  "anybody",
+  "anyhow",
+  "anyone",
+  "anything",
+  "anyway",
+  "anyways",
+  "anywhere",
+  "apart",
+  "appear",
+  "appreciate",
+  "appropriate",
+  "arbitrary",
+  "are",
+  "aren't",
+  "around",
+  "a's",
+  "as",
+  "aside",
+  "ask",
+  "asking",
+  "associated",
+  "at",
+  "available",
+  "away",
+  "awfully",
+  "backward",
+  "backwards",
+  "be",
+  "became",
+  "because",
+  "become",
+  "becomes",
+  "becoming",
+  "been",
+  "before",
+  "beforehand",
+  "begin",
+  "behind",
+  "being",
+  "believe",
+  "below",
+  "beside",
+  "besides",
+  "best",
+  "better",
+  "between",
+  "beyond",
+  "both",
+  "brief",
+  "but",
+  "by",
+  "call",
+  "called",
+  "came",
+  "can",
+  "cannot",
+  "can't",
+  "cant",
+  "caption",
+  "cause",
+  "causes",
+  "certain",
+  "certainly",
+  "case",
+  "changes",
+  "clearly",
+  "c'mon",
+  "co.",
+  "co",
+  "com",
+  "come",
+  "comes",
+  "concerning",
+  "consequently",
+  "consider",
+  "considering",
+  "consist",
+  "consisting",
+  "contain",
+  "containing",
+  "contains",
+  "corresponding",
+  "could",
+  "couldn't",
+  "course",
+  "c's",
+  "currently",
+  "dare",
+  "daren't",
+  "definitely",
+  "defined",
+  "denote",
+  "denoted",
+  "described",
+  "despite",
+  "did",
+  "didn't",
+  "different",
+  "directly",
+  "do",
+  "does",
+  "doesn't",
+  "doing",
+  "done",
+  "don't",
+  "down",
+  "downwards",
+  "during",
+  "each",
+  "easy",
+  "edu",
+  "eg",
+  "eight",
+  "eighty",
+  "either",
+  "else",
+  "elsewhere",
+  "ending",
+  "enough",
+  "entirely",
+  "entry",
+  "especially",
+  "et",
+  "etc",
+  "ever",
+  "evermore",
+  "every",
+  "everybody",
+  "everyone",
+  "everything",
+  "everywhere",
+  "ex",
+  "exactly",
+  "example",
+  "except",
+  "expressed",
+  "express",
+  "fairly",
+  "far",
+  "farther",
+  "few",
+  "fewer",
+  "followed",
+  "following",
+  "follows",
+  "for",
+  "forever",
+  "former",
+  "formerly",
+  "forth",
+  "forward",
+  "found",
+  "from",
+  "further",
+  "furthermore",
+  "get",
+  "gets",
+  "getting",
+  "give",
+  "given",
+  "gives",
+  "goes",
+  "going",
+  "gone",
+  "got",
+  "gotten",
+  "greetings",
+  "had",
+  "hadn't",
+  "happens",
+  "hardly",
+  "has",
+  "hasn't",
+  "have",
+  "haven't",
+  "having",
+  "he",
+  "he'd",
+  "he'll",
+  "hello",
+  "help",
+  "hence",
+  "her",
+  "here",
+  "hereafter",
+  "hereby",
+  "herein",
+  "here's",
+  "hereupon",
+  "hers",
+  "herself",
+  "he's",
+  "hi",
+  "him",
+  "himself",
+  "his",
+  "hither",
+  "hopefully",
+  "how",
+  "howbeit",
+  "however",
+  "i",
+  "i'd",
+  "ie",
+  "if",
+  "ignored",
+  "i'll",
+  "i'm",
+  "immediate",
+  "in",
+  "inasmuch",
+  "inc.",
+  "inc",
+  "include",
+  "includes",
+  "indeed",
+  "indicate",
+  "indicated",
+  "indicates",
+  "inside",
+  "insofar",
+  "instead",
+  "into",
+  "inward",
+  "is",
+  "isn't",
+  "it",
+  "it'd",
+  "it'll",
+  "it's",
+  "its",
+  "itself",
+  "i've",
+  "just",
+  "keep",
+  "keeps",
+  "kept",
+  "know",
+  "known",
+  "knows",
+  "last",
+  "lately",
+  "later",
+  "latter",
+  "latterly",
+  "least",
+  "less",
+  "lest",
+  "let",
+  "let's",
+  "like",
+  "liked",
+  "likely",
+  "likewise",
+  "look",
+  "looking",
+  "looks",
+  "ltd",
+  "made",
+  "mainly",
+  "make",
+  "makes",
+  "many",
+  "may",
+  "maybe",
+  "mayn't",
+  "me",
+  "meantime",
+  "meanwhile",
+  "merely",
+  "might",
+  "mightn't",
+  "mine",
+  "miss",
+  "more",
+  "moreover",
+  "most",
+  "mostly",
+  "mr",
+  "mrs",
+  "much",
+  "must",
+  "mustn't",
+  "my",
+  "myself",
+  "name",
+  "namely",
+  "nd",
+  "near",
+  "nearly",
+  "necessary",
+  "need",
+  "needn't",
+  "needs",
+  "neither",
+  "never",
+  "neverf",
+  "neverless",
+  "nevertheless",
+  "new",
+  "next",
+  "nine",
+  "ninety",
+  "no",
+  "nobody",
+  "non",
+  "none",
+  "nonetheless",
+  "no-one",
+  "noone",
+  "nor",
+  "normally",
+  "not",
+  "note",
+  "notion",
+  "nothing",
+  "notwithstanding",
+  "novel",
+  "now",
+  "nowhere",
+  "obtain",
+  "obtained",
+  "obviously",
+  "of",
+  "off",
+  "often",
+  "oh",
+  "ok",
+  "okay",
+  "old",
+  "on",
+  "once",
+  "one",
+  "one's",
+  "ones",
+  "only",
+  "onto",
+  "opposite",
+  "or",
+  "originally",
+  "other",
+  "others",
+  "otherwise",
+  "ought",
+  "oughtn't",
+  "our",
+  "ours",
+  "ourselves",
+  "out",
+  "outside",
+  "over",
+  "overall",
+  "own",
+  "particular",
+  "particularly",
+  "past",
+  "per",
+  "perhaps",
+  "placed",
+  "please",
+  "possible",
+  "presumably",
+  "probably",
+  "prove",
+  "proves",
+  "proved",
+  "provided",
+  "provides",
+  "que",
+  "quite",
+  "qv",
+  "rather",
+  "rd",
+  "re",
+  "really",
+  "reasonably",
+  "recent",
+  "recently",
+  "reference",
+  "regarding",
+  "regardless",
+  "regards",
+  "relatively",
+  "required",
+  "respective",
+  "respectively",
+  "said",
+  "same",
+  "saw",
+  "say",
+  "saying",
+  "says",
+  "secondly",
+  "see",
+  "seeing",
+  "seem",
+  "seemed",
+  "seeming",
+  "seems",
+  "seen",
+  "self",
+  "selves",
+  "sensible",
+  "sent",
+  "serious",
+  "seriously",
+  "seven",
+  "several",
+  "shall",
+  "shan't",
+  "she",
+  "she'd",
+  "she'll",
+  "she's",
+  "show",
+  "shows",
+  "showed",
+  "should",
+  "shouldn't",
+  "side",
+  "similarly",
+  "since",
+  "six",
+  "so",
+  "solve",
+  "solving",
+  "solved",
+  "some",
+  "somebody",
+  "someday",
+  "somehow",
+  "someone",
+  "something",
+  "sometime",
+  "sometimes",
+  "somewhat",
+  "somewhere",
+  "soon",
+  "sorry",
+  "specified",
+  "specify",
+  "specifying",
+  "still",
+  "sub",
+  "such",
+  "sup",
+  "suppose",
+  "sure",
+  "take",
+  "taken",
+  "taking",
+  "tell",
+  "tends",
+  "th",
+  "than",
+  "thank",
+  "thanks",
+  "thanx",
+  "that",
+  "that'll",
+  "that's",
+  "thats",
+  "that've",
+  "the",
+  "their",
+  "theirs",
+  "them",
+  "themselves",
+  "then",
+  "thence",
+  "there",
+  "thereafter",
+  "thereby",
+  "there'd",
+  "therefore",
+  "therein",
+  "there'll",
+  "there're",
+  "there's",
+  "theres",
+  "thereupon",
+  "there've",
+  "these",
+  "they",
+  "they'd",
+  "they'll",
+  "they're",
+  "they've",
+  "thing",
+  "things",
+  "think",
+  "thirty",
+  "this",
+  "thorough",
+  "thoroughly",
+  "those",
+  "though",
+  "three",
+  "through",
+  "throughout",
+  "thru",
+  "thus",
+  "till",
+  "to",
+  "together",
+  "too",
+  "took",
+  "toward",
+  "towards",
+  "tried",
+  "tries",
+  "truly",
+  "try",
+  "trying",
+  "t's",
+  "twice",
+  "two",
+  "un",
+  "under",
+  "underneath",
+  "undoing",
+  "unfortunately",
+  "unless",
+  "unlike",
+  "unlikely",
+  "until",
+  "unto",
+  "up",
+  "upon",
+  "upwards",
+  "us",
+  "use",
+  "used",
+  "useful",
+  "uses",
+  "using",
+  "usually",
+  "various",
+  "versus",
+  "very",
+  "via",
+  "viz",
+  "vs",
+  "want",
+  "wants",
+  "was",
+  "wasn't",
+  "way",
+  "we",
+  "we'd",
+  "welcome",
+  "we'll",
+  "well",
+  "went",
+  "we're",
+  "were",
+  "weren't",
+  "we've",
+  "what",
+  "whatever",
+  "what'll",
+  "what's",
+  "what've",
+  "when",
+  "whence",
+  "whenever",
+  "where",
+  "whereafter",
+  "whereas",
+  "whereby",
+  "wherein",
+  "where's",
+  "whereupon",
+  "wherever",
+  "whether",
+  "which",
+  "whichever",
+  "while",
+  "whilst",
+  "whither",
+  "who",
+  "who'd",
+  "whoever",
+  "whole",
+  "who'll",
+  "whom",
+  "whomever",
+  "who's",
+  "whose",
+  "why",
+  "will",
+  "willing",
+  "wish",
+  "with",
+  "within",
+  "without",
+  "wonder",
+  "won't",
+  "work",
+  "would",
+  "wouldn't",
+  "write",
+  "written",
+  "yes",
+  "yet",
+  "you",
+  "you'd",
+  "you'll",
+  "your",
+  "you're",
+  "yours",
+  "yourself",
+  "yourselves",
+  "you've",
+  "inspired",
+];
@@ -1,26 +1,36 @@
 //! Provides functionality for tokenizing sentences and words
 use crate::dnm::{DNMRange, DNM};
-use crate::stopwords;
+use crate::stopwords::{self, Stopwords};
+use libxml::readonly::RoNode;
 use std::cmp;
 use std::collections::vec_deque::*;
-use std::collections::HashSet;
 use std::iter::Peekable;
 use std::str::Chars;
+use std::sync::Arc;
 
 use regex::Regex;
 
+/// Recognition of numbers and quantities as single tokens
+pub mod quantity;
+pub use self::quantity::{Quantity, QuantityKind, QuantityValue};
+/// Evaluation of tokenizer configurations against gold segmentations
+pub mod evaluation;
+/// Recognition of citations and cross-references as single tokens
+pub mod reference;
+pub use self::reference::{Reference, ReferenceKind};
+
 /// Stores auxiliary resources required by the tokenizer so that they need to be initialized only
 /// once
 pub struct Tokenizer {
-  /// set of stopwords
-  pub stopwords: HashSet<&'static str>,
+  /// set of stopwords, a capitalized stopword following a period always starts a new sentence
+  stopwords: Arc<Stopwords>,
   /// regular expression for abbreviations
-  pub abbreviations: Regex,
+  abbreviations: Regex,
 }
 impl Default for Tokenizer {
   fn default() -> Tokenizer {
     Tokenizer {
-      stopwords : stopwords::load(),
+      stopwords : stopwords::english(),
       abbreviations : Regex::new(r"^(?:C(?:[ft]|o(?:n[jn]|lo?|rp)?|a(?:l(?:if)?|pt)|mdr|p?l|res)|M(?:[dst]|a(?:[jnry]|ss)|i(?:ch|nn|ss)|o(?:nt)?|ex?|rs?)|A(?:r(?:[ck]|iz)|l(?:t?a)?|ttys?|ssn|dm|pr|ug|ve)|c(?:o(?:rp|l)?|(?:ap)?t|mdr|p?l|res|f)|S(?:e(?:ns?|pt?|c)|(?:up|g)?t|ask|r)|s(?:e(?:ns?|pt?|c)|(?:up|g)?t|r)|a(?:ttys?|ssn|dm|pr|rc|ug|ve|l)|P(?:enna?|-a.s|de?|lz?|rof|a)|D(?:e(?:[cfl]|p?t)|ist|ak|r)|I(?:[as]|n[cd]|da?|.e|ll)|F(?:e[bd]|w?y|ig|la|t)|O(?:k(?:la)?|[cn]t|re)|d(?:e(?:p?t|c)|ist|r)|E(?:xpy?|.g|sp|tc|qs?)|R(?:e(?:ps?|sp|v)|d)|T(?:e(?:nn|x)|ce|hm)|e(?:xpy?|.g|sp|tc|qs?)|m(?:[st]|a[jry]|rs?)|r(?:e(?:ps?|sp|v)|d)|N(?:e(?:br?|v)|ov?)|W(?:isc?|ash|yo?)|f(?:w?y|eb|ig|t)|p(?:de?|lz?|rof)|J(?:u[ln]|an|r)|U(?:SAFA|niv|t)|j(?:u[ln]|an|r)|K(?:ans?|en|y)|B(?:lv?d|ros)|b(?:lv?d|ros)|G(?:en|ov|a)|L(?:td?|a)|g(?:en|ov)|i(?:.e|nc)|l(?:td?|a)|[Hh]wa?y|V[ast]|Que|nov?|univ|Yuk|oct|tce|vs)\s?$").unwrap(),
     }
   }
@@ -49,151 +59,391 @@ fn wordlike_with_upper_next(peekable: Peekable<Chars>) -> bool {
   detected
 }
 
+/// the last word in the left window of the sentence tokenizer
+fn left_word(left_window: &VecDeque<char>) -> String {
+  let lw_string: String = left_window.iter().collect();
+  let lw_word = lw_string.trim().rsplit(|c: char| !c.is_alphabetic()).next();
+  lw_word.unwrap_or_default().to_string()
+}
+
 impl Tokenizer {
+  /// a default tokenizer with another set of stopwords, e.g. for another language, or derived
+  /// from a corpus
+  pub fn with_stopwords(stopwords: Arc<Stopwords>) -> Tokenizer {
+    Tokenizer {
+      stopwords,
+      ..Tokenizer::default()
+    }
+  }
+
+  /// the stopwords, a capitalized stopword following a period always starts a new sentence
+  pub fn stopwords(&self) -> &Stopwords { &self.stopwords }
+
+  /// the regular expression for abbreviations, whose period does not end a sentence
+  pub fn abbreviations(&self) -> &Regex { &self.abbreviations }
+
   fn abbreviation_check(&self, left_window: &VecDeque<char>) -> bool {
     // Check for abbreviations:
     // Longest abbreviation is 6 characters, but mathformula is 11, so take window
     // of window_size chars to the left (allow for a space before dot)
-    let lw_string: String = left_window.clone().into_iter().collect();
-    let lw_str: &str = &lw_string;
-    let lw_opt = lw_str.trim().split(|c: char| !c.is_alphabetic()).last();
-    let lw_word: &str = match lw_opt {
-      None => lw_str,
-      Some(w) => w,
-    };
+    let lw_word = left_word(left_window);
     // Don't consider single letters followed by a punctuation sign an end of a
     // sentence, Also "a.m." and "p.m." shouldn't get split
-    ((lw_word.len() == 1) && (lw_word != "I")) || self.abbreviations.is_match(lw_word)
+    ((lw_word.len() == 1) && (lw_word != "I")) || self.abbreviations.is_match(&lw_word)
+  }
+
+  /// checks for an abbreviated reference prefix, such as "Eq." in "Eq. (2.1)", which never ends a
+  /// sentence
+  fn reference_prefix_check(&self, left_window: &VecDeque<char>) -> bool {
+    reference::is_abbreviated_prefix(&left_word(left_window))
+  }
+
+  /// checks for an "et al." which does not end its sentence, as it is followed by a year or a
+  /// reference, e.g. "Smith et al. (2004)"
+  fn et_al_check<I: Iterator<Item = char>>(
+    &self,
+    left_window: &VecDeque<char>,
+    next: I,
+  ) -> Option<bool> {
+    let left: String = left_window.iter().collect();
+    if reference::ends_with_et_al(&left) {
+      let next: String = next.take(WINDOW_SIZE * 2).collect();
+      Some(reference::starts_with_year_or_reference(&next))
+    } else {
+      None
+    }
   }
 
-  // TODO: Reduce complexity, this tokenization pass is terribly overengineered
   /// gets the sentences from a dnm
-  #[allow(clippy::cognitive_complexity)]
   pub fn sentences<'a>(&self, dnm: &'a DNM) -> Vec<DNMRange<'a>> {
-    let text = &dnm.plaintext;
-    let mut sentences: Vec<DNMRange<'a>> = Vec::new();
-    let mut text_iterator = text.chars().peekable();
-    let mut start = 0;
-    let mut end = 0;
-    let window_size = 12; // size of max string + 1
-    let mut left_window: VecDeque<char> = VecDeque::with_capacity(window_size);
-
-    while let Some(sentence_char) = text_iterator.next() {
+    self.sentence_iter(dnm).collect()
+  }
+
+  /// lazily yields the sentences of a dnm, as they are detected in its plaintext
+  pub fn sentence_iter<'t, 'a>(&'t self, dnm: &'a DNM) -> SentenceIterator<'t, 'a> {
+    SentenceIterator {
+      tokenizer: self,
+      dnm,
+      text_iterator: dnm.plaintext.chars().peekable(),
+      start: 0,
+      end: 0,
+      left_window: VecDeque::with_capacity(WINDOW_SIZE),
+      finished: false,
+    }
+  }
+
+  /// returns the words of a sentence using simple heuristics
+  pub fn words<'b>(&'b self, sentence_range: &DNMRange<'b>) -> Vec<DNMRange> {
+    self.word_iter(sentence_range).collect()
+  }
+
+  /// lazily yields the words of a range, using the same heuristics as `words`
+  pub fn word_iter<'a>(&self, range: &DNMRange<'a>) -> WordIterator<'a> {
+    WordIterator {
+      text_iterator: range.get_plaintext().chars().peekable(),
+      range: range.clone(),
+      start: 0,
+      end: 0,
+    }
+  }
+
+  /// returns the words and punctuation of a sentence, using simple heuristics
+  pub fn words_and_punct<'b>(&'b self, range: &DNMRange<'b>) -> Vec<DNMRange> {
+    self.word_and_punct_iter(range).collect()
+  }
+
+  /// lazily yields the words and punctuation of a range, using the same heuristics as
+  /// `words_and_punct`
+  pub fn word_and_punct_iter<'a>(&self, range: &DNMRange<'a>) -> WordAndPunctIterator<'a> {
+    let range_text = range.get_plaintext();
+    WordAndPunctIterator {
+      range: range.clone(),
+      range_text,
+      text_iterator: range_text.chars(),
+      start: 0,
+      end: 0,
+      apostrophe_flag: false,
+      queue: VecDeque::new(),
+      finished: false,
+    }
+  }
+
+  /// returns the numeric expressions (numbers, ordinals, ranges, percentages, quantities with
+  /// units) of a range, with their normalized values
+  pub fn quantities<'a>(&self, range: &DNMRange<'a>) -> Vec<Quantity<'a>> {
+    quantity::scan(range.get_plaintext())
+      .into_iter()
+      .map(|(start, end, value)| Quantity {
+        range: range.get_subrange_from_byte_offsets(start, end),
+        value,
+      })
+      .collect()
+  }
+
+  /// returns the words and punctuation of a range, as `words_and_punct`, except that each
+  /// numeric expression (e.g. "3.5 km/s" or "10^{-3}") is kept as a single range
+  pub fn words_punct_and_quantities<'a>(&self, range: &DNMRange<'a>) -> Vec<DNMRange<'a>> {
+    let quantities = self
+      .quantities(range)
+      .into_iter()
+      .map(|quantity| quantity.range);
+    self.merge_spans(range, quantities)
+  }
+
+  /// returns the citations (e.g. "[12, 14-16]", "Smith et al. (2004)") and cross-references (e.g.
+  /// "Fig. 3(b)", "Eq. (2.1)") of a range. When the DNM keeps a back-mapping, each reference is
+  /// linked to its `ltx_cite`/`ltx_ref` node, and markup that matches no textual pattern is still
+  /// reported as a reference of its own
+  pub fn references<'a>(&self, range: &DNMRange<'a>) -> Vec<Reference<'a>> {
+    let mut references: Vec<Reference<'a>> = reference::scan(range.get_plaintext())
+      .into_iter()
+      .map(|(start, end, kind)| Reference {
+        range: range.get_subrange_from_byte_offsets(start, end),
+        kind,
+        node: None,
+      })
+      .collect();
+    let dnm = range.dnm;
+    if !dnm.parameters.support_back_mapping || dnm.back_map.len() < range.end {
+      return references;
+    }
+    // collect the runs of characters backed by the same reference element
+    let mut markup: Vec<(usize, usize, RoNode)> = Vec::new();
+    let mut last_node: Option<(usize, Option<RoNode>)> = None;
+    for position in range.start..range.end {
+      let node = dnm.back_map[position].0;
+      let reference_node = match last_node {
+        Some((hashable, reference_node)) if hashable == node.to_hashable() => reference_node,
+        _ => reference::reference_node(node),
+      };
+      last_node = Some((node.to_hashable(), reference_node));
+      if let Some(reference_node) = reference_node {
+        match markup.last_mut() {
+          Some((_, end, run_node))
+            if *end == position && run_node.to_hashable() == reference_node.to_hashable() =>
+          {
+            *end = position + 1
+          },
+          _ => markup.push((position, position + 1, reference_node)),
+        }
+      }
+    }
+    for (start, end, node) in markup {
+      match references
+        .iter_mut()
+        .find(|reference| reference.range.start < end && start < reference.range.end)
+      {
+        Some(reference) => {
+          if reference.node.is_none() {
+            reference.node = Some(node);
+          }
+        },
+        None => {
+          let markup_range = DNMRange { start, end, dnm }.trim();
+          if !markup_range.is_empty() {
+            references.push(Reference {
+              range: markup_range,
+              kind: reference::markup_kind(node),
+              node: Some(node),
+            });
+          }
+        },
+      }
+    }
+    references.sort_by_key(|reference| reference.range.start);
+    references
+  }
+
+  /// returns the words and punctuation of a range, as `words_and_punct`, except that each
+  /// citation or cross-reference (e.g. "Smith et al. (2004)" or "Fig. 3(b)") is kept as a single
+  /// range
+  pub fn words_punct_and_references<'a>(&self, range: &DNMRange<'a>) -> Vec<DNMRange<'a>> {
+    let references = self
+      .references(range)
+      .into_iter()
+      .map(|reference| reference.range);
+    self.merge_spans(range, references)
+  }
+
+  /// merges ordered, non-overlapping spans into the words and punctuation of a range, dropping the
+  /// fragments they cover. The merged words are character ranges, as the spans, also in non-ASCII
+  /// text
+  fn merge_spans<'a>(
+    &self,
+    range: &DNMRange<'a>,
+    spans: impl Iterator<Item = DNMRange<'a>>,
+  ) -> Vec<DNMRange<'a>> {
+    let mut spans = spans.peekable();
+    let mut tokens = Vec::new();
+    let mut span_end = range.start;
+    for word in self.word_and_punct_iter(range) {
+      // the words are offset by bytes into the range, the spans by characters, as `DNMRange`s
+      let word =
+        range.get_subrange_from_byte_offsets(word.start - range.start, word.end - range.start);
+      while let Some(next_span) = spans.next_if(|span| span.start < word.end) {
+        span_end = next_span.end;
+        tokens.push(next_span);
+      }
+      if word.start >= span_end {
+        tokens.push(word);
+      }
+    }
+    tokens.extend(spans);
+    tokens
+  }
+}
+
+/// size of max string + 1, for the left context window of the sentence tokenizer
+const WINDOW_SIZE: usize = 12;
+
+/// A lazy iterator over the sentences of a `DNM`, which only scans as much of the plaintext as is
+/// needed to yield the next sentence
+pub struct SentenceIterator<'t, 'a> {
+  tokenizer: &'t Tokenizer,
+  dnm: &'a DNM,
+  text_iterator: Peekable<Chars<'a>>,
+  start: usize,
+  end: usize,
+  left_window: VecDeque<char>,
+  finished: bool,
+}
+
+impl<'t, 'a> Iterator for SentenceIterator<'t, 'a> {
+  type Item = DNMRange<'a>;
+  fn next(&mut self) -> Option<DNMRange<'a>> {
+    while !self.finished {
+      // Filter out edge cases that return empty ranges
+      if let Some(range) = self.advance() {
+        if range.start < range.end {
+          return Some(range);
+        }
+      }
+    }
+    None
+  }
+}
+
+impl<'t, 'a> SentenceIterator<'t, 'a> {
+  /// add a character to the left window, dropping the oldest one if the window is full
+  fn push_window(&mut self, c: char) {
+    self.left_window.push_back(c);
+    if self.left_window.len() >= WINDOW_SIZE {
+      self.left_window.pop_front();
+    }
+  }
+
+  /// a sentence break at the current end offset: reset the left window and start a new sentence
+  fn split(&mut self) -> DNMRange<'a> {
+    self.left_window.clear();
+    let sentence = DNMRange {
+      start: self.start,
+      end: self.end,
+      dnm: self.dnm,
+    }
+    .trim();
+    self.start = self.end;
+    sentence
+  }
+
+  // TODO: Reduce complexity, this tokenization pass is terribly overengineered
+  /// consumes characters until the next sentence break (returning the candidate sentence), or
+  /// until the end of the plaintext (returning the trailing sentence, if any)
+  #[allow(clippy::cognitive_complexity)]
+  fn advance(&mut self) -> Option<DNMRange<'a>> {
+    while let Some(sentence_char) = self.text_iterator.next() {
+      let mut sentence = None;
       // Bookkeep the end position
-      end += sentence_char.len_utf8();
+      self.end += sentence_char.len_utf8();
 
       match sentence_char {
         '.' | ':' => {
           // Baseline condition - only split when we have a following word-ish string with an
           // uppercase letter Get next non-space, non-quote character
-          while text_iterator.peek().unwrap_or(&'.').is_whitespace()
-            || text_iterator.peek() == Some(&'\'')
+          while self.text_iterator.peek().unwrap_or(&'.').is_whitespace()
+            || self.text_iterator.peek() == Some(&'\'')
           {
-            let space_char = text_iterator.next().unwrap();
-            end += space_char.len_utf8();
+            let space_char = self.text_iterator.next().unwrap();
+            self.end += space_char.len_utf8();
           }
-          if text_iterator.peek().is_none() {
+          if self.text_iterator.peek().is_none() {
             break;
           }
           // Uppercase next?
-          if wordlike_with_upper_next(text_iterator.clone()) {
+          if wordlike_with_upper_next(self.text_iterator.clone()) {
             // Ok, uppercase, but is it a stopword? If so, we must ALWAYS break the
             // sentence:
-            let (next_word_string, next_word_length) = next_word_with_length(&mut text_iterator);
+            let (next_word_string, next_word_length) =
+              next_word_with_length(&mut self.text_iterator);
             let next_word_lc = next_word_string.to_lowercase();
             // Always break the sentence when we see a stopword
-            if self.stopwords.contains(next_word_lc.as_str()) {
-              // Reset the left window
-              left_window = VecDeque::with_capacity(window_size);
-              // New sentence
-              sentences.push(DNMRange { start, end, dnm }.trim());
-              start = end;
+            if self.tokenizer.stopwords.contains(next_word_lc.as_str()) {
+              sentence = Some(self.split());
             } else {
-              // Regular word case.
-              if self.abbreviation_check(&left_window) {
-                left_window.push_back('.');
-                if left_window.len() >= window_size {
-                  left_window.pop_front();
-                }
+              // Regular word case. "et al." before a capitalized word ends its sentence
+              let next = next_word_string.chars().chain(self.text_iterator.clone());
+              let et_al = self.tokenizer.et_al_check(&self.left_window, next);
+              if et_al.unwrap_or_else(|| {
+                self.tokenizer.abbreviation_check(&self.left_window)
+                  || self.tokenizer.reference_prefix_check(&self.left_window)
+              }) {
+                self.push_window('.');
               }
               //TODO: Handle dot-dot-dot "..."
               else {
                 // Not a special case, break the sentence
-                // Reset the left window
-                left_window = VecDeque::with_capacity(window_size);
-                // New sentence
-                sentences.push(DNMRange { start, end, dnm }.trim());
-                start = end;
+                sentence = Some(self.split());
               }
               // We consumed the next word, so make sure we reflect that in either case:
               for next_word_char in next_word_string.chars() {
-                left_window.push_back(next_word_char);
-                if left_window.len() >= window_size {
-                  left_window.pop_front();
-                }
+                self.push_window(next_word_char);
               }
             }
-            end += next_word_length;
+            self.end += next_word_length;
           } else {
             // lowercase and non-alphanum characters
-            match text_iterator.peek() {
+            // a reference prefix, or "et al." before a year, does not end its sentence
+            let reference_follows = self.text_iterator.peek() == Some(&'(')
+              && self
+                .tokenizer
+                .et_al_check(&self.left_window, self.text_iterator.clone())
+                .unwrap_or_else(|| self.tokenizer.reference_prefix_check(&self.left_window));
+            match self.text_iterator.peek() {
+              Some(&'(') if reference_follows => {
+                self.push_window('.');
+              },
               Some(&'*') | Some(&'"') | Some(&'(') => {
-                // Reset the left window
-                left_window = VecDeque::with_capacity(window_size);
-                // New sentence
-                sentences.push(DNMRange { start, end, dnm }.trim());
-                start = end;
+                sentence = Some(self.split());
               },
               Some(&c) => {
                 if sentence_char == '.' && c.is_alphabetic() {
                   let (next_word_string, next_word_length) =
-                    next_word_with_length(&mut text_iterator);
+                    next_word_with_length(&mut self.text_iterator);
                   // TODO: Maybe extend to more lowercase stopwords here? unclear...
                   if next_word_string.to_lowercase().starts_with("mathformula")
-                    && !self.abbreviation_check(&left_window)
+                    && !self.tokenizer.abbreviation_check(&self.left_window)
                   {
-                    // Reset the left window
-                    left_window = VecDeque::with_capacity(window_size);
-                    // New sentence
-                    sentences.push(DNMRange { start, end, dnm }.trim());
-                    start = end;
+                    sentence = Some(self.split());
                   } else {
-                    left_window.push_back('.');
-                    if left_window.len() >= window_size {
-                      left_window.pop_front();
-                    }
+                    self.push_window('.');
                   }
                   // We consumed the next word, so make sure we reflect that in either case:
                   for next_word_char in next_word_string.chars() {
-                    left_window.push_back(next_word_char);
-                    if left_window.len() >= window_size {
-                      left_window.pop_front();
-                    }
+                    self.push_window(next_word_char);
                   }
-                  end += next_word_length;
+                  self.end += next_word_length;
                 } else {
-                  left_window.push_back('.');
-                  if left_window.len() >= window_size {
-                    left_window.pop_front();
-                  }
+                  self.push_window('.');
                 }
               },
               None => {
-                left_window.push_back('.');
-                if left_window.len() >= window_size {
-                  left_window.pop_front();
-                }
+                self.push_window('.');
               },
             }
           }
         },
         '?' | '!' => {
-          if !is_bounded(left_window.back(), text_iterator.peek()) {
-            // Reset the left window
-            left_window = VecDeque::with_capacity(window_size);
-            // New sentence
-            sentences.push(DNMRange { start, end, dnm }.trim());
-            start = end;
+          if !is_bounded(self.left_window.back(), self.text_iterator.peek()) {
+            sentence = Some(self.split());
           }
         },
         // TODO:
@@ -201,18 +451,19 @@ impl Tokenizer {
         // Some('\u{220e}') => { // QED symbol
         '\n' => {
           // newline
-          if let Some(&'\n') = text_iterator.peek() {
+          if let Some(&'\n') = self.text_iterator.peek() {
             // second newline
             // Get next non-space character
-            while text_iterator.peek().unwrap_or(&'.').is_whitespace() {
-              let space_char = text_iterator.next().unwrap();
-              end += space_char.len_utf8();
+            while self.text_iterator.peek().unwrap_or(&'.').is_whitespace() {
+              let space_char = self.text_iterator.next().unwrap();
+              self.end += space_char.len_utf8();
             }
-            if text_iterator.peek().is_none() {
+            if self.text_iterator.peek().is_none() {
               break;
             }
             // Get the next word
-            let (next_word_string, next_word_length) = next_word_with_length(&mut text_iterator);
+            let (next_word_string, next_word_length) =
+              next_word_with_length(&mut self.text_iterator);
             // Sentence-break, UNLESS a "mathformula" or a "lowercase word" follows, or a
             // non-alpha char
             if next_word_string.is_empty()
@@ -221,146 +472,176 @@ impl Tokenizer {
             {
               // We consumed the next word, add it to the left window
               for next_word_char in next_word_string.chars() {
-                left_window.push_back(next_word_char);
-                if left_window.len() >= window_size {
-                  left_window.pop_front();
-                }
+                self.push_window(next_word_char);
               }
             } else {
               // Sentence-break found:
-              left_window = VecDeque::with_capacity(window_size);
-              sentences.push(DNMRange { start, end, dnm }.trim());
-              start = end;
+              sentence = Some(self.split());
             }
 
             // We consumed the next word, so make sure we reflect that in either case:
-            end += next_word_length;
+            self.end += next_word_length;
           }
         },
         other_char => {
           // "mathformula\nCapitalized" case is a sentence break (but never
           // "mathformula\nmathformula")
-          if other_char.is_uppercase() {
-            let lw_string: String = left_window.iter().collect();
-            if lw_string.starts_with("mathformula") {
-              // Sentence-break found, but exclude the current letter from the end:
-              left_window = VecDeque::with_capacity(window_size);
-              sentences.push(
-                DNMRange {
-                  start,
-                  end: end - other_char.len_utf8(),
-                  dnm,
-                }
-                .trim(),
-              );
-              start = end - other_char.len_utf8();
-            }
+          if other_char.is_uppercase()
+            && self
+              .left_window
+              .iter()
+              .copied()
+              .take(11)
+              .eq("mathformula".chars())
+          {
+            // Sentence-break found, but exclude the current letter from the end:
+            self.end -= other_char.len_utf8();
+            sentence = Some(self.split());
+            self.end += other_char.len_utf8();
           }
           // Increment the left window
-          left_window.push_back(other_char);
-          if left_window.len() >= window_size {
-            left_window.pop_front();
-          }
+          self.push_window(other_char);
         },
       }
+      if sentence.is_some() {
+        return sentence;
+      }
     }
 
-    end = cmp::min(end, text.chars().count());
-    let last_left_window: String = left_window.into_iter().collect();
-    if last_left_window.find(char::is_alphabetic).is_some() {
-      sentences.push(DNMRange { start, end, dnm }.trim());
+    // The plaintext is exhausted, wrap up with the trailing sentence, if any
+    self.finished = true;
+    let end = cmp::min(self.end, self.dnm.plaintext.chars().count());
+    if self.left_window.iter().any(|c| c.is_alphabetic()) {
+      Some(
+        DNMRange {
+          start: self.start,
+          end,
+          dnm: self.dnm,
+        }
+        .trim(),
+      )
+    } else {
+      None
     }
-
-    // Filter out edge cases that return empty ranges
-    sentences
-      .into_iter()
-      .filter(|range| range.start < range.end)
-      .collect()
   }
+}
 
-  /// returns the words of a sentence using simple heuristics
-  pub fn words<'b>(&'b self, sentence_range: &DNMRange<'b>) -> Vec<DNMRange> {
-    let mut text_iterator = sentence_range.get_plaintext().chars().peekable();
-    let mut start = 0usize;
-    let mut end = 0usize;
-    let mut result: Vec<DNMRange> = Vec::new();
-    while let Some(c) = text_iterator.next() {
-      end += c.len_utf8();
+/// A lazy iterator over the words of a `DNMRange`, see `Tokenizer::words`
+pub struct WordIterator<'a> {
+  range: DNMRange<'a>,
+  text_iterator: Peekable<Chars<'a>>,
+  start: usize,
+  end: usize,
+}
+
+impl<'a> Iterator for WordIterator<'a> {
+  type Item = DNMRange<'a>;
+  fn next(&mut self) -> Option<DNMRange<'a>> {
+    while let Some(c) = self.text_iterator.next() {
+      self.end += c.len_utf8();
       if !c.is_alphanumeric() {
-        if c == '\'' || c == '’' {
-          if let Some(peeked) = text_iterator.peek() {
-            if peeked == &'s' {
-              continue;
-            }
-          }
+        if (c == '\'' || c == '’') && self.text_iterator.peek() == Some(&'s') {
+          continue;
         }
-        if start < end - c.len_utf8() {
-          result.push(sentence_range.get_subrange(start, end - c.len_utf8()));
+        let word_start = self.start;
+        let word_end = self.end - c.len_utf8();
+        self.start = self.end;
+        if word_start < word_end {
+          return Some(self.range.get_subrange(word_start, word_end));
         }
-        start = end;
       }
     }
-    if start < end {
-      result.push(sentence_range.get_subrange(start, end));
+    if self.start < self.end {
+      let word = self.range.get_subrange(self.start, self.end);
+      self.start = self.end;
+      Some(word)
+    } else {
+      None
     }
-    result
   }
+}
 
-  /// returns the words and punctuation of a sentence, using simple heuristics
-  #[allow(unused_assignments)]
-  pub fn words_and_punct<'b>(&'b self, range: &DNMRange<'b>) -> Vec<DNMRange> {
-    let range_text = range.get_plaintext();
-    let text_iterator = range_text.chars();
-    let mut start = 0usize;
-    let mut end = 0usize;
-    let mut result: Vec<DNMRange> = Vec::new();
-    let mut apostrophe_flag = false;
-    macro_rules! complete_word {
-      () => {
-        if start < end {
-          if apostrophe_flag {
-            match &range_text[start + 1..end] {
-              // Handle closed set of apostrophe cases, detach from all other cases
-              "t" | "s" | "un" | "th" | "ll" | "d" | "ve" | "il" | "re" | "m" => {},
-              _ => {
-                result.push(range.get_subrange(start, start + 1));
-                start += 1;
-              },
-            }
-          }
-          result.push(range.get_subrange(start, end));
-          apostrophe_flag = false;
-          start = end;
-        }
-      };
+/// A lazy iterator over the words and punctuation of a `DNMRange`, see
+/// `Tokenizer::words_and_punct`
+pub struct WordAndPunctIterator<'a> {
+  range: DNMRange<'a>,
+  range_text: &'a str,
+  text_iterator: Chars<'a>,
+  start: usize,
+  end: usize,
+  apostrophe_flag: bool,
+  /// a single character can complete up to three words, which are queued here
+  queue: VecDeque<DNMRange<'a>>,
+  finished: bool,
+}
+
+impl<'a> Iterator for WordAndPunctIterator<'a> {
+  type Item = DNMRange<'a>;
+  fn next(&mut self) -> Option<DNMRange<'a>> {
+    loop {
+      if let Some(word) = self.queue.pop_front() {
+        return Some(word);
+      }
+      if self.finished {
+        return None;
+      }
+      match self.text_iterator.next() {
+        Some(c) => self.step(c),
+        None => {
+          self.complete_word();
+          self.finished = true;
+        },
+      }
     }
+  }
+}
 
-    for c in text_iterator {
-      // letters, numbers can accumulate
-      if c.is_alphanumeric() {
-        end += c.len_utf8();
-      } else {
-        // everything else completes a word and starts a new one
-        complete_word!();
-        // except that whitepace can be skipped over
-        if c.is_whitespace() {
-          end += c.len_utf8();
-          start = end;
+impl<'a> WordAndPunctIterator<'a> {
+  fn complete_word(&mut self) {
+    if self.start < self.end {
+      if self.apostrophe_flag {
+        match &self.range_text[self.start + 1..self.end] {
+          // Handle closed set of apostrophe cases, detach from all other cases
+          "t" | "s" | "un" | "th" | "ll" | "d" | "ve" | "il" | "re" | "m" => {},
+          _ => {
+            self
+              .queue
+              .push_back(self.range.get_subrange(self.start, self.start + 1));
+            self.start += 1;
+          },
         }
-        // non-alphanum chars are standalone words EXCEPT when connectors such as apostrophes
-        else {
-          end += c.len_utf8();
-          if c == '\'' || c == '’' {
-            apostrophe_flag = true;
-          } else {
-            // standalone char word case
-            complete_word!();
-          }
+      }
+      self
+        .queue
+        .push_back(self.range.get_subrange(self.start, self.end));
+      self.apostrophe_flag = false;
+      self.start = self.end;
+    }
+  }
+
+  fn step(&mut self, c: char) {
+    // letters, numbers can accumulate
+    if c.is_alphanumeric() {
+      self.end += c.len_utf8();
+    } else {
+      // everything else completes a word and starts a new one
+      self.complete_word();
+      // except that whitepace can be skipped over
+      if c.is_whitespace() {
+        self.end += c.len_utf8();
+        self.start = self.end;
+      }
+      // non-alphanum chars are standalone words EXCEPT when connectors such as apostrophes
+      else {
+        self.end += c.len_utf8();
+        if c == '\'' || c == '’' {
+          self.apostrophe_flag = true;
+        } else {
+          // standalone char word case
+          self.complete_word();
         }
       }
     }
-    complete_word!();
-    result
   }
 }
 /// checks whether two characters are matching brackets or quotation marks
@@ -0,0 +1,341 @@
+//! Evaluation of tokenizer configurations against gold sentence and token segmentations.
+//!
+//! A gold standard is a tab-separated file, with one gold span per line, e.g.:
+//! ```text
+//! # document  level     span
+//! paper.html  sentence  0  57
+//! paper.html  token     arange(//*[@id="p1"]/text()[1],string-index(//*[@id="p1"]/text()[1],4))
+//! ```
+//! where the document path is relative to the gold file, the level is either "sentence" or
+//! "token", and the span is either a pair of character offsets into the document's DNM plaintext
+//! (which then depend on the `DNMParameters` used) or an XPointer, as produced by
+//! `DNMRange::serialize`.
+use crate::dnm::{DNMParameters, DNMRange, DNM};
+use libxml::parser::Parser;
+use libxml::xpath::Context;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Characters of plaintext shown on each side of a disagreement
+const CONTEXT_WIDTH: usize = 20;
+
+/// The segmentation level of a gold span
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Level {
+  /// sentence boundaries
+  Sentence,
+  /// token (word and punctuation) boundaries
+  Token,
+}
+
+/// A gold span, as recorded in the gold standard file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GoldSpan {
+  /// start and end character offsets into the DNM plaintext
+  Offsets(usize, usize),
+  /// an `arange(...)` XPointer, resolved against the document DOM
+  XPointer(String),
+}
+
+/// The gold segmentations of a single document
+#[derive(Clone, Debug, Default)]
+pub struct GoldDocument {
+  /// gold sentence spans
+  pub sentences: Vec<GoldSpan>,
+  /// gold token spans
+  pub tokens: Vec<GoldSpan>,
+}
+
+impl GoldDocument {
+  /// the gold spans at a given level
+  pub fn spans(&self, level: Level) -> &[GoldSpan] {
+    match level {
+      Level::Sentence => &self.sentences,
+      Level::Token => &self.tokens,
+    }
+  }
+}
+
+/// A set of documents with gold segmentations, keyed by document path
+#[derive(Clone, Debug, Default)]
+pub struct GoldStandard {
+  /// the gold segmentations of each document
+  pub documents: BTreeMap<String, GoldDocument>,
+}
+
+impl GoldStandard {
+  /// Load a gold standard file, resolving the document paths relative to its directory
+  pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+    let base = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+    let mut gold = GoldStandard::parse(&fs::read_to_string(path)?)?;
+    gold.documents = gold
+      .documents
+      .into_iter()
+      .map(|(document, spans)| (base.join(document).to_string_lossy().to_string(), spans))
+      .collect();
+    Ok(gold)
+  }
+
+  /// Parse the tab-separated gold standard format, keeping the document paths as they are
+  pub fn parse(text: &str) -> Result<Self, Box<dyn Error>> {
+    let mut gold = GoldStandard::default();
+    for (line_number, line) in text.lines().enumerate() {
+      let line = line.trim();
+      if line.is_empty() || line.starts_with('#') {
+        continue;
+      }
+      let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+      let span = match fields.as_slice() {
+        [_, _, start, end] => GoldSpan::Offsets(start.parse()?, end.parse()?),
+        [_, _, xpointer] if xpointer.starts_with("arange(") => {
+          GoldSpan::XPointer(xpointer.to_string())
+        },
+        _ => return Err(format!("malformed gold span on line {}: {line}", line_number + 1).into()),
+      };
+      let document = gold.documents.entry(fields[0].to_string()).or_default();
+      match fields[1] {
+        "sentence" => document.sentences.push(span),
+        "token" => document.tokens.push(span),
+        other => return Err(format!("unknown level {other:?} on line {}", line_number + 1).into()),
+      }
+    }
+    Ok(gold)
+  }
+}
+
+/// Whether a boundary was only in the gold standard or only in the tokenizer output
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DisagreementKind {
+  /// a gold boundary the tokenizer did not produce
+  Missed,
+  /// a tokenizer boundary absent from the gold standard
+  Spurious,
+}
+
+/// A single boundary on which the tokenizer and the gold standard disagree
+#[derive(Clone, Debug)]
+pub struct Disagreement {
+  /// the document path
+  pub document: String,
+  /// the character offset of the boundary in the DNM plaintext
+  pub offset: usize,
+  /// missed or spurious
+  pub kind: DisagreementKind,
+  /// the surrounding plaintext, with the boundary marked as "|"
+  pub context: String,
+}
+
+/// Boundary counts and disagreements of a tokenizer configuration
+#[derive(Clone, Debug, Default)]
+pub struct Evaluation {
+  /// boundaries found in both the gold standard and the tokenizer output
+  pub true_positives: usize,
+  /// tokenizer boundaries absent from the gold standard
+  pub false_positives: usize,
+  /// gold boundaries the tokenizer did not produce
+  pub false_negatives: usize,
+  /// every boundary in `false_positives` and `false_negatives`, in document order
+  pub disagreements: Vec<Disagreement>,
+  /// the gold XPointers which could not be resolved in their document, and were skipped, as
+  /// "<document>: <xpointer>: <reason>"
+  pub unresolved: Vec<String>,
+}
+
+impl Evaluation {
+  /// boundary precision, 1.0 if nothing was predicted
+  pub fn precision(&self) -> f64 {
+    ratio(
+      self.true_positives,
+      self.true_positives + self.false_positives,
+    )
+  }
+
+  /// boundary recall, 1.0 if there are no gold boundaries
+  pub fn recall(&self) -> f64 {
+    ratio(
+      self.true_positives,
+      self.true_positives + self.false_negatives,
+    )
+  }
+
+  /// harmonic mean of boundary precision and recall
+  pub fn f1(&self) -> f64 {
+    let (precision, recall) = (self.precision(), self.recall());
+    if precision + recall == 0.0 {
+      0.0
+    } else {
+      2.0 * precision * recall / (precision + recall)
+    }
+  }
+
+  /// accumulate the counts and disagreements of another evaluation, e.g. of another document
+  pub fn merge(&mut self, other: Evaluation) {
+    self.true_positives += other.true_positives;
+    self.false_positives += other.false_positives;
+    self.false_negatives += other.false_negatives;
+    self.disagreements.extend(other.disagreements);
+    self.unresolved.extend(other.unresolved);
+  }
+}
+
+impl fmt::Display for Evaluation {
+  fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+    writeln!(
+      fmt,
+      "precision: {:.4}, recall: {:.4}, F1: {:.4} ({} correct, {} spurious, {} missed)",
+      self.precision(),
+      self.recall(),
+      self.f1(),
+      self.true_positives,
+      self.false_positives,
+      self.false_negatives
+    )?;
+    for disagreement in &self.disagreements {
+      let kind = match disagreement.kind {
+        DisagreementKind::Missed => "missed",
+        DisagreementKind::Spurious => "spurious",
+      };
+      writeln!(
+        fmt,
+        "{}\t{}\t{}\t{:?}",
+        disagreement.document, disagreement.offset, kind, disagreement.context
+      )?;
+    }
+    for unresolved in &self.unresolved {
+      writeln!(fmt, "unresolved gold span\t{unresolved}")?;
+    }
+    Ok(())
+  }
+}
+
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+  if denominator == 0 {
+    1.0
+  } else {
+    numerator as f64 / denominator as f64
+  }
+}
+
+/// Compare the boundaries (start and end offsets, ignoring surrounding whitespace) of the gold and
+/// predicted ranges of a single DNM
+pub fn evaluate_ranges(document: &str, gold: &[DNMRange], predicted: &[DNMRange]) -> Evaluation {
+  let boundaries = |ranges: &[DNMRange]| -> BTreeSet<usize> {
+    ranges
+      .iter()
+      .map(DNMRange::trim)
+      .filter(|range| !range.is_empty())
+      .flat_map(|range| [range.start, range.end])
+      .collect()
+  };
+  let (gold_boundaries, predicted_boundaries) = (boundaries(gold), boundaries(predicted));
+  let dnm = match gold.first().or_else(|| predicted.first()) {
+    Some(range) => range.dnm,
+    None => return Evaluation::default(),
+  };
+
+  let mut evaluation = Evaluation {
+    true_positives: gold_boundaries.intersection(&predicted_boundaries).count(),
+    ..Evaluation::default()
+  };
+  let missed = gold_boundaries
+    .difference(&predicted_boundaries)
+    .map(|offset| (*offset, DisagreementKind::Missed));
+  let spurious = predicted_boundaries
+    .difference(&gold_boundaries)
+    .map(|offset| (*offset, DisagreementKind::Spurious));
+  let mut disagreements: Vec<(usize, DisagreementKind)> = missed.chain(spurious).collect();
+  disagreements.sort_by_key(|(offset, _)| *offset);
+  for (offset, kind) in disagreements {
+    match kind {
+      DisagreementKind::Missed => evaluation.false_negatives += 1,
+      DisagreementKind::Spurious => evaluation.false_positives += 1,
+    }
+    evaluation.disagreements.push(Disagreement {
+      document: document.to_string(),
+      offset,
+      kind,
+      context: boundary_context(dnm, offset),
+    });
+  }
+  evaluation
+}
+
+/// Run a tokenizer configuration over the DNM of every document in a gold standard, and compare
+/// its output against the gold spans of the given level. The segmentation function maps a DNM to
+/// its sentence or token ranges, e.g. `|dnm| tokenizer.sentences(dnm)`. Gold XPointers which are
+/// malformed, or do not resolve in their document, are skipped and listed in
+/// `Evaluation::unresolved`
+pub fn evaluate<F>(
+  gold: &GoldStandard,
+  level: Level,
+  parameters: &DNMParameters,
+  segment: F,
+) -> Result<Evaluation, Box<dyn Error>>
+where
+  F: for<'a> Fn(&'a DNM) -> Vec<DNMRange<'a>>,
+{
+  let xml_parser = Parser::default();
+  let html_parser = Parser::default_html();
+  let mut evaluation = Evaluation::default();
+  for (path, gold_document) in &gold.documents {
+    let dom = if path.ends_with(".xhtml") {
+      xml_parser.parse_file(path)?
+    } else {
+      html_parser.parse_file(path)?
+    };
+    let root = dom
+      .get_root_readonly()
+      .ok_or_else(|| format!("{path} has no root element"))?;
+    let dnm = DNM::new(root, parameters.clone());
+    let mut context = None;
+    let mut gold_ranges = Vec::new();
+    let mut unresolved = Vec::new();
+    for span in gold_document.spans(level) {
+      gold_ranges.push(match span {
+        GoldSpan::Offsets(start, end) => {
+          if *start > *end || *end >= dnm.byte_offsets.len() {
+            return Err(format!("gold span {start}-{end} is out of bounds in {path}").into());
+          }
+          DNMRange {
+            start: *start,
+            end: *end,
+            dnm: &dnm,
+          }
+        },
+        GoldSpan::XPointer(xpointer) => {
+          if context.is_none() {
+            context = Some(Context::new(&dom).map_err(|_| "failed to create an XPath context")?);
+          }
+          match DNMRange::try_deserialize(xpointer, &dnm, context.as_ref().unwrap()) {
+            Ok(range) if range.start <= range.end && range.end < dnm.byte_offsets.len() => range,
+            Ok(range) => {
+              let reason = format!("resolves to {}-{}", range.start, range.end);
+              unresolved.push(format!("{path}: {xpointer}: {reason}"));
+              continue;
+            },
+            Err(reason) => {
+              unresolved.push(format!("{path}: {xpointer}: {reason}"));
+              continue;
+            },
+          }
+        },
+      });
+    }
+    evaluation.merge(evaluate_ranges(path, &gold_ranges, &segment(&dnm)));
+    evaluation.unresolved.append(&mut unresolved);
+  }
+  Ok(evaluation)
+}
+
+/// The plaintext around a boundary, marked as "|"
+fn boundary_context(dnm: &DNM, offset: usize) -> String {
+  let last = dnm.byte_offsets.len() - 1;
+  let offset = offset.min(last);
+  let from = dnm.byte_offsets[offset.saturating_sub(CONTEXT_WIDTH)];
+  let at = dnm.byte_offsets[offset];
+  let to = dnm.byte_offsets[(offset + CONTEXT_WIDTH).min(last)];
+  format!("{}|{}", &dnm.plaintext[from..at], &dnm.plaintext[at..to]).replace('\n', " ")
+}
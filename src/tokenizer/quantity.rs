@@ -0,0 +1,184 @@
+//! Recognition of numeric expressions and quantities, such as "1,000", "3.5 km/s", "10^{-3}",
+//! "2nd", "5-10%" or "2.1 x 10^5 GeV", so that they can be kept as single tokens
+use crate::dnm::DNMRange;
+use lazy_static::lazy_static;
+use regex::Regex;
+
+/// Integers with optional thousands separators, decimals and e-notation
+const NUMBER: &str = r"(?:\d{1,3}(?:,\d{3})+|\d+)(?:\.\d+)?(?:[eE][-+]?\d+)?";
+/// A power of ten, as in "10^{-3}" or "10^5"
+const POWER10: &str = r"10\^\{?[-+−]?\d+\}?";
+/// SI prefixes that are safe to recognize (e.g. atto- and deca- are omitted, as "as" and "da"
+/// are far more likely to be words)
+const PREFIX: &str = r"[TGMkhcmunp]";
+/// Units that accept a prefix
+const PREFIXABLE: &str = r"(?:mol|Hz|Pa|Wb|eV|pc|yr|bar|Jy|rad|sr|erg|[mgsKJWV]|L)";
+/// Units that are used without a prefix
+const STANDALONE: &str = r"(?:deg(?:C|F)?|arcsec|arcmin|mas|min|au|AU|[Oo]hm|h)";
+
+lazy_static! {
+  static ref QUANTITY: Regex = {
+    let value = format!(r"(?:{POWER10}|{NUMBER}(?:\s?(?:x|\*|×|\\times)\s?{POWER10})?)");
+    let atom = format!(r"(?:{PREFIX}?{PREFIXABLE}|{STANDALONE})");
+    let exponent = r"(?:\^\{?[-+−]?\d+\}?|[-−]?\d)";
+    let negative_exponent = r"(?:\^\{?[-−]\d+\}?|[-−]\d)";
+    let unit = format!(r"{atom}{exponent}?(?:/{atom}{exponent}?|\s{atom}{negative_exponent})*");
+    Regex::new(&format!(
+      r"\b(?:(?P<ordinal>\d+(?:st|nd|rd|th))\b|(?P<low>{value})(?:(?:-|\s?[–−]\s?|\s+to\s+)(?P<high>{value}))?(?:\s?(?P<percent>%|per\s?cent\b)|\s?(?P<unit>{unit})\b)?)"
+    ))
+    .unwrap()
+  };
+  static ref UNIT_EXPONENT: Regex = Regex::new(r"([A-Za-z])(-?\d)").unwrap();
+}
+
+/// The shape of a recognized numeric expression
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum QuantityKind {
+  /// e.g. "42", "1,000"
+  Integer,
+  /// e.g. "2.75"
+  Decimal,
+  /// e.g. "1.2e-5", "10^{-3}", "3 x 10^8"
+  Scientific,
+  /// e.g. "2nd", "21st"
+  Ordinal,
+  /// e.g. "5-10", "3 to 4"
+  Range,
+  /// e.g. "15%", "20 percent"
+  Percentage,
+}
+
+/// A normalized numeric expression, independent of the text it was found in
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuantityValue {
+  /// The shape of the expression
+  pub kind: QuantityKind,
+  /// The numeric value (the lower bound, for ranges)
+  pub value: f64,
+  /// The upper bound, for ranges
+  pub upper: Option<f64>,
+  /// The normalized unit attachment, if any (percentages use "%")
+  pub unit: Option<String>,
+}
+
+/// A numeric expression located in a `DNM`
+#[derive(Clone, Debug)]
+pub struct Quantity<'a> {
+  /// The range covering the entire expression, including its unit
+  pub range: DNMRange<'a>,
+  /// The normalized value of the expression
+  pub value: QuantityValue,
+}
+
+/// Finds all numeric expressions in a text, returning their byte spans and normalized values
+pub fn scan(text: &str) -> Vec<(usize, usize, QuantityValue)> {
+  let mut found = Vec::new();
+  for caps in QUANTITY.captures_iter(text) {
+    let whole = caps.get(0).unwrap();
+    let (mut start, end) = (whole.start(), whole.end());
+    // reject fragments of longer words, e.g. the "3" in "3D"
+    if text[end..]
+      .chars()
+      .next()
+      .map(char::is_alphanumeric)
+      .unwrap_or(false)
+    {
+      continue;
+    }
+    let mut value = match caps.name("ordinal") {
+      Some(ordinal) => QuantityValue {
+        kind: QuantityKind::Ordinal,
+        value: parse_value(ordinal.as_str().trim_end_matches(char::is_alphabetic)),
+        upper: None,
+        unit: None,
+      },
+      None => {
+        let low = caps.name("low").unwrap().as_str();
+        let upper = caps.name("high").map(|high| parse_value(high.as_str()));
+        let percent = caps.name("percent").is_some();
+        let kind = if upper.is_some() {
+          QuantityKind::Range
+        } else if percent {
+          QuantityKind::Percentage
+        } else if low.contains('^') || low.contains('e') || low.contains('E') {
+          QuantityKind::Scientific
+        } else if low.contains('.') {
+          QuantityKind::Decimal
+        } else {
+          QuantityKind::Integer
+        };
+        let unit = if percent {
+          Some(String::from("%"))
+        } else {
+          caps.name("unit").map(|unit| normalize_unit(unit.as_str()))
+        };
+        QuantityValue {
+          kind,
+          value: parse_value(low),
+          upper,
+          unit,
+        }
+      },
+    };
+    // a leading sign belongs to the quantity, unless it is a connector, as in "x-3"
+    let mut preceding = text[..start].chars().rev();
+    if let Some(sign) = preceding.next() {
+      if matches!(sign, '-' | '+' | '−')
+        && !preceding
+          .next()
+          .map(|c| c.is_alphanumeric() || c == ')')
+          .unwrap_or(false)
+      {
+        start -= sign.len_utf8();
+        if sign != '+' {
+          value.value = -value.value;
+        }
+      }
+    }
+    found.push((start, end, value));
+  }
+  found
+}
+
+/// Recognizes a text consisting of exactly one numeric expression (surrounding whitespace aside)
+pub fn parse(text: &str) -> Option<QuantityValue> {
+  let trimmed = text.trim();
+  if trimmed.is_empty() {
+    return None;
+  }
+  let mut found = scan(trimmed);
+  if found.len() == 1 && found[0].0 == 0 && found[0].1 == trimmed.len() {
+    found.pop().map(|(_, _, value)| value)
+  } else {
+    None
+  }
+}
+
+/// Numeric value of a (possibly scientific) number literal
+fn parse_value(literal: &str) -> f64 {
+  let literal = literal.replace(',', "").replace('−', "-");
+  match literal.find("10^") {
+    Some(power_start) => {
+      let exponent = literal[power_start + 3..]
+        .trim_matches(|c| c == '{' || c == '}')
+        .parse::<i32>()
+        .unwrap_or(0);
+      let mantissa = literal[..power_start]
+        .trim_end_matches(|c: char| !c.is_ascii_digit())
+        .parse::<f64>()
+        .unwrap_or(1.0);
+      mantissa * 10f64.powi(exponent)
+    },
+    None => literal.parse::<f64>().unwrap_or(0.0),
+  }
+}
+
+/// Canonical spelling of a unit attachment, e.g. "km s-1" to "km s^-1"
+fn normalize_unit(unit: &str) -> String {
+  let unit = unit
+    .replace(['{', '}'], "")
+    .replace('−', "-")
+    .replace("AU", "au")
+    .replace("Ohm", "ohm");
+  UNIT_EXPONENT.replace_all(&unit, "$1^$2").to_string()
+}
@@ -0,0 +1,252 @@
+//! Recognition of citations and cross-references, such as "[12, 14-16]", "Smith et al. (2004)",
+//! "Fig. 3(b)", "Eq. (2.1)" or "Theorem 4.2", so that they can be kept as single tokens
+use crate::dnm::DNMRange;
+use lazy_static::lazy_static;
+use libxml::readonly::RoNode;
+use regex::Regex;
+
+/// A capitalized author name, e.g. "Smith", "O'Neil" or "Jones-Smith"
+const AUTHOR: &str = r"\p{Lu}[\p{L}'’-]+";
+/// A publication year, e.g. "2004" or "2004a"
+const YEAR: &str = r"(?:1[5-9]|20)\d\d[a-z]?";
+/// A numeric or key citation label, e.g. "12", "14-16" or "Smi04"
+const CITE_LABEL: &str = r"(?:\d+[a-z]?|\p{Lu}[\p{L}+]*\d\d[a-z]?)";
+/// The label of a numbered object, e.g. "3", "2.1", "4b", "A.2", "IV" or a normalized "REF"
+const OBJECT_LABEL: &str = r"(?:REF|\d+(?:\.\d+)*[a-z]?|[A-Z](?:\.\d+)*|[IVX]+)\b";
+
+/// Capitalized words which may precede a year without being authors, e.g. "In (2004)" or
+/// "Since (1998)"
+const NON_AUTHORS: [&str; 62] = [
+  "About",
+  "After",
+  "Also",
+  "And",
+  "As",
+  "At",
+  "Before",
+  "But",
+  "By",
+  "During",
+  "For",
+  "From",
+  "Further",
+  "Hence",
+  "Here",
+  "If",
+  "In",
+  "Its",
+  "Later",
+  "Moreover",
+  "Of",
+  "On",
+  "Our",
+  "Since",
+  "So",
+  "Such",
+  "That",
+  "The",
+  "Their",
+  "Then",
+  "There",
+  "These",
+  "This",
+  "Those",
+  "Thus",
+  "To",
+  "Until",
+  "Up",
+  "We",
+  "When",
+  "Where",
+  "While",
+  "With",
+  "Year",
+  "Years",
+  "January",
+  "February",
+  "March",
+  "April",
+  "May",
+  "June",
+  "July",
+  "August",
+  "September",
+  "October",
+  "November",
+  "December",
+  "Spring",
+  "Summer",
+  "Fall",
+  "Autumn",
+  "Winter",
+];
+
+lazy_static! {
+  static ref AUTHOR_WORD: Regex = Regex::new(AUTHOR).unwrap();
+  static ref REFERENCE: Regex = {
+    let authors = format!(r"{AUTHOR}(?:\s+et\s+al\.?|\s+(?:and|&)\s+{AUTHOR})?");
+    let textual = format!(r"{authors}\s?\(\s*{YEAR}(?:\s*[,;]\s*{YEAR})*\s*\)");
+    let parenthetical =
+      format!(r"\(\s*{authors},?\s+{YEAR}(?:\s*[;,]\s*(?:{authors},?\s+)?{YEAR})*\s*\)");
+    let bracketed =
+      format!(r"\[\s*{CITE_LABEL}(?:\s*(?:[,;]|[-–])\s*{CITE_LABEL})*\s*\]|\bCitationElement\b");
+    let label =
+      format!(r"(?:\(\s*{OBJECT_LABEL}\s*\)|{OBJECT_LABEL})(?:\s?\(\s*[a-z]\s*\))?");
+    // the usual abbreviations may be lowercased ("see eq. (3)"), theorem-like names may not
+    let prefix = concat!(
+      r"(?:[Ff]ig(?:ure)?s?\.?|[Ee]q(?:n|uation)?s?\.?|[Tt]ab(?:le)?s?\.?|[Ss]ec(?:t|tion)?s?\.?",
+      r"|[Cc]h(?:ap)?\.|[Cc]hapters?|[Aa]pp\.|[Aa]ppendix|[Aa]ppendices|Thm\.|Theorems?|Lem\.",
+      r"|Lemmas?|Prop\.|Propositions?|Cor\.|Corollary|Corollaries|Def\.|Definitions?|Remarks?",
+      r"|Examples?|Conjectures?|Claims?|Alg\.|Algorithms?)"
+    );
+    Regex::new(&format!(
+      r"(?P<xref>(?P<prefix>\b{prefix}|§§?)\s?{label}(?:\s*(?:,|-|–|and|to)\s*{label})*)|(?P<authoryear>\b{textual}|{parenthetical})|(?P<citation>{bracketed})|(?P<ref>\(\s*REF\s*\)|\bREF\b)"
+    ))
+    .unwrap()
+  };
+  static ref ET_AL: Regex = Regex::new(r"(?:^|\W)et\s+al$").unwrap();
+  static ref YEAR_OR_REFERENCE: Regex = Regex::new(&format!(
+    r"^(?:\(\s*(?:{YEAR}|{CITE_LABEL}|REF)\b|\[\s*{CITE_LABEL}|{YEAR}\b|CitationElement\b|REF\b)"
+  ))
+  .unwrap();
+}
+
+/// The shape of a recognized citation or cross-reference
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReferenceKind {
+  /// a numeric or key citation group, e.g. "[12, 14-16]", or a normalized "CitationElement"
+  Citation,
+  /// an author-year citation, e.g. "Smith et al. (2004)" or "(Smith and Jones, 2004)"
+  AuthorYear,
+  /// a cross-reference to a numbered object, e.g. "Fig. 3(b)", with the object type (e.g.
+  /// "figure") when it is spelled out
+  CrossReference(Option<&'static str>),
+}
+
+/// A citation or cross-reference located in a `DNM`
+#[derive(Clone, Debug)]
+pub struct Reference<'a> {
+  /// The range covering the entire reference, including its prefix (e.g. "Fig.")
+  pub range: DNMRange<'a>,
+  /// The shape of the reference
+  pub kind: ReferenceKind,
+  /// The `ltx_cite` or `ltx_ref` markup behind the reference, if the DNM records it
+  pub node: Option<RoNode>,
+}
+
+/// Finds all citations and cross-references in a text, returning their byte spans and kinds
+pub fn scan(text: &str) -> Vec<(usize, usize, ReferenceKind)> {
+  REFERENCE
+    .captures_iter(text)
+    .filter_map(|caps| {
+      let whole = caps.get(0).unwrap();
+      let kind = if let Some(authoryear) = caps.name("authoryear") {
+        // the leading author must be a name, not e.g. "In" of "In (2004)"
+        let author = AUTHOR_WORD.find(authoryear.as_str())?;
+        if NON_AUTHORS.contains(&author.as_str()) {
+          return None;
+        }
+        ReferenceKind::AuthorYear
+      } else if caps.name("citation").is_some() {
+        ReferenceKind::Citation
+      } else {
+        ReferenceKind::CrossReference(caps.name("prefix").and_then(|p| object_type(p.as_str())))
+      };
+      Some((whole.start(), whole.end(), kind))
+    })
+    .collect()
+}
+
+/// Recognizes a text consisting of exactly one citation or cross-reference (surrounding whitespace
+/// aside)
+pub fn parse(text: &str) -> Option<ReferenceKind> {
+  let trimmed = text.trim();
+  match scan(trimmed).as_slice() {
+    [(0, end, kind)] if *end == trimmed.len() => Some(*kind),
+    _ => None,
+  }
+}
+
+/// Abbreviations which introduce a reference, and whose period does not end the sentence
+const ABBREVIATED_PREFIXES: [&str; 26] = [
+  "Fig", "Figs", "fig", "figs", "Eq", "Eqs", "Eqn", "Eqns", "eq", "eqs", "Tab", "Sec", "Secs",
+  "Sect", "sec", "Ch", "Chap", "App", "Thm", "Lem", "Prop", "Cor", "Def", "Alg", "Ref", "Refs",
+];
+
+/// Checks if a word is an abbreviated reference prefix, such as "Fig"
+pub fn is_abbreviated_prefix(word: &str) -> bool { ABBREVIATED_PREFIXES.contains(&word) }
+
+/// Checks if a text, e.g. the left context of a period, ends with "et al"
+pub fn ends_with_et_al(text: &str) -> bool { ET_AL.is_match(text.trim_end()) }
+
+/// Checks if a text starts with a year or a reference, e.g. "(2004)" or "[12]", as may follow
+/// "et al." within a sentence
+pub fn starts_with_year_or_reference(text: &str) -> bool { YEAR_OR_REFERENCE.is_match(text) }
+
+/// The `ltx_cite` or `ltx_ref` element a node belongs to, if any
+pub fn reference_node(node: RoNode) -> Option<RoNode> {
+  let mut current = Some(node);
+  while let Some(candidate) = current {
+    if candidate.is_element_node() {
+      if candidate.get_name() == "cite" {
+        return Some(candidate);
+      }
+      let classes = candidate.get_class_names();
+      if classes.contains("ltx_cite") || classes.contains("ltx_ref") {
+        return Some(candidate);
+      }
+    }
+    current = candidate.get_parent();
+  }
+  None
+}
+
+/// The kind of reference an `ltx_cite` or `ltx_ref` element stands for
+pub fn markup_kind(node: RoNode) -> ReferenceKind {
+  if node.get_name() == "cite" || node.get_class_names().contains("ltx_cite") {
+    ReferenceKind::Citation
+  } else {
+    ReferenceKind::CrossReference(None)
+  }
+}
+
+/// Normalized object type of a cross-reference prefix, e.g. "Figs." to "figure"
+fn object_type(prefix: &str) -> Option<&'static str> {
+  let prefix = prefix.to_lowercase();
+  let object = if prefix.starts_with('§') || prefix.starts_with("sec") {
+    "section"
+  } else if prefix.starts_with("fig") {
+    "figure"
+  } else if prefix.starts_with("eq") {
+    "equation"
+  } else if prefix.starts_with("tab") {
+    "table"
+  } else if prefix.starts_with("ch") {
+    "chapter"
+  } else if prefix.starts_with("app") {
+    "appendix"
+  } else if prefix.starts_with("th") {
+    "theorem"
+  } else if prefix.starts_with("lem") {
+    "lemma"
+  } else if prefix.starts_with("prop") {
+    "proposition"
+  } else if prefix.starts_with("cor") {
+    "corollary"
+  } else if prefix.starts_with("def") {
+    "definition"
+  } else if prefix.starts_with("rem") {
+    "remark"
+  } else if prefix.starts_with("ex") {
+    "example"
+  } else if prefix.starts_with("conj") {
+    "conjecture"
+  } else if prefix.starts_with("cl") {
+    "claim"
+  } else if prefix.starts_with("alg") {
+    "algorithm"
+  } else {
+    return None;
+  };
+  Some(object)
+}
@@ -9,9 +9,10 @@ use regex::Regex;
 use std::error::Error;
 use whatlang::{detect, Lang, Script};
 
+use super::heading_language::{self, HeadingLanguage};
 use crate::dnm;
 use crate::dnm::{DNMParameters, DNMRange, DNM};
-use crate::tokenizer::Tokenizer;
+use crate::tokenizer::{quantity, Tokenizer};
 
 // Integers, floats, subfigure numbers
 lazy_static! {
@@ -31,6 +32,7 @@ lazy_static! {
 static MAX_WORD_LENGTH: usize = 25;
 
 /// Options for lexical normalization on an individual word
+#[derive(Debug, Clone, Copy)]
 pub struct LexicalOptions {
   /// math will be entirely omitted when set
   pub discard_math: bool,
@@ -50,7 +52,7 @@ impl Default for LexicalOptions {
 }
 /// Normalization of word lexemes created for the "AMS paragraph classification" experiment
 /// operating on a DNMRange representation
-/// - numeric literals are replaced by NUM
+/// - numeric literals and quantities (see `tokenizer::quantity`) are replaced by NUM
 /// - citations become citationelement
 /// - math is replaced by its lexeme annotation (created by latexml), with a "mathformula" fallback
 /// - of the word is longer than the max length of 25, an error is returned
@@ -59,6 +61,10 @@ pub fn ams_normalize_word_range(
   context: &mut Context,
   options: LexicalOptions,
 ) -> Result<String, Box<dyn Error>> {
+  // quantity tokens, e.g. from `Tokenizer::words_punct_and_quantities`, may legitimately be long
+  if quantity::parse(range.get_plaintext()).is_some() {
+    return Ok(String::from("NUM"));
+  }
   let mut word_string = if options.discard_punct {
     range
       .get_plaintext()
@@ -124,6 +130,18 @@ pub fn heading_from_node_aux(
   Some(heading_text)
 }
 
+/// As `heading_from_node_aux`, normalized to a standard title in the language of its document,
+/// see `normalize_heading_title_in`
+pub fn heading_from_node_aux_in(
+  node: RoNode,
+  tokenizer: &Tokenizer,
+  context: &mut Context,
+  language: HeadingLanguage,
+) -> Option<String> {
+  heading_from_node_aux(node, tokenizer, context)
+    .map(|heading| normalize_heading_title_in(&heading, language))
+}
+
 /// Attempt to recover the "type" of a potentially specialized heading,
 /// e.g. "definition xiii a"->"definition"
 #[allow(clippy::cognitive_complexity)]
@@ -145,7 +163,7 @@ pub fn normalize_heading_title(heading: &str) -> String {
   } else {
     // Otherwise, just look for simple variations of known cases, or return as-is:
     match simple_heading.as_ref() {
-      // ignore non-English
+      // ignore non-English, which `normalize_heading_title_in` translates first
       "lemme" | "remarque" | "corollaire" | "dokazatelstvo" => "",
       // synonyms
       "hypothesis" | "hypotheses" => "conjecture",
@@ -237,6 +255,12 @@ pub fn normalize_heading_title(heading: &str) -> String {
   }
 }
 
+/// As `normalize_heading_title`, for a heading in a language, whose phrases are first translated
+/// to English by `heading_language::translate_heading`, e.g. "remarque 2" -> "remark"
+pub fn normalize_heading_title_in(heading: &str, language: HeadingLanguage) -> String {
+  normalize_heading_title(&heading_language::translate_heading(heading, language))
+}
+
 // Analysis is a can of worms... there are many more, and they seem to be varying from extremely
 // narrow to extremely broad discussions some are even false friends, such as method names
 // "principal component analysis"
@@ -283,3 +307,25 @@ pub fn invalid_for_english_latin(dnm: &DNM) -> bool {
     false
   }
 }
+
+/// Check if the given DNM is not in one of the languages with heading normalization tables (see
+/// `HeadingLanguage`), or not in the Latin or Cyrillic script
+pub fn invalid_for_heading_languages(dnm: &DNM) -> bool {
+  let detectable = heading_language::without_placeholders(&dnm.plaintext);
+  if let Some(info) = detect(detectable.trim()) {
+    !matches!(info.script(), Script::Latin | Script::Cyrillic)
+      || (HeadingLanguage::from_lang(info.lang()).is_none() && info.confidence() > 0.93)
+  } else {
+    false
+  }
+}
+
+/// Serialize a value as its `Display` string, e.g. an environment as its name
+#[cfg(feature = "json")]
+pub(crate) fn serialize_display<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+  T: std::fmt::Display,
+  S: serde::Serializer,
+{
+  serializer.collect_str(value)
+}
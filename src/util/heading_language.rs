@@ -0,0 +1,314 @@
+//! Language-specific normalization of headings, translating the heading phrases of French, German,
+//! Spanish, Italian, Portuguese and (transliterated) Russian documents to the English phrases of
+//! `data_helpers::normalize_heading_title`, so that they map to the same `StructuralEnv` and
+//! `AmsEnv` targets, e.g. "Remarque 2.1" to "remark 2 1" and "Доказательство" to "proof".
+//!
+//! The built-in table (`DEFAULT_TRANSLATIONS`) can be extended with rules loaded from a data file,
+//! in the same "<language> <phrase> <english>" format.
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use whatlang::{detect, Lang};
+
+use crate::dnm::DNM;
+
+/// The built-in translations, in the rule file format
+pub const DEFAULT_TRANSLATIONS: &str = include_str!("heading_translations.tsv");
+
+lazy_static! {
+  static ref DEFAULT_HEADING_TRANSLATIONS: HeadingTranslations = HeadingTranslations::default();
+}
+
+/// The languages with heading normalization tables
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum HeadingLanguage {
+  /// English, normalized by `data_helpers::normalize_heading_title` alone
+  #[default]
+  English,
+  /// French
+  French,
+  /// German
+  German,
+  /// Spanish
+  Spanish,
+  /// Italian
+  Italian,
+  /// Portuguese
+  Portuguese,
+  /// Russian, in Cyrillic or in transliteration
+  Russian,
+}
+
+impl HeadingLanguage {
+  /// the language of an ISO 639-1 code, e.g. "fr", if supported
+  pub fn from_code(code: &str) -> Option<Self> {
+    match code {
+      "en" => Some(HeadingLanguage::English),
+      "fr" => Some(HeadingLanguage::French),
+      "de" => Some(HeadingLanguage::German),
+      "es" => Some(HeadingLanguage::Spanish),
+      "it" => Some(HeadingLanguage::Italian),
+      "pt" => Some(HeadingLanguage::Portuguese),
+      "ru" => Some(HeadingLanguage::Russian),
+      _ => None,
+    }
+  }
+
+  /// the ISO 639-1 code of the language, e.g. "fr"
+  pub fn code(&self) -> &'static str {
+    match self {
+      HeadingLanguage::English => "en",
+      HeadingLanguage::French => "fr",
+      HeadingLanguage::German => "de",
+      HeadingLanguage::Spanish => "es",
+      HeadingLanguage::Italian => "it",
+      HeadingLanguage::Portuguese => "pt",
+      HeadingLanguage::Russian => "ru",
+    }
+  }
+
+  /// the language of a `whatlang` detection, if supported
+  pub fn from_lang(lang: Lang) -> Option<Self> {
+    match lang {
+      Lang::Eng => Some(HeadingLanguage::English),
+      Lang::Fra => Some(HeadingLanguage::French),
+      Lang::Deu => Some(HeadingLanguage::German),
+      Lang::Spa => Some(HeadingLanguage::Spanish),
+      Lang::Ita => Some(HeadingLanguage::Italian),
+      Lang::Por => Some(HeadingLanguage::Portuguese),
+      Lang::Rus => Some(HeadingLanguage::Russian),
+      _ => None,
+    }
+  }
+
+  /// Detect the language of a text, if reliably one of the supported languages. Headings are
+  /// too short for a reliable detection, which is best made on the paragraphs of a document
+  pub fn detect(text: &str) -> Option<Self> {
+    let info = detect(text)?;
+    if info.is_reliable() {
+      HeadingLanguage::from_lang(info.lang())
+    } else {
+      None
+    }
+  }
+
+  /// Detect the language of a DNM, without its math and citation placeholders
+  pub fn of_dnm(dnm: &DNM) -> Option<Self> {
+    HeadingLanguage::detect(without_placeholders(&dnm.plaintext).trim())
+  }
+}
+
+impl fmt::Display for HeadingLanguage {
+  fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result { fmt.write_str(self.code()) }
+}
+
+/// The heading translations of a language
+#[derive(Debug, Clone, Default)]
+struct LanguageTable {
+  /// the English phrases of the (folded) phrases of the language
+  phrases: HashMap<String, String>,
+  /// the (folded) lead phrases, dropped from the start of headings
+  leads: Vec<String>,
+  /// the largest number of words of a phrase
+  max_words: usize,
+}
+
+/// Translations of heading phrases to English, by language
+#[derive(Debug, Clone)]
+pub struct HeadingTranslations {
+  tables: HashMap<HeadingLanguage, LanguageTable>,
+}
+
+impl Default for HeadingTranslations {
+  fn default() -> HeadingTranslations {
+    let mut translations = HeadingTranslations::new();
+    translations
+      .add_rules(DEFAULT_TRANSLATIONS)
+      .expect("the built-in heading translations are valid");
+    translations
+  }
+}
+
+impl HeadingTranslations {
+  /// empty translations, without the built-in rules
+  pub fn new() -> Self {
+    HeadingTranslations {
+      tables: HashMap::new(),
+    }
+  }
+
+  /// add the rules of a file
+  pub fn load(&mut self, path: &str) -> Result<(), Box<dyn Error>> {
+    let rules = fs::read_to_string(path)?;
+    self.add_rules(&rules)
+  }
+
+  /// Add the rules of a rule file's contents. Fails on the first invalid rule, keeping the rules
+  /// before it
+  pub fn add_rules(&mut self, rules: &str) -> Result<(), Box<dyn Error>> {
+    for (index, line) in rules.lines().enumerate() {
+      let line = line.trim();
+      if line.is_empty() || line.starts_with('#') {
+        continue;
+      }
+      let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+      let (language, phrase, english) = match fields.as_slice() {
+        [language, phrase, english] => (*language, *phrase, *english),
+        _ => return Err(format!("line {}: not a 3-column rule: {:?}", index + 1, line).into()),
+      };
+      let language = match HeadingLanguage::from_code(language) {
+        Some(language) => language,
+        None => return Err(format!("line {}: unknown language {:?}", index + 1, language).into()),
+      };
+      if english == "-" {
+        self.add_lead_phrase(language, phrase);
+      } else {
+        self.add_phrase(language, phrase, english);
+      }
+    }
+    Ok(())
+  }
+
+  /// translate a phrase of a language to an English phrase, replacing its previous translation
+  pub fn add_phrase(&mut self, language: HeadingLanguage, phrase: &str, english: &str) {
+    let phrase = fold(phrase, language);
+    let table = self.tables.entry(language).or_default();
+    table.max_words = table.max_words.max(phrase.split(' ').count());
+    table.phrases.insert(phrase, english.to_lowercase());
+  }
+
+  /// drop a phrase of a language from the start of headings, e.g. the French article "la"
+  pub fn add_lead_phrase(&mut self, language: HeadingLanguage, phrase: &str) {
+    let phrase = fold(phrase, language);
+    let table = self.tables.entry(language).or_default();
+    if !table.leads.contains(&phrase) {
+      table.leads.push(phrase);
+    }
+  }
+
+  /// the number of translated phrases of a language
+  pub fn phrase_count(&self, language: HeadingLanguage) -> usize {
+    self
+      .tables
+      .get(&language)
+      .map(|table| table.phrases.len())
+      .unwrap_or(0)
+  }
+
+  /// Translate the leading or, failing that, trailing phrase of a heading to English, dropping its
+  /// lead phrases, e.g. "La démonstration du théorème 2" to "proof du theoreme 2" in French.
+  /// Headings without a known phrase are returned as they are
+  pub fn translate(&self, heading: &str, language: HeadingLanguage) -> String {
+    let table = match self.tables.get(&language) {
+      Some(table) if !table.phrases.is_empty() => table,
+      _ => return heading.to_string(),
+    };
+    let folded = fold(heading, language);
+    let mut words: Vec<&str> = folded.split(' ').filter(|word| !word.is_empty()).collect();
+    // drop the lead phrases, keeping at least one word
+    while let Some(lead) = table.leads.iter().find(|lead| {
+      let lead_words = lead.split(' ').count();
+      words.len() > lead_words && words[..lead_words].join(" ") == **lead
+    }) {
+      words.drain(..lead.split(' ').count());
+    }
+    let longest = table.max_words.min(words.len());
+    for count in (1..=longest).rev() {
+      if let Some(english) = table.phrases.get(&words[..count].join(" ")) {
+        let mut translated = vec![english.as_str()];
+        translated.extend(&words[count..]);
+        return translated.join(" ");
+      }
+    }
+    for count in (1..=longest).rev() {
+      if let Some(english) = table.phrases.get(&words[words.len() - count..].join(" ")) {
+        let mut translated = words[..words.len() - count].to_vec();
+        translated.push(english);
+        return translated.join(" ");
+      }
+    }
+    heading.to_string()
+  }
+}
+
+/// a DNM plaintext without its math, citation and reference placeholders, for language detection
+pub(crate) fn without_placeholders(plaintext: &str) -> String {
+  plaintext
+    .replace("mathformula", " ")
+    .replace("CitationElement", " ")
+    .replace("REF", " ")
+}
+
+/// translate a heading with the built-in `HeadingTranslations`
+pub fn translate_heading(heading: &str, language: HeadingLanguage) -> String {
+  DEFAULT_HEADING_TRANSLATIONS.translate(heading, language)
+}
+
+/// Lowercase a phrase, without its accents and punctuation, and with single spaces between words.
+/// Russian is transliterated to Latin letters, e.g. "Доказательство" to "dokazatelstvo"
+fn fold(phrase: &str, language: HeadingLanguage) -> String {
+  let mut folded = String::with_capacity(phrase.len());
+  for c in phrase.to_lowercase().chars() {
+    let latin = match c {
+      'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => "a",
+      'æ' => "ae",
+      'ç' => "c",
+      'è' | 'é' | 'ê' | 'ë' => "e",
+      'ì' | 'í' | 'î' | 'ï' => "i",
+      'ñ' => "n",
+      'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' => "o",
+      'œ' => "oe",
+      'ù' | 'ú' | 'û' | 'ü' => "u",
+      'ý' | 'ÿ' => "y",
+      'ß' => "ss",
+      c if language == HeadingLanguage::Russian && (('а'..='я').contains(&c) || c == 'ё') => {
+        transliterate(c)
+      },
+      c if c.is_alphanumeric() => {
+        folded.push(c);
+        continue;
+      },
+      _ => " ",
+    };
+    folded.push_str(latin);
+  }
+  folded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// the transliteration of a lowercase Cyrillic letter
+fn transliterate(c: char) -> &'static str {
+  match c {
+    'а' => "a",
+    'б' => "b",
+    'в' => "v",
+    'г' => "g",
+    'д' => "d",
+    'е' | 'ё' | 'э' => "e",
+    'ж' => "zh",
+    'з' => "z",
+    'и' | 'й' => "i",
+    'к' => "k",
+    'л' => "l",
+    'м' => "m",
+    'н' => "n",
+    'о' => "o",
+    'п' => "p",
+    'р' => "r",
+    'с' => "s",
+    'т' => "t",
+    'у' => "u",
+    'ф' => "f",
+    'х' => "kh",
+    'ц' => "ts",
+    'ч' => "ch",
+    'ш' => "sh",
+    'щ' => "shch",
+    'ы' => "y",
+    'ю' => "yu",
+    'я' => "ya",
+    // the hard and soft signs
+    _ => "",
+  }
+}
@@ -1,5 +1,6 @@
 //! Various useful code snippets
 pub mod data_helpers;
+pub mod heading_language;
 pub mod path_helpers;
 pub mod plot;
 pub mod test;
@@ -1,4 +1,5 @@
 //! Test utilities for llamapun's crate
+use crate::dnm::{DNMParameters, DNM};
 use lazy_static::lazy_static;
 use walkdir::WalkDir;
 
@@ -27,3 +28,27 @@ lazy_static! { // preload a list of the resources we have for testing, for easy
       .to_string())
     .collect();
 }
+
+/// a DNM of a plain ASCII text, without a DOM, for tokenizer tests
+pub fn ascii_dnm(text: &str) -> DNM {
+  DNM {
+    plaintext: text.to_string(),
+    byte_offsets: (0usize..=text.len()).collect(), // it's just ascii
+    parameters: DNMParameters::llamapun_normalization(),
+    ..DNM::default()
+  }
+}
+
+/// a DNM of a plain UTF-8 text, without a DOM, with the byte offset of every character
+pub fn utf8_dnm(text: &str) -> DNM {
+  DNM {
+    plaintext: text.to_string(),
+    byte_offsets: text
+      .char_indices()
+      .map(|(offset, _)| offset)
+      .chain(std::iter::once(text.len()))
+      .collect(),
+    parameters: DNMParameters::llamapun_normalization(),
+    ..DNM::default()
+  }
+}
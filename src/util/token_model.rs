@@ -2,6 +2,7 @@
 use crate::dnm;
 use crate::dnm::SpecialTagsOption;
 use crate::parallel_data::*;
+use crate::tokenizer::quantity;
 use libxml::xpath::Context;
 use regex::Regex;
 use std::collections::HashMap;
@@ -68,10 +69,11 @@ pub fn extract(
       paragraph_count += 1;
       let mut paragraph_buffer = String::new();
       let mut invalid_paragraph = false;
-      'words: for word in paragraph.word_and_punct_iter() {
+      'words: for word in paragraph.word_punct_and_quantity_iter() {
         if !word.range.is_empty() {
           let word_string = word.range.get_plaintext().to_lowercase();
-          if word_string.len() > MAX_WORD_LENGTH {
+          let is_quantity = quantity::parse(word.range.get_plaintext()).is_some();
+          if word_string.len() > MAX_WORD_LENGTH && !is_quantity {
             // Using a more aggressive normalization, large words tend to be conversion
             // errors with lost whitespace - drop the entire paragraph when this occurs.
             overflow_count += 1;
@@ -94,7 +96,7 @@ pub fn extract(
           } else if word_string.contains("citationelement") {
             word_str = "citationelement";
             citation_count += 1;
-          } else if is_numeric.is_match(&word_string) {
+          } else if is_quantity || is_numeric.is_match(&word_string) {
             num_count += 1;
             word_str = "NUM";
           } else {
@@ -0,0 +1,297 @@
+//! A word vocabulary with frequencies and document frequencies, for preparing the inputs of
+//! embedding and language model toolkits.
+//!
+//! Words map to dense ids, starting with the reserved special tokens, which are never pruned:
+//! the unknown word, and the normalized `NUM`, `citationelement` and `mathformula` tokens of
+//! `util::data_helpers::ams_normalize_word_range`. Pruned words are counted as the unknown word,
+//! which always has id 0 (`UNKNOWN_ID`).
+//!
+//! The word per line formats of word2vec, GloVe and fastText can not represent empty words, nor
+//! words with whitespace, such as the quantities of `Tokenizer::words_punct_and_quantities` (e.g.
+//! "3.5 km/s"), which are left out of their exports.
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// The unknown word, standing in for pruned and unseen words
+pub const UNKNOWN: &str = "<unk>";
+/// The id of the unknown word, the first reserved token
+pub const UNKNOWN_ID: usize = 0;
+/// The default reserved tokens, in id order
+pub const SPECIAL_TOKENS: [&str; 4] = [UNKNOWN, "NUM", "citationelement", "mathformula"];
+
+/// The vocabulary file formats of common toolkits
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VocabularyFormat {
+  /// word2vec's `-save-vocab`/`-read-vocab` files: a "word count" line per word
+  Word2Vec,
+  /// GloVe's `vocab_count` output: a "word count" line per word, leaving out the unknown word,
+  /// which GloVe reserves for itself
+  Glove,
+  /// fastText's `dump dict` output: the number of words, then a "word count word" line per word
+  FastText,
+  /// a HuggingFace `WordLevel` `vocab.json`, mapping every word to its id, with the `json` feature
+  HuggingFaceJson,
+  /// a HuggingFace (e.g. BERT) `vocab.txt`, with the word of id `i` on line `i`, which fails for
+  /// words with line breaks
+  HuggingFaceText,
+}
+
+/// A vocabulary of words with dense ids, frequencies and document frequencies
+#[derive(Debug, Clone)]
+pub struct Vocabulary {
+  /// the number of reserved tokens, which take the first ids
+  special_count: usize,
+  /// the word of every id
+  words: Vec<String>,
+  /// the id of every word
+  ids: HashMap<String, usize>,
+  /// the frequency of every id
+  counts: Vec<usize>,
+  /// the number of documents containing every id
+  document_frequencies: Vec<usize>,
+  /// the number of documents added
+  document_count: usize,
+}
+
+impl Default for Vocabulary {
+  fn default() -> Vocabulary { Vocabulary::with_special_tokens(&SPECIAL_TOKENS) }
+}
+
+impl Vocabulary {
+  /// an empty vocabulary with the default `SPECIAL_TOKENS`
+  pub fn new() -> Self { Vocabulary::default() }
+
+  /// an empty vocabulary with custom reserved tokens, the first of which stands in for pruned and
+  /// unseen words, e.g. `[UNK]`. Without any, `UNKNOWN` is reserved, so that there is always an
+  /// unknown word
+  pub fn with_special_tokens<S: AsRef<str>>(special_tokens: &[S]) -> Self {
+    let mut vocabulary = Vocabulary {
+      special_count: 0,
+      words: Vec::new(),
+      ids: HashMap::new(),
+      counts: Vec::new(),
+      document_frequencies: Vec::new(),
+      document_count: 0,
+    };
+    for token in special_tokens {
+      vocabulary.insert(token.as_ref());
+    }
+    if vocabulary.words.is_empty() {
+      vocabulary.insert(UNKNOWN);
+    }
+    vocabulary.special_count = vocabulary.words.len();
+    vocabulary
+  }
+
+  /// the id of a word, adding it to the vocabulary if it is new
+  fn insert(&mut self, word: &str) -> usize {
+    match self.ids.get(word) {
+      Some(id) => *id,
+      None => self.push(word.to_string(), 0, 0),
+    }
+  }
+
+  /// count an occurrence of a word, returning its id
+  pub fn add_word(&mut self, word: &str) -> usize { self.add_count(word, 1) }
+
+  /// count several occurrences of a word, e.g. from precomputed counts, returning its id
+  pub fn add_count(&mut self, word: &str, count: usize) -> usize {
+    let id = self.insert(word);
+    self.counts[id] += count;
+    id
+  }
+
+  /// count the words of a document, and each distinct word once towards its document frequency
+  pub fn add_document<'w, I>(&mut self, words: I)
+  where I: IntoIterator<Item = &'w str> {
+    let mut seen = HashSet::new();
+    for word in words {
+      let id = self.add_word(word);
+      if seen.insert(id) {
+        self.document_frequencies[id] += 1;
+      }
+    }
+    self.document_count += 1;
+  }
+
+  /// add the words and counts of another vocabulary, e.g. one filled by another thread, keeping
+  /// the reserved tokens and ids of `self`
+  pub fn merge(&mut self, other: Vocabulary) {
+    for (id, word) in other.words.iter().enumerate() {
+      let own_id = self.insert(word);
+      self.counts[own_id] += other.counts[id];
+      self.document_frequencies[own_id] += other.document_frequencies[id];
+    }
+    self.document_count += other.document_count;
+  }
+
+  /// the id of a word
+  pub fn id(&self, word: &str) -> Option<usize> { self.ids.get(word).copied() }
+
+  /// the id of a word, or of the unknown word if it is not in the vocabulary
+  pub fn id_or_unknown(&self, word: &str) -> usize { self.id(word).unwrap_or(UNKNOWN_ID) }
+
+  /// the word of an id
+  pub fn word(&self, id: usize) -> Option<&str> { self.words.get(id).map(String::as_str) }
+
+  /// the frequency of a word
+  pub fn count(&self, word: &str) -> usize { self.id(word).map(|id| self.counts[id]).unwrap_or(0) }
+
+  /// the number of documents containing a word
+  pub fn document_frequency(&self, word: &str) -> usize {
+    self
+      .id(word)
+      .map(|id| self.document_frequencies[id])
+      .unwrap_or(0)
+  }
+
+  /// the number of documents added
+  pub fn document_count(&self) -> usize { self.document_count }
+
+  /// the total frequency of all words
+  pub fn total_count(&self) -> usize { self.counts.iter().sum() }
+
+  /// checks if a word is a reserved token
+  pub fn is_special(&self, word: &str) -> bool {
+    self
+      .id(word)
+      .map(|id| id < self.special_count)
+      .unwrap_or(false)
+  }
+
+  /// the number of words, including the reserved tokens
+  pub fn len(&self) -> usize { self.words.len() }
+
+  /// checks if the vocabulary has no words, not even reserved tokens
+  pub fn is_empty(&self) -> bool { self.words.is_empty() }
+
+  /// the ids, words and frequencies of the vocabulary, in id order
+  pub fn iter(&self) -> impl Iterator<Item = (usize, &str, usize)> {
+    self
+      .words
+      .iter()
+      .enumerate()
+      .map(move |(id, word)| (id, word.as_str(), self.counts[id]))
+  }
+
+  /// Remove the words seen fewer than `min_count` times, or in more than `max_df` of the
+  /// documents (as a ratio), counting them as the unknown word, then renumber the remaining words
+  /// by descending frequency, after the reserved tokens
+  pub fn prune(&mut self, min_count: usize, max_df: f64) {
+    let max_documents = max_df * self.document_count as f64;
+    let document_count = self.document_count;
+    self.retain(|_, count, document_frequency| {
+      count >= min_count && (document_count == 0 || document_frequency as f64 <= max_documents)
+    });
+  }
+
+  /// Keep at most `max_size` words besides the reserved tokens, the most frequent ones, counting
+  /// the rest as the unknown word
+  pub fn truncate(&mut self, max_size: usize) {
+    self.retain(|_, _, _| true);
+    let special_count = self.special_count;
+    self.retain(|id, _, _| id < special_count + max_size);
+  }
+
+  /// keep the reserved tokens and the words for which `keep(id, count, document_frequency)` holds,
+  /// renumbering them by descending frequency, and counting the others as the first reserved token
+  fn retain<F>(&mut self, keep: F)
+  where F: Fn(usize, usize, usize) -> bool {
+    let words = std::mem::take(&mut self.words);
+    let counts = std::mem::take(&mut self.counts);
+    let document_frequencies = std::mem::take(&mut self.document_frequencies);
+    self.ids.clear();
+    let mut kept = Vec::new();
+    let mut pruned_count = 0;
+    for (id, word) in words.into_iter().enumerate() {
+      let (count, document_frequency) = (counts[id], document_frequencies[id]);
+      if id < self.special_count {
+        self.push(word, count, document_frequency);
+      } else if keep(id, count, document_frequency) {
+        kept.push((word, count, document_frequency));
+      } else {
+        pruned_count += count;
+      }
+    }
+    kept.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    for (word, count, document_frequency) in kept {
+      self.push(word, count, document_frequency);
+    }
+    self.counts[UNKNOWN_ID] += pruned_count;
+  }
+
+  /// add a word with the next id
+  fn push(&mut self, word: String, count: usize, document_frequency: usize) -> usize {
+    let id = self.words.len();
+    self.ids.insert(word.clone(), id);
+    self.words.push(word);
+    self.counts.push(count);
+    self.document_frequencies.push(document_frequency);
+    id
+  }
+
+  /// Write the vocabulary in a toolkit's format
+  pub fn write<W: Write>(
+    &self,
+    writer: &mut W,
+    format: VocabularyFormat,
+  ) -> Result<(), Box<dyn Error>> {
+    match format {
+      VocabularyFormat::Word2Vec | VocabularyFormat::Glove => {
+        for (id, word, count) in self.iter() {
+          if (format == VocabularyFormat::Glove && id == UNKNOWN_ID) || !is_line_word(word) {
+            continue;
+          }
+          writeln!(writer, "{word} {count}")?;
+        }
+      },
+      VocabularyFormat::FastText => {
+        let words: Vec<(usize, &str, usize)> = self
+          .iter()
+          .filter(|(_, word, _)| is_line_word(word))
+          .collect();
+        writeln!(writer, "{}", words.len())?;
+        for (_, word, count) in words {
+          writeln!(writer, "{word} {count} word")?;
+        }
+      },
+      #[cfg(feature = "json")]
+      VocabularyFormat::HuggingFaceJson => {
+        // a map in id order, unlike a `serde_json::Map`
+        let mut serializer = serde_json::Serializer::pretty(&mut *writer);
+        serde::Serializer::collect_map(
+          &mut serializer,
+          self.iter().map(|(id, word, _)| (word, id)),
+        )?;
+        writeln!(writer)?;
+      },
+      #[cfg(not(feature = "json"))]
+      VocabularyFormat::HuggingFaceJson => {
+        return Err("HuggingFace JSON vocabularies require the json feature".into());
+      },
+      VocabularyFormat::HuggingFaceText => {
+        for (id, word, _) in self.iter() {
+          if word.contains(['\n', '\r']) {
+            return Err(format!("the word of id {id} has a line break: {word:?}").into());
+          }
+          writeln!(writer, "{word}")?;
+        }
+      },
+    }
+    Ok(())
+  }
+
+  /// Save the vocabulary to a file, in a toolkit's format
+  pub fn save(&self, path: &str, format: VocabularyFormat) -> Result<(), Box<dyn Error>> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    self.write(&mut writer, format)?;
+    writer.flush()?;
+    Ok(())
+  }
+}
+
+/// checks if a word can be written in a word per line format, i.e. is not empty and has no
+/// whitespace
+fn is_line_word(word: &str) -> bool { !word.is_empty() && !word.contains(char::is_whitespace) }
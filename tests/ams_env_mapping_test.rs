@@ -0,0 +1,90 @@
+extern crate libxml;
+extern crate llamapun;
+
+use libxml::parser::Parser;
+use llamapun::ams::env_mapping::EnvMapping;
+use llamapun::ams::{self, AmsEnv};
+use std::collections::HashMap;
+
+#[test]
+fn default_mapping_keeps_builtin_aliases() {
+  let mapping = EnvMapping::default();
+  assert_eq!(mapping.normalize("lemma"), AmsEnv::Lemma);
+  assert_eq!(mapping.normalize("thm"), AmsEnv::Theorem);
+  assert_eq!(mapping.normalize("hypothesis"), AmsEnv::Conjecture);
+  assert_eq!(mapping.label("defn"), Some("definition"));
+  assert_eq!(mapping.label("notanenvironment"), None);
+  assert_eq!(mapping.normalize("notanenvironment"), AmsEnv::Other);
+  assert_eq!(ams::normalize_env("acks"), AmsEnv::Acknowledgement);
+  assert_eq!(
+    ams::class_to_env("ltx_theorem ltx_theorem_cor"),
+    Some(AmsEnv::Corollary)
+  );
+  assert_eq!(ams::class_to_env("ltx_theorem"), Some(AmsEnv::Theorem));
+  assert_eq!(ams::class_to_env("ltx_proof"), Some(AmsEnv::Proof));
+  assert_eq!(ams::class_to_env("ltx_para"), None);
+}
+
+#[test]
+fn can_add_prefix_regex_and_custom_rules() {
+  let mut mapping = EnvMapping::default();
+  mapping
+    .add_rules(
+      "# local aliases\n\
+       lemma\tremark\n\
+       hauptsatz*  theorem\n\
+       hauptsatzlemma* lemma\n\
+       /^alg(orithm)?[0-9]+$/\talgorithm\n\
+       protocol\tprotocol\n",
+    )
+    .unwrap();
+  // exact rules override the built-in ones
+  assert_eq!(mapping.normalize("lemma"), AmsEnv::Remark);
+  // the longest prefix applies
+  assert_eq!(mapping.normalize("hauptsatz2"), AmsEnv::Theorem);
+  assert_eq!(mapping.normalize("hauptsatzlemmab"), AmsEnv::Lemma);
+  assert_eq!(mapping.normalize("algorithm17"), AmsEnv::Algorithm);
+  // exact aliases take precedence over patterns
+  assert_eq!(mapping.normalize("algorithm1"), AmsEnv::Algorithm);
+  assert_eq!(mapping.label("alg12"), Some("algorithm"));
+  // custom labels are kept, beyond the fixed `AmsEnv` variants
+  assert_eq!(mapping.label("protocol"), Some("protocol"));
+  assert_eq!(mapping.normalize("protocol"), AmsEnv::Other);
+  assert_eq!(
+    mapping.class_label("ltx_theorem ltx_theorem_protocol"),
+    Some("protocol")
+  );
+  assert_eq!(
+    mapping.class_label("ltx_theorem ltx_theorem_unknownenv"),
+    Some("other")
+  );
+
+  let mut invalid = EnvMapping::new();
+  assert!(invalid.add_rules("lonelyalias").is_err());
+  assert!(invalid.add_rules("/(unclosed/ theorem").is_err());
+  assert!(invalid.is_empty());
+}
+
+#[test]
+fn can_count_unmapped_environments() {
+  let mut mapping = EnvMapping::new();
+  mapping
+    .add_rules("theorem theorem\ndef* definition\n/^(lemma|corollary)$/ lemma")
+    .unwrap();
+  let parser = Parser::default();
+  let doc = parser
+    .parse_file("tests/resources/1311.0066.xhtml")
+    .unwrap();
+  let mut unmapped: HashMap<String, usize> = HashMap::new();
+  mapping.count_unmapped(&doc, &mut unmapped);
+  let mut expected = HashMap::new();
+  expected.insert("example".to_string(), 3);
+  expected.insert("hypothesis".to_string(), 1);
+  expected.insert("question".to_string(), 2);
+  expected.insert("remark".to_string(), 2);
+  assert_eq!(unmapped, expected);
+
+  let mut unmapped: HashMap<String, usize> = HashMap::new();
+  EnvMapping::default().count_unmapped(&doc, &mut unmapped);
+  assert!(unmapped.is_empty());
+}
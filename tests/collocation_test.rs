@@ -0,0 +1,112 @@
+extern crate llamapun;
+use llamapun::ngrams::collocation::{self, Collocations, Measure};
+use llamapun::stopwords;
+use senna::pos::POS;
+
+fn add_text(collocations: &mut Collocations, text: &str) {
+  for sentence in text.split(" . ") {
+    let words: Vec<&str> = sentence.split_whitespace().collect();
+    collocations.add_words(&words);
+  }
+}
+
+#[test]
+fn can_rank_by_association() {
+  let mut collocations = Collocations::new(2, 2);
+  add_text(
+    &mut collocations,
+    "the hilbert space is complete . a hilbert space has a norm . the norm is a map . the space \
+     of maps is a space . we take a hilbert space . the map is the norm",
+  );
+  for measure in [Measure::Pmi, Measure::TScore, Measure::LogLikelihood] {
+    let ranked = collocations.rank(measure);
+    assert_eq!(
+      ranked[0].phrase, "hilbert space",
+      "top phrase by {:?}",
+      measure
+    );
+    assert_eq!(ranked[0].count, 3);
+    assert!(ranked.iter().all(|c| c.count >= 2), "min_count applies");
+    assert!(ranked.windows(2).all(|pair| pair[0].score >= pair[1].score));
+  }
+  // "hilbert" always precedes "space": log2(N * 3 / (3 * 5)), with N = 33 words
+  let pmi = collocations.rank(Measure::Pmi);
+  assert!((pmi[0].score - (33.0f64 / 5.0).log2()).abs() < 1e-9);
+}
+
+#[test]
+fn can_rank_nested_terms_by_c_value() {
+  let mut collocations = Collocations::new(2, 3);
+  for _ in 0..5 {
+    add_text(&mut collocations, "a floating point number");
+  }
+  add_text(
+    &mut collocations,
+    "one floating point . another floating point",
+  );
+  let c_values = collocations.rank(Measure::CValue);
+  let score = |phrase: &str| {
+    c_values
+      .iter()
+      .find(|c| c.phrase == phrase)
+      .map(|c| c.score)
+      .unwrap()
+  };
+  // seen 7 times, but 5 of them nested in the single longer candidate
+  assert_eq!(score("floating point"), 2.0);
+  assert!((score("floating point number") - 3f64.log2() * 5.0).abs() < 1e-9);
+  assert!((score("a floating point") - 3f64.log2() * 5.0).abs() < 1e-9);
+  assert_eq!(score("point number"), 0.0);
+
+  // "a" is a context word of the top terms, and of "floating point number"
+  let nc_values = collocations.rank(Measure::NcValue);
+  let nc_score = nc_values
+    .iter()
+    .find(|c| c.phrase == "floating point number")
+    .unwrap()
+    .score;
+  assert!(nc_score > 0.8 * 3f64.log2() * 5.0);
+}
+
+#[test]
+fn can_filter_candidates() {
+  let mut collocations = Collocations::new(2, 3);
+  collocations.stopwords = Some(stopwords::english());
+  add_text(
+    &mut collocations,
+    "the ring of integers is a ring . the ring of integers is the ring",
+  );
+  let phrases: Vec<String> = collocations
+    .rank(Measure::Pmi)
+    .into_iter()
+    .map(|c| c.phrase)
+    .collect();
+  assert_eq!(phrases, vec!["ring of integers"]);
+
+  let mut tagged = Collocations::new(2, 3);
+  for _ in 0..2 {
+    tagged.add_tagged_words(&[
+      ("every", POS::DT),
+      ("compact", POS::JJ),
+      ("set", POS::NN),
+      ("is", POS::VBZ),
+      ("closed", POS::JJ),
+    ]);
+  }
+  let mut other = Collocations::new(2, 3);
+  other.add_words(&["a", "set", "is", "closed"]);
+  tagged.merge(other);
+  let phrases: Vec<String> = tagged
+    .rank(Measure::LogLikelihood)
+    .into_iter()
+    .map(|c| c.phrase)
+    .collect();
+  assert_eq!(phrases, vec!["compact set"]);
+  assert_eq!(tagged.counts.get("set is closed"), 3);
+
+  assert!(collocation::is_term_pattern("NPN"));
+  assert!(collocation::is_term_pattern("AAN"));
+  assert!(!collocation::is_term_pattern("NA"));
+  assert!(!collocation::is_term_pattern("NPNPN"));
+  assert_eq!(collocation::coarse_tag(POS::NNS), 'N');
+}
@@ -0,0 +1,77 @@
+extern crate llamapun;
+use llamapun::cooccurrence::{self, Cooccurrences, Weighting};
+use llamapun::vocabulary::{Vocabulary, VocabularyFormat};
+use std::sync::Arc;
+
+fn vocabulary(text: &str) -> Arc<Vocabulary> {
+  let mut vocabulary = Vocabulary::new();
+  vocabulary.add_document(text.split(' '));
+  Arc::new(vocabulary)
+}
+
+#[test]
+fn can_count_windowed_cooccurrences() {
+  let mut cooccurrences = Cooccurrences::new(vocabulary("let the group act on the set"));
+  cooccurrences.window_size = 2;
+  cooccurrences.add_words(&["let", "the", "unseen", "group", "act"]);
+  // out-of-vocabulary words are skipped before windowing
+  assert_eq!(cooccurrences.get("the", "group"), 1.0);
+  assert_eq!(cooccurrences.get("group", "the"), 1.0);
+  assert_eq!(cooccurrences.get("let", "group"), 0.5);
+  assert_eq!(
+    cooccurrences.get("let", "act"),
+    0.0,
+    "outside of the window"
+  );
+  assert_eq!(cooccurrences.len(), 10);
+
+  let mut uniform = Cooccurrences::new(vocabulary("let the group act on the set"));
+  uniform.weighting = Weighting::Uniform;
+  uniform.symmetric = false;
+  uniform.add_words(&["the", "group", "act", "on", "the", "set"]);
+  let mut other = uniform.empty_clone();
+  other.add_words(&["the", "set"]);
+  uniform.merge(other);
+  // from both "the" of the first sequence, and from the second one
+  assert_eq!(uniform.get("the", "set"), 3.0);
+  assert_eq!(uniform.get("set", "the"), 0.0);
+  assert_eq!(uniform.get("the", "the"), 1.0);
+}
+
+#[test]
+fn can_collapse_math() {
+  let mut cooccurrences = Cooccurrences::new(vocabulary("let be a group"));
+  cooccurrences.collapse_math = true;
+  cooccurrences.add_words(&["let", "ID_G", "OPERATOR_end", "ID_H", "be", "a", "NUM"]);
+  assert_eq!(cooccurrences.get("let", "mathformula"), 1.0);
+  assert_eq!(cooccurrences.get("mathformula", "be"), 1.0);
+  assert!((cooccurrences.get("mathformula", "NUM") - 1.0 / 3.0).abs() < 1e-12);
+  assert!(cooccurrence::is_math_lexeme("ARRAY"));
+  assert!(!cooccurrence::is_math_lexeme("NUM"));
+  assert!(!cooccurrence::is_math_lexeme("group"));
+}
+
+#[test]
+fn can_write_glove_cooccurrences() {
+  let vocabulary = vocabulary("the group");
+  let mut glove_vocabulary = Vec::new();
+  vocabulary
+    .write(&mut glove_vocabulary, VocabularyFormat::Glove)
+    .unwrap();
+  assert_eq!(
+    String::from_utf8(glove_vocabulary).unwrap(),
+    "NUM 0\ncitationelement 0\nmathformula 0\nthe 1\ngroup 1\n"
+  );
+  let mut cooccurrences = Cooccurrences::new(vocabulary);
+  cooccurrences.add_words(&["the", "group"]);
+  let mut binary = Vec::new();
+  cooccurrences.write_glove(&mut binary).unwrap();
+  // (the, group) then (group, the), with the 1-based ids of the GloVe vocabulary lines
+  let mut expected = Vec::new();
+  for (first, second) in [(4i32, 5i32), (5, 4)] {
+    expected.extend_from_slice(&first.to_le_bytes());
+    expected.extend_from_slice(&second.to_le_bytes());
+    expected.extend_from_slice(&1.0f64.to_le_bytes());
+  }
+  assert_eq!(binary, expected);
+}
@@ -0,0 +1,118 @@
+extern crate llamapun;
+use llamapun::ngrams::counting::{CountMinSketch, Counter, SpaceSaving, SpillCounter};
+use llamapun::ngrams::Ngrams;
+use std::collections::HashMap;
+
+/// A skewed stream of 1,000 bigrams: "w0 w1" occurs 200 times, then fewer and fewer
+fn skewed_words() -> Vec<String> {
+  let mut words = Vec::new();
+  for rank in 0..40usize {
+    for _ in 0..(200 / (rank + 1)) {
+      words.push(format!("w{}", 2 * rank));
+      words.push(format!("w{}", 2 * rank + 1));
+    }
+  }
+  words
+}
+
+fn count_bigrams<C: Counter>(counter: &mut C) {
+  let bigrams = Ngrams {
+    n: 2,
+    ..Ngrams::default()
+  };
+  let words = skewed_words();
+  let words: Vec<&str> = words.iter().map(String::as_str).collect();
+  for pair in words.chunks(2) {
+    bigrams.count_words(pair, counter);
+  }
+}
+
+#[test]
+fn approximate_counts_bound_exact_counts() {
+  let mut exact = HashMap::new();
+  count_bigrams(&mut exact);
+  let mut sketch = CountMinSketch::with_error(0.01, 0.01);
+  count_bigrams(&mut sketch);
+  let total: usize = exact.values().sum();
+  assert_eq!(sketch.total(), total);
+  for (phrase, count) in &exact {
+    let estimate = Counter::get(&sketch, phrase);
+    assert!(estimate >= *count, "{} underestimated", phrase);
+    assert!(estimate <= count + total / 100, "{} overestimated", phrase);
+  }
+  assert_eq!(sketch.top(3), exact.top(3));
+  assert_eq!(
+    exact.top(2),
+    vec![("w0 w1".to_string(), 200), ("w2 w3".to_string(), 100)]
+  );
+
+  let mut space_saving = SpaceSaving::new(10);
+  count_bigrams(&mut space_saving);
+  for (phrase, count) in space_saving.top(10) {
+    let error = space_saving.error(&phrase).unwrap();
+    assert!(count >= exact[&phrase] && count - error <= exact[&phrase]);
+  }
+  // every phrase seen more than total / capacity times is monitored, even in this worst-case
+  // order, where the rarest phrases come last
+  let top: Vec<String> = space_saving.top(2).into_iter().map(|(p, _)| p).collect();
+  assert_eq!(top, vec!["w0 w1", "w2 w3"]);
+  assert!(space_saving.min_count() > 0);
+}
+
+#[test]
+fn can_merge_counters() {
+  let mut halves = (SpaceSaving::new(20), SpaceSaving::new(20));
+  let mut sketches = (CountMinSketch::new(512, 4), CountMinSketch::new(512, 4));
+  let mut exact = HashMap::new();
+  count_bigrams(&mut exact);
+  for (index, (phrase, count)) in exact.iter().enumerate() {
+    if index % 2 == 0 {
+      halves.0.add(phrase, *count);
+      sketches.0.add(phrase, *count);
+    } else {
+      halves.1.add(phrase, *count);
+      sketches.1.add(phrase, *count);
+    }
+  }
+  halves.0.merge(halves.1);
+  sketches.0.merge(sketches.1);
+  for (phrase, count) in halves.0.top(20) {
+    assert!(count >= exact[&phrase]);
+  }
+  // phrases missing from one half are counted with that half's lowest count
+  let top: Vec<String> = halves.0.top(2).into_iter().map(|(p, _)| p).collect();
+  assert_eq!(top, vec!["w0 w1", "w2 w3"]);
+  assert_eq!(sketches.0.top(5), exact.top(5));
+}
+
+#[test]
+fn can_spill_counts_to_disk() {
+  let directory = std::env::temp_dir().join(format!("llamapun-spill-test-{}", std::process::id()));
+  let mut exact = HashMap::new();
+  count_bigrams(&mut exact);
+  let mut spilled = SpillCounter::new(&directory, 8);
+  count_bigrams(&mut spilled);
+  assert!(spilled.run_count() > 1);
+
+  let mut other = SpillCounter::new(&directory, 8);
+  other.add("w0 w1", 5);
+  other.add("unseen phrase", 1);
+  spilled.merge(other);
+  exact.add("w0 w1", 5);
+  exact.add("unseen phrase", 1);
+
+  let mut sorted: Vec<(String, usize)> = exact.clone().into_iter().collect();
+  sorted.sort();
+  assert_eq!(spilled.sorted_counts().unwrap().collect::<Vec<_>>(), sorted);
+  assert_eq!(spilled.get("w0 w1"), 205);
+  assert_eq!(spilled.get("w10 w11"), exact["w10 w11"]);
+  assert_eq!(spilled.get("missing"), 0);
+  assert_eq!(spilled.top(4), exact.top(4));
+
+  spilled.compact().unwrap();
+  assert_eq!(spilled.run_count(), 1);
+  assert_eq!(spilled.top(4), exact.top(4));
+  drop(spilled);
+  assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 0);
+  std::fs::remove_dir(&directory).unwrap();
+}
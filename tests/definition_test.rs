@@ -0,0 +1,97 @@
+use llamapun::parallel_data::definition::{self, TermEvidence};
+use llamapun::parallel_data::*;
+
+#[test]
+fn can_extract_glossary() {
+  let corpus = Corpus::new(".".to_string());
+  let document = Document::new("tests/fixtures/definitions.html".to_string(), &corpus).unwrap();
+
+  let definition = document
+    .statement_iter(StatementOptions::default())
+    .nth(1)
+    .unwrap();
+  let candidates = definition::extract_terms(&definition.dnm, &corpus.tokenizer);
+  let terms: Vec<(&str, TermEvidence, bool)> = candidates
+    .iter()
+    .map(|candidate| {
+      (
+        candidate.term.as_str(),
+        candidate.evidence,
+        candidate.math.is_some(),
+      )
+    })
+    .collect();
+  assert_eq!(
+    terms,
+    vec![
+      ("simple", TermEvidence::Copula, false),
+      ("Z(G)", TermEvidence::MathDeclaration, true),
+      ("f(x)", TermEvidence::MathDeclaration, true),
+    ]
+  );
+  assert_eq!(
+    candidates[0].definiens_text(),
+    "A group without proper normal subgroups"
+  );
+  assert_eq!(candidates[1].definiens_text(), "center of the group.");
+
+  let glossary = document.glossary();
+  let entries: Vec<(&str, Option<&str>)> = glossary
+    .entries
+    .iter()
+    .map(|entry| (entry.term.as_str(), entry.source_id.as_deref()))
+    .collect();
+  assert_eq!(
+    entries,
+    vec![
+      ("abelian", Some("S1.Thmtheorem1")),
+      ("simple", Some("S1.Thmtheorem2")),
+      ("Z(G)", Some("S1.Thmtheorem2")),
+      ("f(x)", Some("S1.Thmtheorem2")),
+      ("perfect", Some("S1.p1")),
+      ("reduced", Some("S2.p1")),
+    ]
+  );
+  assert_eq!(
+    glossary.entries[0].definiens,
+    "if any two of its elements commute."
+  );
+  #[cfg(feature = "json")]
+  {
+    let mut lines = Vec::new();
+    glossary.write_json_lines(&mut lines).unwrap();
+    let first_line = lines.split(|byte| *byte == b'\n').next().unwrap();
+    let record: serde_json::Value = serde_json::from_slice(first_line).unwrap();
+    assert_eq!(record["term"], "abelian");
+    assert_eq!(record["evidence"], "emphasis");
+  }
+}
+
+#[test]
+fn skips_headings_of_inferred_definitions() {
+  let corpus = Corpus::new(".".to_string());
+  let document = Document::new("tests/fixtures/definitions.html".to_string(), &corpus).unwrap();
+  let options = StatementOptions {
+    infer_unmarked: true,
+    ..StatementOptions::default()
+  };
+  let inferred = document
+    .statement_iter(options)
+    .find(|statement| statement.inferred)
+    .unwrap();
+  assert_eq!(inferred.tag.as_deref(), Some("Definition 1.4"));
+
+  let glossary = document.glossary();
+  assert!(!glossary
+    .entries
+    .iter()
+    .any(|entry| entry.term.contains("Definition")));
+  let entry = glossary
+    .entries
+    .iter()
+    .find(|entry| entry.source_id.as_deref() == Some("S1.p1"))
+    .unwrap();
+  assert_eq!(entry.term, "perfect");
+  assert_eq!(entry.evidence, TermEvidence::Emphasis);
+  assert_eq!(entry.definiens, "if it equals its commutator subgroup.");
+}
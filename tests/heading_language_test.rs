@@ -0,0 +1,145 @@
+extern crate llamapun;
+
+use llamapun::ams::{self, AmsEnv, StructuralEnv};
+use llamapun::dnm::DNM;
+use llamapun::parallel_data::StatementOptions;
+use llamapun::util::data_helpers;
+use llamapun::util::heading_language::{HeadingLanguage, HeadingTranslations};
+
+#[test]
+fn can_normalize_non_english_headings() {
+  use HeadingLanguage::*;
+  // dropped as English, translated in their language
+  assert_eq!(data_helpers::normalize_heading_title("lemme"), "");
+  assert_eq!(
+    data_helpers::normalize_heading_title_in("lemme", French),
+    "lemma"
+  );
+  assert_eq!(
+    data_helpers::normalize_heading_title_in("remarques", French),
+    "remark"
+  );
+  assert_eq!(
+    data_helpers::normalize_heading_title_in("les résultats numériques", French),
+    "result"
+  );
+  assert_eq!(
+    data_helpers::normalize_heading_title_in("einleitung", German),
+    "introduction"
+  );
+  assert_eq!(
+    data_helpers::normalize_heading_title_in("trabajos relacionados", Spanish),
+    "related work"
+  );
+  assert_eq!(
+    data_helpers::normalize_heading_title_in("dimostrazione", Italian),
+    "proof"
+  );
+  assert_eq!(
+    data_helpers::normalize_heading_title_in("conclusões", Portuguese),
+    "conclusion"
+  );
+  assert_eq!(
+    data_helpers::normalize_heading_title_in("доказательство", Russian),
+    "proof"
+  );
+  assert_eq!(
+    data_helpers::normalize_heading_title_in("dokazatelstvo", Russian),
+    "proof"
+  );
+  // English headings of other languages' papers are still normalized
+  assert_eq!(
+    data_helpers::normalize_heading_title_in("introduction", French),
+    "introduction"
+  );
+}
+
+#[test]
+fn can_map_non_english_environments() {
+  use HeadingLanguage::*;
+  assert_eq!(
+    StructuralEnv::from_heading("Travaux connexes", French),
+    StructuralEnv::RelatedWork
+  );
+  assert_eq!(
+    StructuralEnv::from_heading("Zusammenfassung", German),
+    StructuralEnv::Abstract
+  );
+  assert_eq!(
+    StructuralEnv::from_heading("Введение", Russian),
+    StructuralEnv::Introduction
+  );
+  assert_eq!(StructuralEnv::from("Введение"), StructuralEnv::Other);
+  assert_eq!(ams::normalize_env_in("satz", German), AmsEnv::Theorem);
+  assert_eq!(ams::normalize_env_in("teorema", Spanish), AmsEnv::Theorem);
+  assert_eq!(ams::normalize_env_in("lemma", Russian), AmsEnv::Lemma);
+  assert_eq!(
+    ams::normalize_env_in("folgerung", German),
+    AmsEnv::Corollary
+  );
+  assert_eq!(ams::normalize_env_in("folgerung", English), AmsEnv::Other);
+  assert_eq!(
+    ams::class_to_env_in("ltx_theorem ltx_theorem_remarque", French),
+    Some(AmsEnv::Remark)
+  );
+}
+
+#[test]
+fn can_detect_and_extend_languages() {
+  assert_eq!(
+    HeadingLanguage::detect(
+      "Nous montrons que tout groupe fini d'ordre premier est cyclique, et nous en déduisons \
+       plusieurs conséquences pour la théorie des représentations de ces groupes."
+    ),
+    Some(HeadingLanguage::French)
+  );
+  assert_eq!(
+    HeadingLanguage::from_code("pt"),
+    Some(HeadingLanguage::Portuguese)
+  );
+  assert_eq!(HeadingLanguage::Russian.to_string(), "ru");
+
+  let mut translations = HeadingTranslations::new();
+  assert_eq!(translations.phrase_count(HeadingLanguage::German), 0);
+  translations
+    .add_rules("# custom\nde\tdie\t-\nde\tbeweisidee\tproof\n")
+    .unwrap();
+  assert_eq!(translations.phrase_count(HeadingLanguage::German), 1);
+  assert_eq!(
+    translations.translate("Die Beweisidee", HeadingLanguage::German),
+    "proof"
+  );
+  assert_eq!(
+    translations.translate("Die Beweisidee", HeadingLanguage::French),
+    "Die Beweisidee"
+  );
+  assert!(translations.add_rules("xx\tfoo\tbar").is_err());
+  assert!(translations.add_rules("de\tfoo").is_err());
+}
+
+#[test]
+fn keeps_statements_in_heading_languages() {
+  let dnm = |text: &str| DNM {
+    plaintext: text.to_string(),
+    ..DNM::default()
+  };
+  let french = dnm(
+    "Soit mathformula un groupe fini d'ordre premier. Alors mathformula est cyclique, et tout \
+     élément non trivial de mathformula engendre le groupe.",
+  );
+  assert!(data_helpers::invalid_for_english_latin(&french));
+  assert!(!data_helpers::invalid_for_heading_languages(&french));
+  let russian = dnm(
+    "Пусть mathformula конечная группа простого порядка. Тогда группа mathformula является \
+     циклической, и любой её неединичный элемент порождает всю группу.",
+  );
+  assert!(!data_helpers::invalid_for_heading_languages(&russian));
+  let chinese = dnm(
+    "设 mathformula 是素数阶的有限群，则 mathformula 是循环群，并且它的每个非单位元都生成整个群。",
+  );
+  assert!(data_helpers::invalid_for_heading_languages(&chinese));
+
+  let options = StatementOptions::paragraph_model(false);
+  assert!(!options.english_only);
+  assert!(options.heading_languages_only);
+}
@@ -4,7 +4,7 @@ use senna::pos::POS;
 
 #[test]
 fn can_iterate_corpus() {
-  let mut corpus = Corpus::new("tests".to_string());
+  let mut corpus = Corpus::new("tests/resources".to_string());
   let mut word_count = 0;
   let mut doc_count = 0;
   for mut document in corpus.iter() {
@@ -75,7 +75,7 @@ fn can_iterate_sentences_directly() {
 
 #[test]
 fn can_senna_iterate_corpus() {
-  let mut corpus = Corpus::new("tests".to_string());
+  let mut corpus = Corpus::new("tests/resources".to_string());
   let mut word_count = 0;
   let mut doc_count = 0;
   for mut document in corpus.iter() {
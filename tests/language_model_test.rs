@@ -0,0 +1,111 @@
+extern crate llamapun;
+use llamapun::ngrams::language_model::*;
+use llamapun::ngrams::Ngrams;
+use std::io::Cursor;
+
+const CORPUS: [&str; 6] = [
+  "we prove the main theorem",
+  "we prove the lemma",
+  "the main theorem holds",
+  "the lemma holds for every group",
+  "we show the main result",
+  "every group is a ring",
+];
+
+fn trigram_model() -> LanguageModel {
+  let mut counts = LanguageModel::counter(3);
+  for sentence in CORPUS.iter() {
+    let words: Vec<&str> = sentence.split(' ').collect();
+    counts.add_sentence(&words);
+  }
+  LanguageModel::train(&counts).unwrap()
+}
+
+fn vocabulary() -> Vec<&'static str> {
+  let mut words: Vec<&str> = CORPUS.iter().flat_map(|s| s.split(' ')).collect();
+  words.sort_unstable();
+  words.dedup();
+  words.push(SENTENCE_END);
+  words.push(UNKNOWN);
+  words
+}
+
+#[test]
+fn probabilities_are_normalized() {
+  let model = trigram_model();
+  assert_eq!(model.order, 3);
+  for context in [
+    vec![],
+    vec![SENTENCE_START],
+    vec![SENTENCE_START, "we"],
+    vec!["the", "main"],
+    vec!["main", "lemma"],
+    vec!["unseen", "words"],
+  ] {
+    let total: f64 = vocabulary()
+      .iter()
+      .map(|word| 10f64.powf(model.log10_prob(&context, word)))
+      .sum();
+    assert!(
+      (total - 1.0).abs() < 1e-9,
+      "p(.|{:?}) sums to {}",
+      context,
+      total
+    );
+  }
+  // seen continuations are preferred over unseen ones
+  assert!(
+    model.log10_prob(&["the", "main"], "theorem") > model.log10_prob(&["the", "main"], "group")
+  );
+}
+
+#[test]
+fn can_score_sequences() {
+  let model = trigram_model();
+  let typical = model.score_words(&["we", "prove", "the", "main", "result"]);
+  assert_eq!(typical.token_count(), 6);
+  assert_eq!(typical.oov_count, 0);
+  let garbage = model.score_words(&["ring", "xqzzy", "the", "prove", "holds"]);
+  assert_eq!(garbage.oov_count, 1);
+  assert!(typical.perplexity() < garbage.perplexity());
+  let surprisal_sum: f64 = typical.surprisals.iter().sum();
+  assert!((surprisal_sum + typical.log10_prob / 2f64.log10()).abs() < 1e-9);
+
+  let mut paragraph = typical.clone();
+  paragraph.merge(garbage);
+  assert_eq!(paragraph.token_count(), 12);
+  assert!(paragraph.perplexity() > typical.perplexity());
+}
+
+#[test]
+fn can_round_trip_arpa() {
+  let model = trigram_model();
+  let mut arpa = Vec::new();
+  model.write_arpa(&mut arpa).unwrap();
+  let text = String::from_utf8(arpa.clone()).unwrap();
+  assert!(text.contains("\\data\\\nngram 1="));
+  assert!(text.contains("\\3-grams:"));
+  assert!(text.trim_end().ends_with("\\end\\"));
+
+  let reloaded = LanguageModel::read_arpa(Cursor::new(arpa)).unwrap();
+  assert_eq!(reloaded.order, 3);
+  assert_eq!(reloaded.len(), model.len());
+  for words in [
+    vec!["we", "prove", "the", "lemma"],
+    vec!["a", "ring", "xqzzy"],
+  ] {
+    let (before, after) = (model.score_words(&words), reloaded.score_words(&words));
+    assert!((before.log10_prob - after.log10_prob).abs() < 1e-4);
+  }
+  assert!(LanguageModel::read_arpa(Cursor::new("no header")).is_err());
+}
+
+#[test]
+fn rejects_unsuitable_counts() {
+  let only_trigrams = Ngrams {
+    n: 3,
+    ..Ngrams::default()
+  };
+  assert!(LanguageModel::train(&only_trigrams).is_err());
+  assert!(LanguageModel::train(&LanguageModel::counter(2)).is_err());
+}
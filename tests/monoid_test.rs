@@ -0,0 +1,37 @@
+use llamapun::parallel_data::monoid::Monoid;
+use std::collections::{BTreeMap, HashMap};
+
+#[test]
+fn can_combine_catalogs() {
+  let mut catalog: HashMap<String, u64> = HashMap::empty();
+  catalog.combine(HashMap::from([
+    (String::from("a"), 1),
+    (String::from("b"), 2),
+  ]));
+  catalog.combine(HashMap::from([(String::from("b"), 3)]));
+  assert_eq!(catalog["a"], 1);
+  assert_eq!(catalog["b"], 5);
+}
+
+#[test]
+fn can_combine_nested_results() {
+  let mut histograms: BTreeMap<String, HashMap<usize, u64>> = BTreeMap::empty();
+  histograms.combine(BTreeMap::from([(
+    String::from("sentences"),
+    HashMap::from([(3, 1)]),
+  )]));
+  histograms.combine(BTreeMap::from([(
+    String::from("sentences"),
+    HashMap::from([(3, 2), (5, 1)]),
+  )]));
+  assert_eq!(histograms["sentences"][&3], 3);
+  assert_eq!(histograms["sentences"][&5], 1);
+
+  let mut results: (Vec<&str>, Option<u64>, f64) = Monoid::empty();
+  results.combine((vec!["0903.1000"], None, 0.5));
+  results.combine((vec!["1307.8133"], Some(4), 1.0));
+  results.combine((Vec::new(), Some(1), 0.0));
+  assert_eq!(results.0, vec!["0903.1000", "1307.8133"]);
+  assert_eq!(results.1, Some(5));
+  assert_eq!(results.2, 1.5);
+}
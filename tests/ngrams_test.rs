@@ -0,0 +1,142 @@
+extern crate llamapun;
+use llamapun::dnm::DNMRange;
+use llamapun::ngrams::collocation::Collocations;
+use llamapun::ngrams::Ngrams;
+use llamapun::parallel_data::Corpus;
+use llamapun::tokenizer::Tokenizer;
+use llamapun::util::test::ascii_dnm;
+
+#[test]
+fn can_count_plain_ngrams() {
+  let mut bigrams = Ngrams {
+    n: 2,
+    ..Ngrams::default()
+  };
+  bigrams.add_content("the cat saw the cat , then the dog");
+  assert_eq!(bigrams.get("the cat"), 2);
+  assert_eq!(bigrams.get("cat saw"), 1);
+  assert_eq!(bigrams.get("then the"), 1);
+  assert_eq!(bigrams.get("cat ,"), 0, "punctuation is skipped");
+  assert_eq!(bigrams.get("cat then"), 1);
+  assert_eq!(bigrams.distinct_count(), 6);
+  assert_eq!(bigrams.sorted()[0], (&"the cat".to_string(), 2));
+
+  let mut too_short = Ngrams {
+    n: 3,
+    ..Ngrams::default()
+  };
+  too_short.add_content("two words");
+  assert_eq!(too_short.distinct_count(), 0);
+}
+
+#[test]
+fn can_count_ngram_ranges_and_skipgrams() {
+  let mut upto_three = Ngrams {
+    n: 3,
+    min_n: Some(1),
+    ..Ngrams::default()
+  };
+  upto_three.record_words(vec!["a", "b", "c"]);
+  let mut recorded: Vec<&str> = upto_three.counts.keys().map(String::as_str).collect();
+  recorded.sort_unstable();
+  assert_eq!(recorded, vec!["a", "a b", "a b c", "b", "b c", "c"]);
+
+  // 2-skip-bigrams of "insurgents killed in ongoing fighting" (Guthrie et al., 2006)
+  let mut skipgrams = Ngrams {
+    n: 2,
+    skip: 2,
+    ..Ngrams::default()
+  };
+  skipgrams.record_words(vec!["insurgents", "killed", "in", "ongoing", "fighting"]);
+  let mut recorded: Vec<&str> = skipgrams.counts.keys().map(String::as_str).collect();
+  recorded.sort_unstable();
+  assert_eq!(
+    recorded,
+    vec![
+      "in fighting",
+      "in ongoing",
+      "insurgents in",
+      "insurgents killed",
+      "insurgents ongoing",
+      "killed fighting",
+      "killed in",
+      "killed ongoing",
+      "ongoing fighting"
+    ]
+  );
+
+  // the skips are shared by the whole n-gram
+  let mut skip_trigrams = Ngrams {
+    n: 3,
+    skip: 1,
+    ..Ngrams::default()
+  };
+  skip_trigrams.record_words(vec!["a", "b", "c", "d"]);
+  let mut recorded: Vec<&str> = skip_trigrams.counts.keys().map(String::as_str).collect();
+  recorded.sort_unstable();
+  assert_eq!(recorded, vec!["a b c", "a b d", "a c d", "b c d"]);
+}
+
+#[test]
+fn can_count_tokenizer_output() {
+  let text = "We prove the main theorem. The main theorem holds.";
+  let dnm = ascii_dnm(text);
+  let tokenizer = Tokenizer::default();
+  let mut bigrams = Ngrams {
+    n: 2,
+    ..Ngrams::default()
+  };
+  for sentence in tokenizer.sentence_iter(&dnm) {
+    let words: Vec<DNMRange> = tokenizer.word_iter(&sentence).collect();
+    bigrams.add_ranges(&words);
+  }
+  assert_eq!(bigrams.get("main theorem"), 2);
+  assert_eq!(
+    bigrams.get("theorem The"),
+    0,
+    "sentences are counted separately"
+  );
+}
+
+#[test]
+fn can_merge_ngrams() {
+  let template = Ngrams {
+    n: 2,
+    ..Ngrams::default()
+  };
+  let mut left = template.clone();
+  left.add_content("the main result");
+  let mut right = template.clone();
+  right.add_content("the main theorem");
+  let mut merged = template;
+  merged.merge(left);
+  merged.merge(right);
+  assert_eq!(merged.n, 2);
+  assert_eq!(merged.get("the main"), 2);
+  assert_eq!(merged.get("main result"), 1);
+  assert_eq!(merged.get("main theorem"), 1);
+}
+
+#[test]
+fn can_fold_ngrams_with_parallel_walk() {
+  let corpus = Corpus::new("tests/resources".to_string());
+  let ngrams = corpus.fold_with_parallel_walk(
+    || Ngrams {
+      n: 3,
+      min_n: Some(2),
+      ..Ngrams::default()
+    },
+    |document, ngrams| {
+      for paragraph in document.paragraph_iter() {
+        ngrams.add_content(&paragraph.dnm.plaintext);
+      }
+    },
+    Ngrams::merge,
+  );
+  assert_eq!((ngrams.n, ngrams.min_n), (3, Some(2)));
+  assert!(ngrams.distinct_count() > 0);
+
+  let collocations =
+    corpus.fold_with_parallel_walk(|| Collocations::new(1, 4), |_, _| {}, Collocations::merge);
+  assert_eq!((collocations.min_n, collocations.max_n), (1, 4));
+}
@@ -0,0 +1,49 @@
+use llamapun::ams::StructuralEnv;
+use llamapun::parallel_data::outline::SectionKind;
+use llamapun::parallel_data::*;
+
+#[test]
+fn can_build_outline() {
+  let corpus = Corpus::new(".".to_string());
+  let document = Document::new("tests/resources/1311.0066.xhtml".to_string(), &corpus).unwrap();
+  let outline = document.outline();
+  // 5 sections, 10 subsections and the bibliography
+  assert_eq!(outline.sections.len(), 16);
+  assert_eq!(outline.roots().count(), 6);
+
+  let introduction = &outline.sections[0];
+  assert_eq!(introduction.kind, SectionKind::Section);
+  assert_eq!(introduction.id.as_deref(), Some("S1"));
+  assert_eq!(introduction.tag.as_deref(), Some("1."));
+  assert_eq!(introduction.number.as_deref(), Some("1"));
+  assert_eq!(introduction.title, "Introduction");
+  assert_eq!(introduction.env, StructuralEnv::Introduction);
+  assert_eq!(introduction.label(), "1 Introduction");
+
+  let background = &outline.sections[2];
+  assert_eq!(background.id.as_deref(), Some("S2.SS1"));
+  assert_eq!(background.parent, Some(1));
+  assert_eq!(outline.sections[1].children, vec![2, 3]);
+  assert_eq!(background.normalized_title, "background");
+  assert_eq!(
+    outline.section_path(background.node),
+    vec!["2 Tensor triangulated geometry", "2.1 Background"]
+  );
+  let bibliography = outline.sections.last().unwrap();
+  assert_eq!(bibliography.kind, SectionKind::Bibliography);
+
+  let statement = document
+    .statement_iter(StatementOptions::default())
+    .find(|statement| statement.id.as_deref() == Some("S2.Thmtheorem1"))
+    .unwrap();
+  let path = statement.section_path(&outline);
+  assert_eq!(path.len(), 2);
+  assert!(path[1].starts_with("2.1"));
+
+  for mut paragraph in document.paragraph_iter() {
+    let path = paragraph.section_path(&outline);
+    for sentence in paragraph.iter() {
+      assert_eq!(sentence.section_path(&outline), path);
+    }
+  }
+}
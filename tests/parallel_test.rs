@@ -1,11 +1,13 @@
 use libxml::readonly::RoNode;
+use llamapun::ngrams::counting::{CountMinSketch, Counter};
+use llamapun::ngrams::Ngrams;
 use llamapun::parallel_data::*;
 use llamapun::util::test::RESOURCE_DOCUMENTS;
 use std::collections::HashMap;
 
 #[test]
 fn can_iterate_corpus() {
-  let corpus = Corpus::new("tests".to_string());
+  let corpus = Corpus::new("tests/resources".to_string());
   let catalog = corpus.catalog_with_parallel_walk(|document| {
     let mut t_catalog = HashMap::new();
     t_catalog.insert(String::from("doc_count"), 1);
@@ -40,7 +42,7 @@ fn can_iterate_corpus() {
 
 #[test]
 fn can_iterate_xpath() {
-  let corpus = Corpus::new("tests".to_string());
+  let corpus = Corpus::new("tests/resources".to_string());
   let catalog = corpus.catalog_with_parallel_walk(|document| {
     let mut t_catalog = HashMap::new();
     let mut contacts = 0;
@@ -75,7 +77,7 @@ fn can_iterate_xpath() {
 
 #[test]
 fn can_iterate_custom() {
-  let corpus = Corpus::new("tests".to_string());
+  let corpus = Corpus::new("tests/resources".to_string());
   let email_filter = |node: &RoNode| {
     node.get_name() == "span"
       && node
@@ -99,3 +101,68 @@ fn can_iterate_custom() {
     contact_count
   );
 }
+
+#[test]
+fn can_count_with_parallel_walk() {
+  let corpus = Corpus::new("tests/resources".to_string());
+  let bigrams = Ngrams {
+    n: 2,
+    ..Ngrams::default()
+  };
+  let sketch = corpus.fold_with_parallel_walk(
+    || CountMinSketch::new(4096, 4),
+    |document, counter| {
+      for mut paragraph in document.paragraph_iter() {
+        for mut sentence in paragraph.iter() {
+          let words: Vec<&str> = sentence
+            .word_iter()
+            .map(|word| word.range.get_plaintext())
+            .collect();
+          bigrams.count_words(&words, counter);
+        }
+      }
+    },
+    Counter::merge,
+  );
+  assert!(
+    sketch.total() > 5000,
+    "expected more than 5000 bigrams, found {:?}",
+    sketch.total()
+  );
+}
+
+#[test]
+fn can_map_reduce_corpus() {
+  let corpus = Corpus::new("tests/resources".to_string());
+  let (paragraph_counts, paths): (HashMap<String, Vec<usize>>, Vec<String>) = corpus
+    .map_reduce_with_parallel_walk(|document| {
+      let mut paragraph_counts = HashMap::new();
+      paragraph_counts.insert(
+        String::from("paragraphs"),
+        vec![document.paragraph_iter().count()],
+      );
+      (paragraph_counts, vec![document.path.clone()])
+    });
+  assert_eq!(paths.len(), RESOURCE_DOCUMENTS.len());
+  assert_eq!(paragraph_counts["paragraphs"].len(), paths.len());
+
+  let longest = corpus.reduce_with_parallel_walk(
+    || 0,
+    |a: usize, b: usize| a.max(b),
+    |document| document.paragraph_iter().count(),
+  );
+  assert_eq!(
+    longest,
+    *paragraph_counts["paragraphs"].iter().max().unwrap()
+  );
+
+  let mut folded = corpus.fold_with_parallel_walk(
+    Vec::new,
+    |document, counts: &mut Vec<usize>| counts.push(document.paragraph_iter().count()),
+    |counts, other| counts.extend(other),
+  );
+  folded.sort_unstable();
+  let mut expected = paragraph_counts["paragraphs"].clone();
+  expected.sort_unstable();
+  assert_eq!(folded, expected);
+}
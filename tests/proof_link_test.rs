@@ -0,0 +1,87 @@
+use llamapun::ams::AmsEnv;
+use llamapun::parallel_data::proof_link::{self, LinkEvidence, ProofPair};
+use llamapun::parallel_data::*;
+
+#[test]
+fn can_link_proofs() {
+  let corpus = Corpus::new(".".to_string());
+  let document = Document::new("tests/fixtures/proof_links.html".to_string(), &corpus).unwrap();
+  let statements: Vec<Statement> = document
+    .statement_iter(StatementOptions::default())
+    .collect();
+  assert_eq!(statements.len(), 8);
+  let links = proof_link::link_proofs(&statements);
+  let linked: Vec<(Option<&str>, Option<&str>, LinkEvidence)> = links
+    .iter()
+    .map(|link| {
+      (
+        statements[link.proof].id.as_deref(),
+        statements[link.statement].id.as_deref(),
+        link.evidence,
+      )
+    })
+    .collect();
+  assert_eq!(
+    linked,
+    vec![
+      (
+        Some("S1.1"),
+        Some("S1.Thmtheorem1"),
+        LinkEvidence::Reference
+      ),
+      (Some("S1.2"), Some("S1.Thmtheorem2"), LinkEvidence::Heading),
+      (
+        Some("S1.3"),
+        Some("S1.Thmtheorem2"),
+        LinkEvidence::Preceding
+      ),
+      (
+        Some("S1.4"),
+        Some("S1.Thmtheorem4"),
+        LinkEvidence::Adjacency
+      ),
+    ]
+  );
+
+  let pairs = ProofPair::from_links(&statements, &links, 0.6);
+  assert_eq!(pairs.len(), 3);
+  assert_eq!(pairs[1].env, AmsEnv::Theorem);
+  assert_eq!(pairs[1].statement_tag.as_deref(), Some("Theorem 1.2"));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn can_write_proof_pairs_as_json() {
+  let corpus = Corpus::new(".".to_string());
+  let document = Document::new("tests/fixtures/proof_links.html".to_string(), &corpus).unwrap();
+  let statements: Vec<Statement> = document
+    .statement_iter(StatementOptions::default())
+    .collect();
+  let links = proof_link::link_proofs(&statements);
+  let pairs = ProofPair::from_links(&statements, &links, 0.6);
+  let mut dataset = Vec::new();
+  pairs[0].write_json_line(&mut dataset).unwrap();
+  let record: serde_json::Value = serde_json::from_slice(&dataset).unwrap();
+  assert_eq!(record["statement_id"], "S1.Thmtheorem1");
+  assert_eq!(record["proof_id"], "S1.1");
+  assert_eq!(record["evidence"], "reference");
+  assert!(record["proof_text"].as_str().unwrap().contains("bisect"));
+}
+
+#[test]
+fn can_link_adjacent_proofs() {
+  let corpus = Corpus::new(".".to_string());
+  let document = Document::new("tests/resources/1311.0066.xhtml".to_string(), &corpus).unwrap();
+  let statements: Vec<Statement> = document
+    .statement_iter(StatementOptions::default())
+    .collect();
+  let links = proof_link::link_proofs(&statements);
+  assert_eq!(links.len(), 17);
+  assert!(links
+    .iter()
+    .all(|link| proof_link::is_provable(statements[link.statement].env)));
+  assert!(links.iter().any(|link| {
+    statements[link.statement].id.as_deref() == Some("S4.Thmtheorem17")
+      && link.evidence == LinkEvidence::Adjacency
+  }));
+}
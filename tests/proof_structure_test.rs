@@ -0,0 +1,69 @@
+use llamapun::parallel_data::proof_structure::ProofPartKind;
+use llamapun::parallel_data::*;
+
+#[test]
+fn can_segment_proofs() {
+  let corpus = Corpus::new(".".to_string());
+  let document = Document::new("tests/fixtures/proof_structure.html".to_string(), &corpus).unwrap();
+  let proof = document
+    .statement_iter(StatementOptions::default())
+    .find(|statement| statement.id.as_deref() == Some("S1.1"))
+    .unwrap();
+  let structure = proof.proof_structure().unwrap();
+  assert_eq!(structure.root.kind, ProofPartKind::Proof);
+  let parts: Vec<(usize, ProofPartKind, Option<&str>, bool)> = structure
+    .parts()
+    .into_iter()
+    .map(|(depth, part)| (depth, part.kind, part.label.as_deref(), part.marked))
+    .collect();
+  assert_eq!(
+    parts,
+    vec![
+      (1, ProofPartKind::Case, Some("1"), false),
+      (2, ProofPartKind::Step, Some("1"), false),
+      (2, ProofPartKind::Step, Some("2"), false),
+      (1, ProofPartKind::Case, Some("2"), false),
+      (2, ProofPartKind::Item, Some("(i)"), true),
+      (2, ProofPartKind::Item, Some("(ii)"), true),
+      (2, ProofPartKind::Claim, Some("3"), true),
+    ]
+  );
+  let first_case = &structure.root.children[0];
+  let text = structure.text(first_case);
+  assert!(text.starts_with("Case 1:"));
+  assert!(text.contains("largest"));
+  assert!(text.ends_with("This concludes the first case."));
+  // paragraphs without a header continue the innermost open part
+  let second_step = structure.text(&first_case.children[1]);
+  assert!(second_step.starts_with("Step 2."));
+  assert!(second_step.ends_with("This concludes the first case."));
+  assert!(!structure.dnm.plaintext.contains("Proof."));
+}
+
+#[test]
+fn can_nest_sub_parts() {
+  let corpus = Corpus::new(".".to_string());
+  let document = Document::new("tests/fixtures/proof_structure.html".to_string(), &corpus).unwrap();
+  let proof = document
+    .statement_iter(StatementOptions::default())
+    .find(|statement| statement.id.as_deref() == Some("S1.2"))
+    .unwrap();
+  let structure = proof.proof_structure().unwrap();
+  let parts: Vec<(usize, ProofPartKind, Option<&str>)> = structure
+    .parts()
+    .into_iter()
+    .map(|(depth, part)| (depth, part.kind, part.label.as_deref()))
+    .collect();
+  assert_eq!(
+    parts,
+    vec![
+      (1, ProofPartKind::Case, Some("1")),
+      (2, ProofPartKind::Case, Some("1a")),
+      (2, ProofPartKind::Case, Some("1b")),
+      (1, ProofPartKind::Case, Some("2")),
+    ]
+  );
+  assert!(structure
+    .text(&structure.root.children[0])
+    .ends_with("the map is not surjective."));
+}
@@ -0,0 +1,106 @@
+use llamapun::dnm::DNMRange;
+use llamapun::tokenizer::quantity;
+use llamapun::tokenizer::*;
+use llamapun::util::test::{ascii_dnm, utf8_dnm};
+
+#[test]
+fn can_parse_numbers() {
+  let cases = [
+    ("42", QuantityKind::Integer, 42.0),
+    ("1,000", QuantityKind::Integer, 1000.0),
+    ("2.75", QuantityKind::Decimal, 2.75),
+    ("-2.5", QuantityKind::Decimal, -2.5),
+    ("1.2e-5", QuantityKind::Scientific, 1.2e-5),
+    ("10^{-3}", QuantityKind::Scientific, 1e-3),
+    ("3 x 10^8", QuantityKind::Scientific, 3e8),
+    ("2nd", QuantityKind::Ordinal, 2.0),
+    ("15%", QuantityKind::Percentage, 15.0),
+    ("20 percent", QuantityKind::Percentage, 20.0),
+  ];
+  for (text, kind, value) in cases.iter() {
+    let parsed = quantity::parse(text).unwrap_or_else(|| panic!("failed to parse {:?}", text));
+    assert_eq!(parsed.kind, *kind, "kind mismatch for {:?}", text);
+    assert!(
+      (parsed.value - value).abs() < 1e-12,
+      "value mismatch for {:?}: {}",
+      text,
+      parsed.value
+    );
+  }
+  for text in ["3D", "H2O", "x3", "v1.2.3a", "Theorem"].iter() {
+    assert!(quantity::parse(text).is_none(), "{:?} is not a quantity", text);
+  }
+}
+
+#[test]
+fn can_parse_ranges_and_units() {
+  let range = quantity::parse("5-10%").unwrap();
+  assert_eq!(range.kind, QuantityKind::Range);
+  assert_eq!(range.value, 5.0);
+  assert_eq!(range.upper, Some(10.0));
+  assert_eq!(range.unit.as_deref(), Some("%"));
+
+  let speed = quantity::parse("3.5 km/s").unwrap();
+  assert_eq!(speed.kind, QuantityKind::Decimal);
+  assert_eq!(speed.unit.as_deref(), Some("km/s"));
+
+  let velocity = quantity::parse("220 km s-1").unwrap();
+  assert_eq!(velocity.unit.as_deref(), Some("km s^-1"));
+
+  let energy = quantity::parse("2.1 x 10^5 GeV").unwrap();
+  assert_eq!(energy.kind, QuantityKind::Scientific);
+  assert_eq!(energy.unit.as_deref(), Some("GeV"));
+
+  // a following word is not a unit
+  assert!(quantity::parse("3 most").is_none());
+  assert_eq!(quantity::scan("in 3 most cases").len(), 1);
+}
+
+#[test]
+fn can_tokenize_quantities() {
+  let dnm = ascii_dnm("The wind reached 3.5 km/s at 10^{-3} bar, 1,000 times in the 2nd run.");
+  let tokenizer = Tokenizer::default();
+  let range = DNMRange {
+    start: 0,
+    end: dnm.plaintext.len(),
+    dnm: &dnm,
+  };
+  let quantities = tokenizer.quantities(&range);
+  let texts: Vec<&str> = quantities.iter().map(|q| q.range.get_plaintext()).collect();
+  assert_eq!(texts, vec!["3.5 km/s", "10^{-3} bar", "1,000", "2nd"]);
+
+  let tokens: Vec<&str> = tokenizer
+    .words_punct_and_quantities(&range)
+    .iter()
+    .map(|t| t.get_plaintext())
+    .collect();
+  assert_eq!(
+    tokens,
+    vec![
+      "The", "wind", "reached", "3.5 km/s", "at", "10^{-3} bar", ",", "1,000", "times", "in",
+      "the", "2nd", "run", "."
+    ]
+  );
+}
+
+#[test]
+fn can_tokenize_quantities_after_non_ascii_text() {
+  let dnm = utf8_dnm("Über die Größe: 3.5 km/s, oder 2 × 10^8 m/s – schnell.");
+  let tokenizer = Tokenizer::default();
+  let range = DNMRange {
+    start: 0,
+    end: dnm.plaintext.chars().count(),
+    dnm: &dnm,
+  };
+  let tokens: Vec<&str> = tokenizer
+    .words_punct_and_quantities(&range)
+    .iter()
+    .map(|t| t.get_plaintext())
+    .collect();
+  assert_eq!(
+    tokens,
+    vec![
+      "Über", "die", "Größe", ":", "3.5 km/s", ",", "oder", "2 × 10^8 m/s", "–", "schnell", "."
+    ]
+  );
+}
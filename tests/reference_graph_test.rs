@@ -0,0 +1,66 @@
+use llamapun::ams::AmsEnv;
+use llamapun::parallel_data::reference_graph::{GraphFormat, LabelKind, DOCUMENT_ID};
+use llamapun::parallel_data::*;
+
+#[test]
+fn can_build_reference_graph() {
+  let corpus = Corpus::new(".".to_string());
+  let document = Document::new("tests/fixtures/reference_graph.html".to_string(), &corpus).unwrap();
+  let graph = document.reference_graph();
+
+  let kinds: Vec<(&str, LabelKind)> = graph
+    .nodes
+    .iter()
+    .map(|node| (node.id.as_str(), node.kind))
+    .collect();
+  assert_eq!(
+    kinds,
+    vec![
+      (DOCUMENT_ID, LabelKind::Document),
+      ("S1", LabelKind::Section),
+      ("S1.Thmtheorem1", LabelKind::Statement(AmsEnv::Lemma)),
+      ("S1.E1", LabelKind::Equation),
+      ("proof1", LabelKind::Statement(AmsEnv::Proof)),
+      ("bib.bib1", LabelKind::Bibitem),
+    ]
+  );
+  assert_eq!(graph.node("S1.E1").unwrap().tag.as_deref(), Some("(1)"));
+  assert_eq!(graph.edges.len(), 5);
+  assert_eq!(graph.unresolved, 1);
+  assert_eq!(graph.references_to("S1.Thmtheorem1").count(), 3);
+  assert_eq!(graph.references_from(DOCUMENT_ID).count(), 1);
+
+  let dependencies: Vec<&str> = graph
+    .dependencies("proof1")
+    .iter()
+    .map(|node| node.id.as_str())
+    .collect();
+  assert_eq!(dependencies, vec!["S1.Thmtheorem1", "S1.E1", "bib.bib1"]);
+  let first = graph.references_from("proof1").next().unwrap();
+  assert_eq!(first.text, "Lemma 1.1");
+  assert!(first.context.contains("By Lemma 1.1"));
+  assert!(!first.context.contains("closed"));
+
+  #[cfg(feature = "json")]
+  {
+    let mut json = Vec::new();
+    graph.write(&mut json, GraphFormat::Json).unwrap();
+    let record: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(record["nodes"].as_array().unwrap().len(), 6);
+    assert_eq!(record["edges"][1]["source"], "proof1");
+    assert_eq!(record["nodes"][2]["env"], "lemma");
+    assert_eq!(record["nodes"][0]["tag"], serde_json::Value::Null);
+  }
+
+  let mut dot = Vec::new();
+  graph.write(&mut dot, GraphFormat::Dot).unwrap();
+  let dot = String::from_utf8(dot).unwrap();
+  assert!(dot.starts_with("digraph"));
+  assert!(dot.contains("\"proof1\" -> \"S1.E1\""));
+
+  let mut graphml = Vec::new();
+  graph.write(&mut graphml, GraphFormat::GraphMl).unwrap();
+  let graphml = String::from_utf8(graphml).unwrap();
+  assert!(graphml.contains("edgedefault=\"directed\""));
+  assert!(graphml.contains("<edge source=\"proof1\" target=\"bib.bib1\">"));
+}
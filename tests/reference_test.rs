@@ -0,0 +1,220 @@
+use libxml::parser::Parser;
+use llamapun::dnm::{DNMParameters, DNMRange, DNM};
+use llamapun::tokenizer::reference;
+use llamapun::tokenizer::*;
+use llamapun::util::test::{ascii_dnm, utf8_dnm};
+
+#[test]
+fn can_parse_references() {
+  let cases = [
+    ("[12, 14-16]", ReferenceKind::Citation),
+    ("[Smi04; Jon05a]", ReferenceKind::Citation),
+    ("CitationElement", ReferenceKind::Citation),
+    ("Smith et al. (2004)", ReferenceKind::AuthorYear),
+    ("Smith and Jones (2004, 2005b)", ReferenceKind::AuthorYear),
+    (
+      "(Smith et al., 2004; Jones 2005)",
+      ReferenceKind::AuthorYear,
+    ),
+    ("Fig. 3(b)", ReferenceKind::CrossReference(Some("figure"))),
+    ("Eq. (2.1)", ReferenceKind::CrossReference(Some("equation"))),
+    (
+      "Eqs. (2)-(4)",
+      ReferenceKind::CrossReference(Some("equation")),
+    ),
+    (
+      "Theorem 4.2",
+      ReferenceKind::CrossReference(Some("theorem")),
+    ),
+    (
+      "Section REF",
+      ReferenceKind::CrossReference(Some("section")),
+    ),
+    (
+      "Appendix A",
+      ReferenceKind::CrossReference(Some("appendix")),
+    ),
+    ("(REF)", ReferenceKind::CrossReference(None)),
+    (
+      "Section (3)",
+      ReferenceKind::CrossReference(Some("section")),
+    ),
+  ];
+  for (text, kind) in cases.iter() {
+    assert_eq!(
+      reference::parse(text),
+      Some(*kind),
+      "kind mismatch for {:?}",
+      text
+    );
+  }
+  for text in [
+    "[0, x]",
+    "Smith",
+    "Theorem",
+    "Figure out",
+    "The 2004 results",
+    "In (2004)",
+    "Since (1998, 2002)",
+    "(In, 2004)",
+  ]
+  .iter()
+  {
+    assert!(
+      reference::parse(text).is_none(),
+      "{:?} is not a reference",
+      text
+    );
+  }
+}
+
+#[test]
+fn can_tokenize_references() {
+  let dnm =
+    ascii_dnm("As in Fig. 3(b) and Eq. (2.1), Smith et al. (2004) improved on [12, 14-16].");
+  let tokenizer = Tokenizer::default();
+  let range = DNMRange {
+    start: 0,
+    end: dnm.plaintext.len(),
+    dnm: &dnm,
+  };
+  let references = tokenizer.references(&range);
+  let texts: Vec<&str> = references.iter().map(|r| r.range.get_plaintext()).collect();
+  assert_eq!(
+    texts,
+    vec![
+      "Fig. 3(b)",
+      "Eq. (2.1)",
+      "Smith et al. (2004)",
+      "[12, 14-16]"
+    ]
+  );
+  assert!(references.iter().all(|r| r.node.is_none()));
+
+  let tokens: Vec<&str> = tokenizer
+    .words_punct_and_references(&range)
+    .iter()
+    .map(|t| t.get_plaintext())
+    .collect();
+  assert_eq!(
+    tokens,
+    vec![
+      "As",
+      "in",
+      "Fig. 3(b)",
+      "and",
+      "Eq. (2.1)",
+      ",",
+      "Smith et al. (2004)",
+      "improved",
+      "on",
+      "[12, 14-16]",
+      "."
+    ]
+  );
+}
+
+#[test]
+fn can_tokenize_references_after_non_ascii_text() {
+  let dnm = utf8_dnm("Laut Müller et al. (2004) gilt die Schätzung – siehe Abb. und Fig. 3(b).");
+  let tokenizer = Tokenizer::default();
+  let range = DNMRange {
+    start: 0,
+    end: dnm.plaintext.chars().count(),
+    dnm: &dnm,
+  };
+  let tokens: Vec<&str> = tokenizer
+    .words_punct_and_references(&range)
+    .iter()
+    .map(|t| t.get_plaintext())
+    .collect();
+  assert_eq!(
+    tokens,
+    vec![
+      "Laut",
+      "Müller et al. (2004)",
+      "gilt",
+      "die",
+      "Schätzung",
+      "–",
+      "siehe",
+      "Abb",
+      ".",
+      "und",
+      "Fig. 3(b)",
+      "."
+    ]
+  );
+}
+
+#[test]
+fn references_do_not_split_sentences() {
+  let dnm = ascii_dnm(
+    "This was shown by Smith et al. (2004) in detail. It follows from Eq. (2.1) as in Ref. \
+     CitationElement and Fig. 3. The end.",
+  );
+  let tokenizer = Tokenizer::default();
+  let sentences: Vec<&str> = tokenizer
+    .sentences(&dnm)
+    .iter()
+    .map(|s| s.get_plaintext())
+    .collect();
+  assert_eq!(
+    sentences,
+    vec![
+      "This was shown by Smith et al. (2004) in detail.",
+      "It follows from Eq. (2.1) as in Ref. CitationElement and Fig. 3.",
+      "The end."
+    ]
+  );
+}
+
+#[test]
+fn et_al_ends_sentences_before_capitalized_words() {
+  let dnm = ascii_dnm(
+    "This was shown by Smith et al. Recently, it was refined by Jones et al. (2004) and Lee et \
+     al. [12]. The end.",
+  );
+  let tokenizer = Tokenizer::default();
+  let sentences: Vec<&str> = tokenizer
+    .sentences(&dnm)
+    .iter()
+    .map(|s| s.get_plaintext())
+    .collect();
+  assert_eq!(
+    sentences,
+    vec![
+      "This was shown by Smith et al.",
+      "Recently, it was refined by Jones et al. (2004) and Lee et al. [12].",
+      "The end."
+    ]
+  );
+}
+
+#[test]
+fn references_link_to_markup() {
+  let html = "<html><body><div class=\"ltx_para\"><p>As shown in <a class=\"ltx_ref\" \
+              href=\"#S2\">Section 2</a> and by <cite class=\"ltx_cite\">Smith et al. (<a \
+              class=\"ltx_ref\" href=\"#bib1\">2004</a>)</cite>, it holds.</p></div></body></html>";
+  let doc = Parser::default_html().parse_string(html).unwrap();
+  let dnm = DNM::new(
+    doc.get_root_readonly().unwrap(),
+    DNMParameters::llamapun_normalization(),
+  );
+  let tokenizer = Tokenizer::default();
+  let range = dnm.get_range().unwrap();
+  let references = tokenizer.references(&range);
+  assert_eq!(references.len(), 2);
+  assert_eq!(references[0].range.get_plaintext(), "REF");
+  assert_eq!(references[0].kind, ReferenceKind::CrossReference(None));
+  assert_eq!(
+    references[0].node.map(|n| n.get_name()).as_deref(),
+    Some("a")
+  );
+  assert_eq!(references[1].range.get_plaintext(), "CitationElement");
+  assert_eq!(references[1].kind, ReferenceKind::Citation);
+  assert_eq!(
+    references[1].node.map(|n| n.get_name()).as_deref(),
+    Some("cite")
+  );
+}
@@ -0,0 +1,150 @@
+use llamapun::parallel_data::selection::{self, Selection};
+use llamapun::parallel_data::walk::{FailureKind, WalkOptions};
+use std::env;
+use std::fs;
+use std::io::Cursor;
+
+#[test]
+fn can_read_manifest_entries() {
+  assert_eq!(selection::manifest_entry("0903.1000"), Some("0903.1000"));
+  assert_eq!(
+    selection::manifest_entry("\"0903.1000\",train,12"),
+    Some("0903.1000")
+  );
+  assert_eq!(
+    selection::manifest_entry("0903/0903.1000.html\ttest"),
+    Some("0903/0903.1000.html")
+  );
+  assert_eq!(selection::manifest_entry("  "), None);
+  assert_eq!(selection::manifest_entry("# the test split"), None);
+}
+
+#[test]
+fn can_resolve_paths_and_ids() {
+  let root = "tests/resources";
+  assert_eq!(
+    selection::resolve("0903.1000", root, &None),
+    Some(String::from("tests/resources/0903.1000.html"))
+  );
+  assert_eq!(
+    selection::resolve("1311.0066", root, &None),
+    Some(String::from("tests/resources/1311.0066.xhtml"))
+  );
+  assert_eq!(
+    selection::resolve("astro-ph/9710163", root, &None),
+    Some(String::from("tests/resources/astro-ph9710163.html"))
+  );
+  assert_eq!(
+    selection::resolve("1307.8133.html", root, &None),
+    Some(String::from("tests/resources/1307.8133.html"))
+  );
+  assert_eq!(
+    selection::resolve("0903.1000", root, &Some(String::from(".html5"))),
+    None
+  );
+  assert_eq!(selection::resolve("9999.0001", root, &None), None);
+}
+
+#[test]
+fn can_resolve_nested_layouts() {
+  let root = env::temp_dir().join("llamapun_selection_test");
+  let nested = root.join("0903").join("0903.2000");
+  fs::create_dir_all(&nested).unwrap();
+  fs::write(nested.join("0903.2000.html"), "<html></html>").unwrap();
+  let root = root.to_str().unwrap();
+  assert_eq!(
+    selection::resolve("0903.2000", root, &None),
+    Some(format!("{root}/0903/0903.2000/0903.2000.html"))
+  );
+  fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn can_report_unresolved_entries() {
+  let manifest = "paper_id,split\n0903.1000,train\n9999.0001,train\n\n1307.8133,test\n";
+  let sources = selection::listed_sources(
+    Cursor::new(manifest),
+    "tests/resources",
+    &None,
+    &WalkOptions::default(),
+  );
+  let (resolved, unresolved): (Vec<_>, Vec<_>) = sources.partition(|source| source.is_ok());
+  let resolved: Vec<String> = resolved
+    .into_iter()
+    .map(|source| source.unwrap().path().to_string())
+    .collect();
+  assert_eq!(
+    resolved,
+    vec![
+      "tests/resources/0903.1000.html",
+      "tests/resources/1307.8133.html"
+    ]
+  );
+  assert_eq!(unresolved.len(), 1);
+  let failure = unresolved[0].as_ref().unwrap_err();
+  assert_eq!(failure.path, "9999.0001");
+  assert_eq!(failure.kind, FailureKind::Unresolved);
+}
+
+#[test]
+fn can_match_glob_patterns() {
+  assert!(selection::glob_matches(
+    "0903/*.html",
+    "0903/0903.1000.html"
+  ));
+  assert!(!selection::glob_matches(
+    "0903/*.html",
+    "0903/0903.1000.xhtml"
+  ));
+  assert!(!selection::glob_matches("*.html", "0903/0903.1000.html"));
+  assert!(selection::glob_matches("**/*.html", "0903/0903.1000.html"));
+  assert!(selection::glob_matches("**/*.html", "0903.1000.html"));
+  assert!(selection::glob_matches(
+    "09??/**/09*.html",
+    "0903/a/b/0903.1000.html"
+  ));
+  assert!(!selection::glob_matches(
+    "09??/*.html",
+    "09031/0903.1000.html"
+  ));
+}
+
+#[test]
+fn can_report_missing_manifests() {
+  let selection = Selection::Manifest(String::from("tests/resources/no_such_manifest.csv"));
+  let sources: Vec<_> = selection
+    .sources("tests/resources", &None, &WalkOptions::default())
+    .collect();
+  assert_eq!(sources.len(), 1);
+  assert_eq!(sources[0].as_ref().unwrap_err().kind, FailureKind::Io);
+}
+
+#[test]
+fn can_select_glob_matches_once() {
+  let selection = Selection::Glob(vec![
+    String::from("*.html"),
+    String::from("0903*"),
+    String::from("/*/no_prefix.html"),
+  ]);
+  let (selected, failed): (Vec<_>, Vec<_>) = selection
+    .sources("tests/resources", &None, &WalkOptions::default())
+    .partition(|source| source.is_ok());
+  let mut selected: Vec<String> = selected
+    .into_iter()
+    .map(|source| source.unwrap().path().to_string())
+    .collect();
+  selected.sort();
+  assert_eq!(
+    selected,
+    vec![
+      "tests/resources/0903.1000.html",
+      "tests/resources/1307.8133.html",
+      "tests/resources/astro-ph9710163.html"
+    ]
+  );
+  // the pattern without a directory prefix is rejected, rather than walking from the root
+  assert_eq!(failed.len(), 1);
+  let failure = failed[0].as_ref().unwrap_err();
+  assert_eq!(failure.path, "/*/no_prefix.html");
+  assert_eq!(failure.kind, FailureKind::Unresolved);
+}
@@ -0,0 +1,154 @@
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use llamapun::parallel_data::source::{self, ArchiveFormat, DocumentSource};
+use llamapun::parallel_data::walk::{FailureKind, WalkOptions};
+use llamapun::parallel_data::*;
+use std::collections::HashMap;
+use std::env;
+use std::fs::{self, File};
+use std::io::Write;
+use tar::{Builder, Header};
+
+static PAPER: &str =
+  "<html><body><div class=\"ltx_para\"><p class=\"ltx_p\">Hello archive.</p></div>\
+                      </body></html>";
+
+fn write_tar<W: Write>(writer: W, entries: &[(&str, &str)]) -> W {
+  let mut builder = Builder::new(writer);
+  for (name, content) in entries {
+    let mut header = Header::new_gnu();
+    header.set_size(content.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder
+      .append_data(&mut header, name, content.as_bytes())
+      .unwrap();
+  }
+  builder.into_inner().unwrap()
+}
+
+#[test]
+fn can_detect_archive_formats() {
+  assert_eq!(
+    ArchiveFormat::of_path("arXMLiv_0903.zip"),
+    Some(ArchiveFormat::Zip)
+  );
+  assert_eq!(
+    ArchiveFormat::of_path("bundle.tar.gz"),
+    Some(ArchiveFormat::TarGz)
+  );
+  assert_eq!(
+    ArchiveFormat::of_path("bundle.tgz"),
+    Some(ArchiveFormat::TarGz)
+  );
+  assert_eq!(
+    ArchiveFormat::of_path("bundle.tar"),
+    Some(ArchiveFormat::Tar)
+  );
+  assert_eq!(
+    ArchiveFormat::of_path("0903.1000.html.gz"),
+    Some(ArchiveFormat::Gz)
+  );
+  assert_eq!(ArchiveFormat::of_path("0903.1000.html"), None);
+}
+
+#[test]
+fn can_read_tar_entries() {
+  let path = env::temp_dir().join("llamapun_source_test.tar");
+  let file = File::create(&path).unwrap();
+  write_tar(
+    file,
+    &[
+      ("0903/0903.1000.html", PAPER),
+      ("0903/README.txt", "not a document"),
+      ("0903/0903.1001.html", "<html></html>"),
+    ],
+  );
+  let options = WalkOptions {
+    max_file_size: Some(20),
+    ..WalkOptions::default()
+  };
+  let archive = path.to_str().unwrap();
+  let sources: Vec<_> = source::expand(archive, &None, &options).collect();
+  assert_eq!(sources.len(), 2);
+  let failure = sources[0].as_ref().unwrap_err();
+  assert_eq!(failure.path, "0903/0903.1000.html");
+  assert_eq!(failure.archive.as_deref(), Some(archive));
+  assert_eq!(failure.kind, FailureKind::TooLarge);
+  assert_eq!(failure.location(), format!("{archive}:0903/0903.1000.html"));
+  assert_eq!(
+    sources[1].as_ref().unwrap(),
+    &DocumentSource::Bytes {
+      path: String::from("0903/0903.1001.html"),
+      archive: Some(archive.to_string()),
+      content: b"<html></html>".to_vec(),
+    }
+  );
+  assert_eq!(sources[1].as_ref().unwrap().archive(), Some(archive));
+  fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn can_read_gzipped_files() {
+  let path = env::temp_dir().join("llamapun_source_test.html.gz");
+  let mut encoder = GzEncoder::new(File::create(&path).unwrap(), Compression::default());
+  encoder.write_all(PAPER.as_bytes()).unwrap();
+  encoder.finish().unwrap();
+  let gzipped = path.to_str().unwrap();
+  let sources: Vec<_> = source::expand(gzipped, &None, &WalkOptions::default()).collect();
+  assert_eq!(
+    sources[0].as_ref().unwrap(),
+    &DocumentSource::Bytes {
+      path: gzipped.trim_end_matches(".gz").to_string(),
+      archive: None,
+      content: PAPER.as_bytes().to_vec(),
+    }
+  );
+  fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn can_walk_archived_corpus() {
+  let path = env::temp_dir().join("llamapun_source_test.tar.gz");
+  let encoder = GzEncoder::new(File::create(&path).unwrap(), Compression::default());
+  write_tar(
+    encoder,
+    &[
+      ("0903/0903.1000.html", PAPER),
+      ("0903/0903.1001.xhtml", "<html/>"),
+    ],
+  )
+  .finish()
+  .unwrap();
+  let corpus = Corpus::new(path.to_str().unwrap().to_string());
+  let (catalog, report) = corpus.catalog_with_parallel_walk_report(|document| {
+    let mut catalog = HashMap::new();
+    catalog.insert(document.path.clone(), 1);
+    catalog
+  });
+  assert!(report.is_clean());
+  let mut paths: Vec<&String> = catalog.keys().collect();
+  paths.sort();
+  assert_eq!(paths, vec!["0903/0903.1000.html", "0903/0903.1001.xhtml"]);
+  fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn can_walk_into_directories_named_as_documents() {
+  let root = env::temp_dir().join("llamapun_source_test_directories");
+  let directory = root.join("chapter.html");
+  fs::create_dir_all(&directory).unwrap();
+  fs::write(directory.join("0903.1000.html"), PAPER).unwrap();
+  let corpus = Corpus::new(root.to_str().unwrap().to_string());
+  let (catalog, report) = corpus.catalog_with_parallel_walk_report(|document| {
+    let mut catalog = HashMap::new();
+    catalog.insert(document.path.clone(), 1);
+    catalog
+  });
+  assert!(report.is_clean());
+  assert_eq!(report.processed, 1);
+  assert!(catalog
+    .keys()
+    .all(|path| path.ends_with("chapter.html/0903.1000.html")));
+  fs::remove_dir_all(&root).unwrap();
+}
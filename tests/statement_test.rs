@@ -0,0 +1,123 @@
+use llamapun::ams::AmsEnv;
+use llamapun::parallel_data::*;
+
+#[test]
+fn can_iterate_statements() {
+  let corpus = Corpus::new(".".to_string());
+  let document = Document::new("tests/resources/1311.0066.xhtml".to_string(), &corpus).unwrap();
+  let statements: Vec<Statement> = document
+    .statement_iter(StatementOptions::default())
+    .collect();
+  // 73 AMS environments and 17 proofs
+  assert_eq!(statements.len(), 90);
+  assert_eq!(
+    statements
+      .iter()
+      .filter(|statement| statement.env == AmsEnv::Proof)
+      .count(),
+    17
+  );
+
+  let question = &statements[0];
+  assert_eq!(question.env, AmsEnv::Question);
+  assert_eq!(question.id.as_deref(), Some("S1.Thmtheorem1"));
+  assert_eq!(question.tag.as_deref(), Some("Question 1.1"));
+  assert_eq!(question.number.as_deref(), Some("1.1"));
+  assert_eq!(question.title, None);
+  assert_eq!(question.paragraphs.len(), 2);
+  assert_eq!(question.paragraph_iter().count(), 2);
+  assert!(question.dnm.plaintext.contains("Green-Griffiths"));
+  assert!(!question.dnm.plaintext.contains("Question 1.1"));
+
+  let definition = statements
+    .iter()
+    .find(|statement| statement.id.as_deref() == Some("S2.Thmtheorem1"))
+    .unwrap();
+  assert_eq!(definition.env, AmsEnv::Definition);
+  assert_eq!(definition.number.as_deref(), Some("2.1"));
+  assert_eq!(definition.title.as_deref(), Some("6"));
+}
+
+#[test]
+fn can_filter_statements() {
+  let corpus = Corpus::new(".".to_string());
+  let document = Document::new("tests/resources/1311.0066.xhtml".to_string(), &corpus).unwrap();
+  let options = StatementOptions {
+    excluded_envs: vec![AmsEnv::Proof, AmsEnv::Example],
+    ..StatementOptions::default()
+  };
+  assert_eq!(document.statement_iter(options).count(), 70);
+
+  let mut statements = document.statement_iter(StatementOptions::paragraph_model(false));
+  let mut seen = 0;
+  for statement in statements.by_ref() {
+    seen += 1;
+    assert_eq!(statement.paragraphs.len(), 1);
+    let words = statement.words.unwrap();
+    assert!((4..=1024).contains(&words.len()));
+  }
+  assert!(seen > 0);
+  assert!(seen + statements.discarded() <= 90);
+}
+
+#[test]
+fn can_infer_unmarked_statements() {
+  let corpus = Corpus::new(".".to_string());
+  let document = Document::new(
+    "tests/fixtures/unmarked_statements.html".to_string(),
+    &corpus,
+  )
+  .unwrap();
+  assert_eq!(
+    document.statement_iter(StatementOptions::default()).count(),
+    1
+  );
+
+  let options = StatementOptions {
+    infer_unmarked: true,
+    ..StatementOptions::default()
+  };
+  let statements: Vec<Statement> = document.statement_iter(options).collect();
+  let found: Vec<(AmsEnv, Option<&str>, bool)> = statements
+    .iter()
+    .map(|statement| (statement.env, statement.tag.as_deref(), statement.inferred))
+    .collect();
+  assert_eq!(
+    found,
+    vec![
+      (AmsEnv::Theorem, Some("Theorem 1"), true),
+      (AmsEnv::Proof, Some("Proof"), true),
+      (AmsEnv::Lemma, Some("Lemma 2.3"), true),
+      (AmsEnv::Definition, Some("Definition 3"), true),
+      (AmsEnv::Remark, Some("Remark 1.4"), false),
+    ]
+  );
+  assert_eq!(statements[0].id.as_deref(), Some("S1.p2"));
+  assert_eq!(statements[0].number.as_deref(), Some("1"));
+  assert_eq!(statements[2].title.as_deref(), Some("Zorn"));
+  let words = statements[0].words.as_ref().unwrap();
+  assert_eq!(words[0], "every");
+  assert_eq!(statements[3].paragraphs.len(), 1);
+  assert!(!statements[3].dnm.plaintext.contains("Definition"));
+}
+
+#[test]
+fn numbers_only_numbered_tags() {
+  let corpus = Corpus::new(".".to_string());
+  let document = Document::new("tests/fixtures/statement_tags.html".to_string(), &corpus).unwrap();
+  let numbered: Vec<(Option<String>, Option<String>)> = document
+    .statement_iter(StatementOptions::default())
+    .map(|statement| (statement.tag, statement.number))
+    .collect();
+  let expected = [
+    ("Main Theorem", None),
+    ("Theorem A", Some("A")),
+    ("Lemma IV", Some("IV")),
+    ("Corollary 1.4", Some("1.4")),
+  ];
+  assert_eq!(numbered.len(), expected.len());
+  for ((tag, number), (expected_tag, expected_number)) in numbered.iter().zip(expected.iter()) {
+    assert_eq!(tag.as_deref(), Some(*expected_tag));
+    assert_eq!(number.as_deref(), *expected_number);
+  }
+}
@@ -1,5 +1,11 @@
 extern crate llamapun;
+use llamapun::dnm::DNMRange;
+use llamapun::ngrams::Ngrams;
 use llamapun::stopwords;
+use llamapun::stopwords::{DocumentFrequencies, Stopwords};
+use llamapun::tokenizer::Tokenizer;
+use llamapun::util::test::ascii_dnm;
+use std::sync::Arc;
 
 #[test]
 fn can_load_stopwords() {
@@ -14,3 +20,120 @@ fn can_use_stopwords() {
   assert!(stopwords.contains("about"));
   assert!(!stopwords.contains("equation"));
 }
+
+#[test]
+fn can_combine_stopwords() {
+  let english = stopwords::english();
+  assert!(english.contains("the"));
+  assert_eq!(english.language, "en");
+  assert_eq!(english.len(), stopwords::load().len());
+  assert!(std::sync::Arc::ptr_eq(&english, &Stopwords::builtin("english").unwrap()));
+  assert!(Stopwords::builtin("klingon").is_none());
+
+  let paper = Stopwords::from_words("en", &["in  this paper", "we show", "the"]);
+  assert!(paper.contains("in this paper"));
+  let combined = english.union(&paper);
+  assert_eq!(combined.len(), english.len() + 2);
+  assert!(combined.contains("we show") && combined.contains("about"));
+  let phrases_only = paper.difference(&english);
+  assert_eq!(phrases_only.len(), 2);
+  assert!(!phrases_only.contains("the"));
+
+  assert!(paper.is_stop_sequence(&["the", "proof"]));
+  assert!(paper.is_stop_sequence(&["we", "show"]));
+  assert!(!paper.is_stop_sequence(&["main", "result"]));
+}
+
+#[test]
+fn can_load_stopwords_file() {
+  let path = std::env::temp_dir().join("llamapun_stopwords_test.txt");
+  std::fs::write(&path, "# German\nder\ndie\n\ndas\n").unwrap();
+  let german = Stopwords::load("de", path.to_str().unwrap()).unwrap();
+  assert_eq!(german.language, "de");
+  assert_eq!(german.len(), 3);
+  assert!(german.contains("die") && !german.contains("# German"));
+  assert!(Stopwords::load("de", "tests/resources/missing_stopwords.txt").is_err());
+}
+
+#[test]
+fn can_derive_stopwords_from_corpus() {
+  let documents = [
+    "in this paper we show that every group is nice",
+    "in this paper we study a ring",
+    "we show a bound on the ring",
+    "in this paper we prove that groups are fun",
+  ];
+  let mut frequencies = DocumentFrequencies::new(3);
+  for document in documents.iter() {
+    let words: Vec<&str> = document.split(' ').collect();
+    frequencies.add_document(&words);
+  }
+  assert_eq!(frequencies.document_count, 4);
+  assert_eq!(frequencies.document_frequency("in this paper"), 3);
+  assert_eq!(frequencies.document_frequency("ring"), 2);
+
+  let frequent = frequencies.stopwords_by_document_frequency("en", 0.75);
+  assert!(frequent.contains("in this paper"));
+  assert!(frequent.contains("we"));
+  assert!(!frequent.contains("we show"));
+  assert!(!frequent.contains("ring"));
+
+  // "we" is in every document, so its inverse document frequency is 0
+  assert_eq!(frequencies.tfidf("we"), 0.0);
+  assert!(frequencies.tfidf("in this paper") < frequencies.tfidf("ring"));
+  let flat = frequencies.stopwords_by_tfidf("en", 0.0, 0.5);
+  assert!(flat.contains("we"));
+  assert!(!flat.contains("group"));
+  // whatever their TF-IDF, the words of a single document, e.g. "nice", are left out
+  let spread = frequencies.stopwords_by_tfidf("en", f64::INFINITY, 0.5);
+  assert!(spread.contains("ring"));
+  assert!(!spread.contains("nice"));
+}
+
+#[test]
+fn can_derive_stopwords_from_quantity_tokens() {
+  let tokenizer = Tokenizer::default();
+  let mut frequencies = DocumentFrequencies::new(3);
+  for text in [
+    "The wind reached 3.5 km/s at 10^{-3} bar.",
+    "The wind reached 2 km/s in the 2nd run.",
+  ] {
+    let dnm = ascii_dnm(text);
+    let range = DNMRange {
+      start: 0,
+      end: text.len(),
+      dnm: &dnm,
+    };
+    let words: Vec<String> = tokenizer
+      .words_punct_and_quantities(&range)
+      .iter()
+      .map(|word| word.get_plaintext().to_lowercase())
+      .collect();
+    assert!(words.iter().any(|word| word.ends_with(" km/s")));
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    frequencies.add_document(&words);
+  }
+  assert_eq!(frequencies.document_frequency("the wind reached"), 2);
+  assert_eq!(frequencies.document_frequency("3.5 km/s"), 0);
+  let flat = frequencies.stopwords_by_tfidf("en", 0.0, 1.0);
+  assert!(flat.contains("the wind reached"));
+  assert!(!flat.contains("at"));
+}
+
+#[test]
+fn stopwords_plug_into_ngrams_and_tokenizer() {
+  let stopwords = Arc::new(Stopwords::from_words("en", &["the", "we show"]));
+  let mut ngrams = Ngrams {
+    n: 2,
+    stopwords: Some(Arc::clone(&stopwords)),
+    ..Ngrams::default()
+  };
+  ngrams.record_words(vec!["we", "show", "the", "main", "result"]);
+  assert_eq!(ngrams.get("we show"), 0);
+  assert_eq!(ngrams.get("show the"), 0);
+  assert_eq!(ngrams.get("main result"), 1);
+
+  let tokenizer = Tokenizer::with_stopwords(stopwords);
+  assert!(tokenizer.stopwords().contains("we show"));
+  assert!(!tokenizer.stopwords().contains("about"));
+}
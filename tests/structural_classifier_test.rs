@@ -0,0 +1,115 @@
+use llamapun::ams::structural_classifier::*;
+use llamapun::ams::StructuralEnv;
+use llamapun::parallel_data::*;
+
+fn example(heading: &str, content: &str) -> SectionExample {
+  let mut features: Vec<String> = words(heading)
+    .into_iter()
+    .map(|word| format!("{HEADING_PREFIX}{word}"))
+    .collect();
+  features.extend(words(content));
+  SectionExample {
+    env: StructuralEnv::from(heading),
+    heading: heading.to_string(),
+    features,
+  }
+}
+
+#[test]
+fn can_classify_with_default_model() {
+  let classifier = StructuralClassifier::default();
+  // trained on the labelled sections of tests/resources, with their content words
+  assert_eq!(classifier.class_count(), 9);
+  assert!(classifier.vocabulary_size() > 500);
+  let (env, _) = classifier.predict(&["h:introduction"]).unwrap();
+  assert_eq!(env, StructuralEnv::Introduction);
+  // headings which map cleanly are kept
+  let introduction = example("Introduction", "");
+  assert_eq!(
+    classifier.classify(&introduction, 0.5),
+    StructuralEnv::Introduction
+  );
+  // without any known feature, the prediction falls back to the priors
+  let unknown = example("Xyzzy", "");
+  assert_eq!(unknown.env, StructuralEnv::Other);
+  assert_eq!(classifier.classify(&unknown, 0.5), StructuralEnv::Other);
+}
+
+#[test]
+fn can_train_and_evaluate() {
+  let mut classifier = StructuralClassifier::new();
+  classifier.add_example(
+    StructuralEnv::Introduction,
+    &words("in this paper we study the problem and outline our contributions"),
+  );
+  let mut other = StructuralClassifier::new();
+  other.add_example(
+    StructuralEnv::Conclusion,
+    &words("we have shown that the method works and conclude with open directions"),
+  );
+  classifier.merge(other);
+  assert_eq!(classifier.class_count(), 2);
+  let (env, probability) = classifier
+    .predict(&words("this paper studies a problem"))
+    .unwrap();
+  assert_eq!(env, StructuralEnv::Introduction);
+  assert!(probability > 0.5);
+  let probabilities = classifier.probabilities(&words("we conclude"));
+  assert_eq!(probabilities[0].0, StructuralEnv::Conclusion);
+  let total: f64 = probabilities.iter().map(|(_, p)| p).sum();
+  assert!((total - 1.0).abs() < 1e-9);
+
+  let path = std::env::temp_dir().join("llamapun_structural_model.tsv");
+  classifier.save(path.to_str().unwrap()).unwrap();
+  let loaded = StructuralClassifier::load(path.to_str().unwrap()).unwrap();
+  assert_eq!(loaded.vocabulary_size(), classifier.vocabulary_size());
+  assert_eq!(
+    loaded.predict(&words("we conclude")),
+    classifier.predict(&words("we conclude"))
+  );
+  std::fs::remove_file(&path).unwrap();
+  assert!(StructuralClassifier::new()
+    .add_model("examples\tnot an env\t1")
+    .is_err());
+
+  let mut evaluation = Evaluation::default();
+  evaluation.add(StructuralEnv::Introduction, StructuralEnv::Introduction);
+  evaluation.add(StructuralEnv::Introduction, StructuralEnv::Conclusion);
+  evaluation.add(StructuralEnv::Conclusion, StructuralEnv::Conclusion);
+  assert_eq!(evaluation.total(), 3);
+  assert!((evaluation.accuracy() - 2.0 / 3.0).abs() < 1e-9);
+  assert!((evaluation.precision(StructuralEnv::Conclusion) - 0.5).abs() < 1e-9);
+  assert!((evaluation.recall(StructuralEnv::Introduction) - 0.5).abs() < 1e-9);
+  assert!(evaluation.macro_f1() > 0.6);
+  let mut merged = Evaluation::default();
+  assert_eq!(merged.accuracy(), 1.0);
+  merged.merge(evaluation);
+  assert_eq!(merged.total(), 3);
+
+  // every document is held out in exactly one fold
+  let options = TrainingOptions::default();
+  let held_out = (0..options.holdout)
+    .filter(|fold| {
+      TrainingOptions {
+        fold: *fold,
+        ..options
+      }
+      .is_held_out("tests/resources/0903.1000.html")
+    })
+    .count();
+  assert_eq!(held_out, 1);
+}
+
+#[test]
+fn can_collect_section_examples() {
+  let corpus = Corpus::new(".".to_string());
+  let document = Document::new("tests/resources/1311.0066.xhtml".to_string(), &corpus).unwrap();
+  let examples = SectionExample::from_document(&document, 2);
+  assert_eq!(examples.len(), 16);
+  assert_eq!(examples[0].env, StructuralEnv::Introduction);
+  assert_eq!(examples[0].features[0], "h:introduction");
+  assert!(examples[0].content_features().len() > 10);
+  let mut classifier = StructuralClassifier::new();
+  classifier.add_document(&document, 2);
+  assert!(classifier.class_count() >= 2);
+}
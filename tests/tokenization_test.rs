@@ -54,6 +54,126 @@ fn test_sentence_tokenization_simple() {
   }
 }
 
+#[test]
+/// The lazy iterators must yield the ranges of the original, `Vec`-building, tokenization passes
+fn test_lazy_iterators_match_eager() {
+  let text = "Deciphering Eqn. 1 is sometimes. difficult Prof. Automation, isn't it? \
+    Consider Eqn. 1, in light of eqs. 2 and 3. Then it is clear that eq. 1 is indeed correct.\n\n\
+    Let mathformula be a scheme. Balmer's reconstruction theorem [1,Theorem 6.3] opens the land."
+    .to_string();
+  let text_len = text.len();
+  let dnm = DNM {
+    plaintext: text,
+    byte_offsets: (0usize..=text_len).collect(), // it's just ascii
+    parameters: DNMParameters::llamapun_normalization(),
+    ..DNM::default()
+  };
+  let tokenizer = Tokenizer::default();
+  let offsets = |ranges: Vec<DNMRange>| -> Vec<(usize, usize)> {
+    ranges.iter().map(|r| (r.start, r.end)).collect()
+  };
+
+  // the ranges of the original tokenizer
+  let sentences: Vec<DNMRange> = tokenizer.sentence_iter(&dnm).collect();
+  assert_eq!(
+    offsets(sentences.clone()),
+    vec![(0, 70), (71, 113), (114, 160), (162, 190), (191, 254)]
+  );
+  assert_eq!(
+    offsets(tokenizer.sentences(&dnm)),
+    offsets(sentences.clone())
+  );
+  assert_eq!(
+    offsets(tokenizer.word_iter(&sentences[0]).collect()),
+    vec![
+      (0, 11),
+      (12, 15),
+      (17, 18),
+      (19, 21),
+      (22, 31),
+      (33, 42),
+      (43, 47),
+      (49, 59),
+      (61, 64),
+      (65, 66),
+      (67, 69)
+    ]
+  );
+  assert_eq!(
+    offsets(tokenizer.word_and_punct_iter(&sentences[0]).collect()),
+    vec![
+      (0, 11),
+      (12, 15),
+      (15, 16),
+      (17, 18),
+      (19, 21),
+      (22, 31),
+      (31, 32),
+      (33, 42),
+      (43, 47),
+      (47, 48),
+      (49, 59),
+      (59, 60),
+      (61, 64),
+      (64, 66),
+      (67, 69),
+      (69, 70)
+    ]
+  );
+  assert_eq!(
+    offsets(tokenizer.word_iter(&sentences[4]).collect()),
+    vec![
+      (191, 199),
+      (200, 214),
+      (215, 222),
+      (224, 225),
+      (226, 233),
+      (234, 235),
+      (236, 237),
+      (239, 244),
+      (245, 248),
+      (249, 253)
+    ]
+  );
+  assert_eq!(
+    offsets(tokenizer.word_and_punct_iter(&sentences[4]).collect()),
+    vec![
+      (191, 197),
+      (197, 199),
+      (200, 214),
+      (215, 222),
+      (223, 224),
+      (224, 225),
+      (225, 226),
+      (226, 233),
+      (234, 235),
+      (235, 236),
+      (236, 237),
+      (237, 238),
+      (239, 244),
+      (245, 248),
+      (249, 253),
+      (253, 254)
+    ]
+  );
+  for sentence in sentences.iter() {
+    assert_eq!(
+      offsets(tokenizer.words(sentence)),
+      offsets(tokenizer.word_iter(sentence).collect())
+    );
+    assert_eq!(
+      offsets(tokenizer.words_and_punct(sentence)),
+      offsets(tokenizer.word_and_punct_iter(sentence).collect())
+    );
+  }
+  // the iterators are lazy, so partial consumption is possible
+  let first_two: Vec<DNMRange> = tokenizer.sentence_iter(&dnm).take(2).collect();
+  assert_eq!(
+    first_two[1].get_plaintext(),
+    "Consider Eqn. 1, in light of eqs. 2 and 3."
+  );
+}
+
 #[test]
 /// Test sentence tokenization of an `arXMLiv` XHTML document
 fn test_sentence_tokenization_arxmliv_xhtml() {
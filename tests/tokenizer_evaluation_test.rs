@@ -0,0 +1,95 @@
+use llamapun::dnm::{DNMParameters, DNMRange};
+use llamapun::tokenizer::evaluation::*;
+use llamapun::tokenizer::Tokenizer;
+use llamapun::util::test::ascii_dnm;
+
+#[test]
+fn can_parse_gold_standard() {
+  let gold = GoldStandard::parse(
+    "# document\tlevel\tspan\n\
+     a.html\tsentence\t0\t12\n\
+     a.html\ttoken\t0\t4\n\
+     b.xhtml\ttoken\tarange(//*[@id=\"p1\"]/text()[1],string-index(//*[@id=\"p1\"]/text()[1],4))\n",
+  )
+  .unwrap();
+  assert_eq!(gold.documents.len(), 2);
+  let a = &gold.documents["a.html"];
+  assert_eq!(a.spans(Level::Sentence), &[GoldSpan::Offsets(0, 12)]);
+  assert_eq!(a.spans(Level::Token), &[GoldSpan::Offsets(0, 4)]);
+  let b = &gold.documents["b.xhtml"];
+  assert!(b.sentences.is_empty());
+  assert!(matches!(b.tokens[0], GoldSpan::XPointer(_)));
+
+  assert!(GoldStandard::parse("a.html\tparagraph\t0\t12").is_err());
+  assert!(GoldStandard::parse("a.html\tsentence\t0").is_err());
+}
+
+#[test]
+fn can_evaluate_sentence_boundaries() {
+  let text = "We follow Smith et al. (2004) here. It works. Then we stop.";
+  let dnm = ascii_dnm(text);
+  let span = |sentence: &str| {
+    let start = text.find(sentence).unwrap();
+    DNMRange {
+      start,
+      end: start + sentence.len(),
+      dnm: &dnm,
+    }
+  };
+  let gold = vec![
+    span("We follow Smith et al. (2004) here."),
+    span("It works."),
+    span("Then we stop."),
+  ];
+  let perfect = evaluate_ranges("doc", &gold, &Tokenizer::default().sentences(&dnm));
+  assert_eq!(perfect.true_positives, 6);
+  assert!(perfect.disagreements.is_empty());
+  assert_eq!(perfect.f1(), 1.0);
+
+  // a naive splitter on every period breaks "et al." and misses nothing else
+  let naive = vec![
+    span("We follow Smith et al."),
+    span("(2004) here."),
+    span("It works."),
+    span("Then we stop."),
+  ];
+  let evaluation = evaluate_ranges("doc", &gold, &naive);
+  assert_eq!(evaluation.true_positives, 6);
+  assert_eq!(evaluation.false_positives, 2);
+  assert_eq!(evaluation.false_negatives, 0);
+  assert_eq!(evaluation.precision(), 0.75);
+  assert_eq!(evaluation.recall(), 1.0);
+  assert!((evaluation.f1() - 6.0 / 7.0).abs() < 1e-12);
+  assert_eq!(evaluation.disagreements.len(), 2);
+  assert_eq!(evaluation.disagreements[0].kind, DisagreementKind::Spurious);
+  assert_eq!(evaluation.disagreements[0].offset, 22);
+  assert!(evaluation.disagreements[0].context.contains("et al.|"));
+
+  let mut total = perfect;
+  total.merge(evaluation);
+  assert_eq!(total.true_positives, 12);
+  assert!(total
+    .to_string()
+    .starts_with("precision: 0.8571, recall: 1.0000"));
+}
+
+#[test]
+fn skips_unresolved_gold_xpointers() {
+  let gold = GoldStandard::parse(
+    "tests/resources/0903.1000.html\tsentence\t0\t12\n\
+     tests/resources/0903.1000.html\tsentence\tarange(//*[@id=\"p1\"]\n\
+     tests/resources/0903.1000.html\tsentence\tarange(//*[@id=\"nowhere\"],//*[@id=\"nowhere\"])\n",
+  )
+  .unwrap();
+  let tokenizer = Tokenizer::default();
+  let evaluation = evaluate(
+    &gold,
+    Level::Sentence,
+    &DNMParameters::llamapun_normalization(),
+    |dnm| tokenizer.sentences(dnm),
+  )
+  .unwrap();
+  assert_eq!(evaluation.unresolved.len(), 2);
+  assert!(evaluation.unresolved[1].contains("selects 0 nodes"));
+  assert!(evaluation.to_string().contains("unresolved gold span"));
+}
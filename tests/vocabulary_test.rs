@@ -0,0 +1,129 @@
+extern crate llamapun;
+use llamapun::vocabulary::{Vocabulary, VocabularyFormat, SPECIAL_TOKENS, UNKNOWN, UNKNOWN_ID};
+
+fn example_vocabulary() -> Vocabulary {
+  let mut vocabulary = Vocabulary::new();
+  vocabulary.add_document("the group acts on the set mathformula".split(' '));
+  vocabulary.add_document("the set is finite".split(' '));
+  vocabulary.add_document("the group has NUM elements".split(' '));
+  vocabulary
+}
+
+#[test]
+fn can_count_words() {
+  let vocabulary = example_vocabulary();
+  assert_eq!(vocabulary.count("the"), 4);
+  assert_eq!(vocabulary.document_frequency("the"), 3);
+  assert_eq!(vocabulary.document_frequency("set"), 2);
+  assert_eq!(vocabulary.document_count(), 3);
+  assert_eq!(vocabulary.total_count(), 16);
+  // the reserved tokens take the first ids, and ids map both ways
+  for (id, token) in SPECIAL_TOKENS.iter().enumerate() {
+    assert_eq!(vocabulary.id(token), Some(id));
+    assert!(vocabulary.is_special(token));
+  }
+  let id = vocabulary.id("group").unwrap();
+  assert_eq!(vocabulary.word(id), Some("group"));
+  assert_eq!(
+    vocabulary.id_or_unknown("ring"),
+    vocabulary.id(UNKNOWN).unwrap()
+  );
+
+  let mut merged = Vocabulary::new();
+  merged.add_document(["ring", "the"]);
+  merged.merge(example_vocabulary());
+  assert_eq!(merged.count("the"), 5);
+  assert_eq!(merged.document_frequency("the"), 4);
+  assert_eq!(merged.id("ring"), Some(4));
+}
+
+#[test]
+fn can_prune_vocabulary() {
+  let mut vocabulary = example_vocabulary();
+  // "the" is in every document, the words seen once are rare
+  vocabulary.prune(2, 0.9);
+  let words: Vec<&str> = vocabulary.iter().map(|(_, word, _)| word).collect();
+  assert_eq!(
+    words,
+    vec![
+      UNKNOWN,
+      "NUM",
+      "citationelement",
+      "mathformula",
+      "group",
+      "set"
+    ]
+  );
+  assert_eq!(vocabulary.count(UNKNOWN), 16 - 1 - 1 - 2 - 2);
+  assert_eq!(vocabulary.count("the"), 0);
+  assert_eq!(vocabulary.total_count(), 16);
+
+  let mut truncated = example_vocabulary();
+  truncated.truncate(2);
+  let words: Vec<&str> = truncated.iter().skip(4).map(|(_, word, _)| word).collect();
+  assert_eq!(words, vec!["the", "group"]);
+  assert_eq!(truncated.id("group"), Some(5));
+}
+
+#[test]
+fn can_export_vocabulary() {
+  let mut vocabulary = example_vocabulary();
+  vocabulary.prune(2, 1.0);
+  let export = |format| {
+    let mut output = Vec::new();
+    vocabulary.write(&mut output, format).unwrap();
+    String::from_utf8(output).unwrap()
+  };
+  assert_eq!(
+    export(VocabularyFormat::Word2Vec),
+    "<unk> 6\nNUM 1\ncitationelement 0\nmathformula 1\nthe 4\ngroup 2\nset 2\n"
+  );
+  assert!(export(VocabularyFormat::Glove).starts_with("NUM 1\n"));
+  assert!(export(VocabularyFormat::FastText).starts_with("7\n<unk> 6 word\n"));
+  assert_eq!(
+    export(VocabularyFormat::HuggingFaceText),
+    "<unk>\nNUM\ncitationelement\nmathformula\nthe\ngroup\nset\n"
+  );
+  #[cfg(feature = "json")]
+  {
+    let exported = export(VocabularyFormat::HuggingFaceJson);
+    assert!(exported.starts_with("{\n  \"<unk>\": 0,\n  \"NUM\": 1,"));
+    let json: serde_json::Value = serde_json::from_str(&exported).unwrap();
+    assert_eq!(json["<unk>"], 0);
+    assert_eq!(json["set"], 6);
+    assert_eq!(json.as_object().unwrap().len(), 7);
+  }
+  #[cfg(not(feature = "json"))]
+  assert!(vocabulary
+    .write(&mut Vec::new(), VocabularyFormat::HuggingFaceJson)
+    .is_err());
+}
+
+#[test]
+fn can_skip_spaced_words_in_line_formats() {
+  let mut vocabulary = Vocabulary::with_special_tokens(&["[UNK]"]);
+  vocabulary.add_document(["speed", "3.5 km/s", "speed"]);
+  let export = |format| {
+    let mut output = Vec::new();
+    vocabulary.write(&mut output, format).unwrap();
+    String::from_utf8(output).unwrap()
+  };
+  assert_eq!(export(VocabularyFormat::Word2Vec), "[UNK] 0\nspeed 2\n");
+  assert_eq!(export(VocabularyFormat::Glove), "speed 2\n");
+  assert_eq!(
+    export(VocabularyFormat::FastText),
+    "2\n[UNK] 0 word\nspeed 2 word\n"
+  );
+  vocabulary.add_word("line\nbreak");
+  assert!(vocabulary
+    .write(&mut Vec::new(), VocabularyFormat::HuggingFaceText)
+    .is_err());
+}
+
+#[test]
+fn can_reserve_unknown_word() {
+  let vocabulary = Vocabulary::with_special_tokens::<&str>(&[]);
+  assert_eq!(vocabulary.word(UNKNOWN_ID), Some(UNKNOWN));
+  assert_eq!(vocabulary.id_or_unknown("unseen"), UNKNOWN_ID);
+  assert!(vocabulary.is_special(UNKNOWN));
+}
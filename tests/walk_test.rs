@@ -0,0 +1,115 @@
+use llamapun::parallel_data::walk::{self, DocumentFailure, FailureKind, WalkOptions, WalkReport};
+use llamapun::parallel_data::*;
+use llamapun::util::test::RESOURCE_DOCUMENTS;
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn can_report_failures() {
+  let mut report = WalkReport::default();
+  assert_eq!(report.record::<u64>(Ok(1)), Some(1));
+  let failure = DocumentFailure {
+    path: String::from("broken.html"),
+    archive: None,
+    kind: FailureKind::Parse,
+    message: String::from("unexpected\tend"),
+    attempts: 2,
+  };
+  assert_eq!(report.record::<u64>(Err(failure)), None);
+  let mut other = WalkReport::default();
+  other.record::<u64>(Ok(2));
+  report.merge(other);
+  assert_eq!(report.processed, 2);
+  assert!(!report.is_clean());
+  assert_eq!(report.failure_counts().get("parse"), Some(&1));
+  assert_eq!(
+    report.summary(),
+    "2 documents processed, 1 failed (1 parse)"
+  );
+  let mut written = Vec::new();
+  report.write(&mut written).unwrap();
+  assert_eq!(
+    String::from_utf8(written).unwrap(),
+    "broken.html\tparse\t2\tunexpected end\n"
+  );
+}
+
+#[test]
+fn can_walk_past_failing_documents() {
+  let mut corpus = Corpus::new("tests/resources".to_string());
+  corpus.walk_options = WalkOptions {
+    max_file_size: Some(1_000_000),
+    ..WalkOptions::default()
+  };
+  let (catalog, report) = corpus.catalog_with_parallel_walk_report(|document| {
+    if document.path.ends_with("1307.8133.html") {
+      panic!("failing on purpose");
+    }
+    let mut catalog = HashMap::new();
+    catalog.insert(String::from("doc_count"), 1);
+    catalog
+  });
+  // 0903.1000.html is too large, 1307.8133.html panics
+  let processed = RESOURCE_DOCUMENTS.len() - 2;
+  assert_eq!(catalog.get("doc_count"), Some(&(processed as u64)));
+  assert_eq!(report.processed, processed);
+  let mut failures: Vec<(&str, &FailureKind)> = report
+    .failures
+    .iter()
+    .map(|failure| (failure.path.rsplit('/').next().unwrap(), &failure.kind))
+    .collect();
+  failures.sort_by_key(|(path, _)| *path);
+  assert_eq!(
+    failures,
+    vec![
+      ("0903.1000.html", &FailureKind::TooLarge),
+      ("1307.8133.html", &FailureKind::Panic),
+    ]
+  );
+  assert!(report
+    .failures
+    .iter()
+    .all(|failure| !failure.message.is_empty()));
+}
+
+#[test]
+fn can_skip_documents_which_panic_while_loading() {
+  let failure =
+    walk::load_with_retries::<(), String, _>("broken.html", &WalkOptions::default(), || {
+      panic!("failing to load")
+    })
+    .unwrap_err();
+  assert_eq!(failure.kind, FailureKind::Panic);
+  assert_eq!(failure.message, "failing to load");
+  assert_eq!(failure.attempts, 1);
+}
+
+#[test]
+fn can_discard_slow_documents() {
+  let mut corpus = Corpus::new("tests/resources".to_string());
+  corpus.walk_options = WalkOptions {
+    max_duration: Some(Duration::from_millis(50)),
+    ..WalkOptions::default()
+  };
+  let (catalog, report) = corpus.catalog_with_parallel_walk_report(|document| {
+    if document.path.ends_with("1307.8133.html") {
+      // not interrupted, only discarded once done
+      thread::sleep(Duration::from_millis(200));
+    }
+    let mut catalog = HashMap::new();
+    catalog.insert(String::from("doc_count"), 1);
+    catalog
+  });
+  let slow: Vec<&DocumentFailure> = report
+    .failures
+    .iter()
+    .filter(|failure| failure.kind == FailureKind::TooSlow)
+    .collect();
+  assert_eq!(slow.len(), 1);
+  assert!(slow[0].path.ends_with("1307.8133.html"));
+  assert_eq!(
+    catalog.get("doc_count").copied().unwrap_or(0),
+    report.processed as u64
+  );
+}